@@ -0,0 +1,217 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use tar::{EntryType, Header};
+
+use super::error::{BuildError, BuildResult, IoResultExt};
+use super::reproducible::sorted_entries;
+use crate::i18n::gettext;
+
+// Arquivo tar com dono root e entradas em ordem de nome. Com `mtime` (builds
+// reprodutíveis), todas as entradas recebem a mesma data
+pub(super) struct TarWriter<W: Write> {
+    builder: tar::Builder<W>,
+    mtime: Option<u64>,
+    // Caminho do arquivo gravado, para as mensagens de erro
+    path: PathBuf,
+}
+
+impl<W: Write> TarWriter<W> {
+    pub(super) fn new(output: W, path: &Path, mtime: Option<u64>) -> Self {
+        let mut builder = tar::Builder::new(output);
+        builder.follow_symlinks(false);
+        TarWriter {
+            builder,
+            mtime,
+            path: path.to_path_buf(),
+        }
+    }
+
+    // Adiciona `source` (e, se for diretório, todo o conteúdo) com o nome `name`.
+    // `skip` recebe os caminhos relativos a `source` e descarta os que retornar true
+    pub(super) fn append_tree(&mut self, source: &Path, name: &Path, skip: &dyn Fn(&Path) -> bool) -> BuildResult<()> {
+        self.append_tree_inner(source, source, name, skip)
+    }
+
+    fn append_tree_inner(
+        &mut self,
+        root: &Path,
+        source: &Path,
+        name: &Path,
+        skip: &dyn Fn(&Path) -> bool,
+    ) -> BuildResult<()> {
+        let meta = fs::symlink_metadata(source).at_path(source)?;
+        let mut header = self.header(&meta);
+
+        if meta.file_type().is_symlink() {
+            let target = fs::read_link(source).at_path(source)?;
+            header.set_entry_type(EntryType::Symlink);
+            header.set_size(0);
+            self.builder
+                .append_link(&mut header, name, &target)
+                .at_path(&self.path)?;
+        } else if meta.is_dir() {
+            header.set_entry_type(EntryType::Directory);
+            header.set_size(0);
+            // Diretórios terminam em '/', como no GNU tar
+            let mut dir_name = name.as_os_str().to_os_string();
+            dir_name.push("/");
+            self.builder
+                .append_data(&mut header, &dir_name, io::empty())
+                .at_path(&self.path)?;
+            for child in sorted_entries(source)? {
+                let relative = child.strip_prefix(root).unwrap_or(&child);
+                if skip(relative) {
+                    continue;
+                }
+                let child_name = name.join(child.file_name().unwrap_or_default());
+                self.append_tree_inner(root, &child, &child_name, skip)?;
+            }
+        } else {
+            let file = File::open(source).at_path(source)?;
+            header.set_entry_type(EntryType::Regular);
+            header.set_size(meta.len());
+            self.builder.append_data(&mut header, name, file).at_path(&self.path)?;
+        }
+        Ok(())
+    }
+
+    // Arquivo gerado em memória (control, launcher, install.sh...)
+    pub(super) fn append_bytes(&mut self, name: &str, mode: u32, contents: &[u8]) -> BuildResult<()> {
+        let mut header = Header::new_gnu();
+        header.set_entry_type(EntryType::Regular);
+        header.set_mode(mode);
+        header.set_size(contents.len() as u64);
+        header.set_mtime(self.mtime.unwrap_or_else(now));
+        set_root_owner(&mut header)?;
+        self.builder
+            .append_data(&mut header, name, contents)
+            .at_path(&self.path)
+    }
+
+    pub(super) fn finish(self) -> BuildResult<W> {
+        self.builder.into_inner().at_path(&self.path)
+    }
+
+    fn header(&self, meta: &fs::Metadata) -> Header {
+        let mut header = Header::new_gnu();
+        header.set_mode(file_mode(meta));
+        let mtime = meta
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        header.set_mtime(self.mtime.unwrap_or(mtime));
+        // Nomes fixos não falham
+        let _ = set_root_owner(&mut header);
+        header
+    }
+}
+
+fn set_root_owner(header: &mut Header) -> BuildResult<()> {
+    header.set_uid(0);
+    header.set_gid(0);
+    header
+        .set_username("root")
+        .and_then(|()| header.set_groupname("root"))
+        .map_err(|err| BuildError::io(Path::new("tar"), err))
+}
+
+#[cfg(unix)]
+fn file_mode(meta: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+fn file_mode(meta: &fs::Metadata) -> u32 {
+    if meta.is_dir() {
+        0o755
+    } else {
+        0o644
+    }
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Arquivo ar no formato comum (o contêiner do .deb). Os nomes têm até 16 bytes
+pub(super) struct ArWriter<W: Write> {
+    output: W,
+    mtime: u64,
+}
+
+impl<W: Write> ArWriter<W> {
+    pub(super) fn new(mut output: W, mtime: Option<u64>) -> io::Result<Self> {
+        output.write_all(b"!<arch>\n")?;
+        Ok(ArWriter {
+            output,
+            mtime: mtime.unwrap_or_else(now),
+        })
+    }
+
+    pub(super) fn append(&mut self, name: &str, size: u64, contents: &mut dyn Read) -> io::Result<()> {
+        if name.len() > 16 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, gettext("ar member name longer than 16 bytes")));
+        }
+        let header = format!("{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n", name, self.mtime, 0, 0, "100644", size);
+        self.output.write_all(header.as_bytes())?;
+        let copied = io::copy(contents, &mut self.output)?;
+        if copied != size {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, gettext("ar member size differs from the declared size")));
+        }
+        // Membros começam em posições pares
+        if size % 2 == 1 {
+            self.output.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn finish(self) -> W {
+        self.output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ar_header_layout() {
+        let mut ar = ArWriter::new(Vec::new(), Some(1_700_000_000)).unwrap();
+        ar.append("debian-binary", 4, &mut &b"2.0\n"[..]).unwrap();
+        ar.append("odd", 3, &mut &b"abc"[..]).unwrap();
+        let data = ar.finish();
+
+        assert_eq!(&data[..8], b"!<arch>\n");
+        // Cabeçalhos de 60 bytes: nome, data, dono, grupo, modo, tamanho e fim
+        let header = &data[8..68];
+        assert_eq!(&header[..16], b"debian-binary   ");
+        assert_eq!(&header[16..28], b"1700000000  ");
+        assert_eq!(&header[28..34], b"0     ");
+        assert_eq!(&header[34..40], b"0     ");
+        assert_eq!(&header[40..48], b"100644  ");
+        assert_eq!(&header[48..58], b"4         ");
+        assert_eq!(&header[58..60], b"`\n");
+        assert_eq!(&data[68..72], b"2.0\n");
+
+        // Membro de tamanho ímpar recebe um '\n' para o próximo começar em posição par
+        assert_eq!(&data[72..88], b"odd             ");
+        assert_eq!(&data[132..135], b"abc");
+        assert_eq!(&data[135..], b"\n");
+    }
+
+    #[test]
+    fn ar_rejects_long_names_and_wrong_sizes() {
+        let mut ar = ArWriter::new(Vec::new(), Some(0)).unwrap();
+        assert!(ar.append("a-name-longer-than-16", 0, &mut io::empty()).is_err());
+        assert!(ar.append("short", 10, &mut &b"abc"[..]).is_err());
+    }
+}
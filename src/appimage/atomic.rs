@@ -0,0 +1,270 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use super::error::{BuildError, BuildResult, IoResultExt};
+use super::workdir::process_is_running;
+use crate::i18n::gettext;
+
+const PENDING_SUFFIX: &str = ".partial";
+const MAX_CREATE_ATTEMPTS: u32 = 100;
+
+// Arquivo gravado ao lado do destino e renomeado sobre ele só depois de completo.
+// O rename na mesma pasta é atômico, então o destino nunca aparece truncado; se
+// a geração falhar ou for cancelada, o arquivo pendente é removido ao ser descartado
+pub(super) struct PendingFile {
+    path: PathBuf,
+    destination: PathBuf,
+    file: File,
+    committed: bool,
+}
+
+impl PendingFile {
+    pub(super) fn create(destination: &Path) -> BuildResult<Self> {
+        remove_stale_pending(destination);
+
+        for attempt in 0..MAX_CREATE_ATTEMPTS {
+            let path = pending_path(destination, attempt);
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => {
+                    return Ok(PendingFile {
+                        path,
+                        destination: destination.to_path_buf(),
+                        file,
+                        committed: false,
+                    })
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(BuildError::io(&path, err)),
+            }
+        }
+
+        Err(no_pending_name(destination))
+    }
+
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(super) fn write_all(&mut self, data: &[u8]) -> BuildResult<()> {
+        self.file.write_all(data).at_path(&self.path)
+    }
+
+    // Garante que o conteúdo está no disco antes da verificação e do rename
+    pub(super) fn sync(&self) -> BuildResult<()> {
+        self.file.sync_all().at_path(&self.path)
+    }
+
+    pub(super) fn commit(mut self) -> BuildResult<()> {
+        fs::rename(&self.path, &self.destination).at_path(&self.destination)?;
+        self.committed = true;
+        sync_parent(&self.destination);
+        Ok(())
+    }
+}
+
+// Permite gravar com BufWriter, tar e afins; erros sem o caminho do arquivo
+impl Write for PendingFile {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.file.write(data)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Drop for PendingFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+// Grava um arquivo pequeno (como o .md5) pelo mesmo caminho atômico
+pub(super) fn write_file(destination: &Path, contents: &[u8]) -> BuildResult<()> {
+    let mut pending = PendingFile::create(destination)?;
+    pending.write_all(contents)?;
+    pending.sync()?;
+    pending.commit()
+}
+
+// Diretório montado ao lado do destino e trocado por ele ao final
+pub(super) struct PendingDir {
+    path: PathBuf,
+    destination: PathBuf,
+    committed: bool,
+}
+
+impl PendingDir {
+    pub(super) fn create(destination: &Path) -> BuildResult<Self> {
+        remove_stale_pending(destination);
+
+        for attempt in 0..MAX_CREATE_ATTEMPTS {
+            let path = pending_path(destination, attempt);
+            match fs::create_dir(&path) {
+                Ok(()) => {
+                    return Ok(PendingDir {
+                        path,
+                        destination: destination.to_path_buf(),
+                        committed: false,
+                    })
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(BuildError::io(&path, err)),
+            }
+        }
+
+        Err(no_pending_name(destination))
+    }
+
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Um diretório não pode ser renomeado sobre outro com conteúdo: o anterior
+    // sai do caminho com um nome pendente e só é removido depois que o novo
+    // ocupa o destino. Se essa troca falhar, o anterior volta ao lugar
+    pub(super) fn commit(mut self) -> BuildResult<()> {
+        let previous = match fs::symlink_metadata(&self.destination) {
+            Ok(_) => Some(move_aside(&self.destination)?),
+            Err(_) => None,
+        };
+
+        if let Err(err) = fs::rename(&self.path, &self.destination) {
+            if let Some(previous) = &previous {
+                let _ = fs::rename(previous, &self.destination);
+            }
+            return Err(BuildError::io(&self.destination, err));
+        }
+        self.committed = true;
+        sync_parent(&self.destination);
+
+        if let Some(previous) = previous {
+            let _ = fs::remove_dir_all(&previous);
+        }
+        Ok(())
+    }
+}
+
+// Renomeia o destino atual para um nome pendente livre, que a limpeza de
+// pendentes abandonados também reconhece
+fn move_aside(destination: &Path) -> BuildResult<PathBuf> {
+    for attempt in 0..MAX_CREATE_ATTEMPTS {
+        let path = pending_path(destination, attempt);
+        if fs::symlink_metadata(&path).is_ok() {
+            continue;
+        }
+        fs::rename(destination, &path).at_path(destination)?;
+        return Ok(path);
+    }
+
+    Err(no_pending_name(destination))
+}
+
+impl Drop for PendingDir {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+// Nome oculto com o PID do dono: .Nome.AppImage.<pid>-<tentativa>.partial
+fn pending_path(destination: &Path, attempt: u32) -> PathBuf {
+    let file_name = destination.file_name().unwrap_or_default().to_string_lossy();
+    destination.with_file_name(format!(".{}.{}-{}{}", file_name, process::id(), attempt, PENDING_SUFFIX))
+}
+
+fn no_pending_name(destination: &Path) -> BuildError {
+    BuildError::io(
+        destination,
+        io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            gettext("could not create a temporary file at the destination"),
+        ),
+    )
+}
+
+// Remove pendentes deste destino deixados por processos que não estão mais em
+// execução (encerrados sem passar pelo Drop)
+fn remove_stale_pending(destination: &Path) {
+    let (Some(dir), Some(file_name)) = (destination.parent(), destination.file_name()) else {
+        return;
+    };
+    let prefix = format!(".{}.", file_name.to_string_lossy());
+    let Ok(entries) = fs::read_dir(if dir.as_os_str().is_empty() { Path::new(".") } else { dir }) else {
+        return;
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(owner) = name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(PENDING_SUFFIX))
+            .and_then(|rest| rest.split_once('-'))
+            .and_then(|(pid, _)| pid.parse::<u32>().ok())
+        else {
+            continue;
+        };
+
+        if !process_is_running(owner) {
+            let path = entry.path();
+            let _ = if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+        }
+    }
+}
+
+// Sem o fsync da pasta, o rename pode se perder em uma queda de energia. Nem
+// todo sistema de arquivos aceita fsync em diretórios, então falhas são ignoradas
+fn sync_parent(path: &Path) {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        let _ = File::open(parent).and_then(|dir| dir.sync_all());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TestDir;
+
+    #[test]
+    fn pending_dir_replaces_existing_destination() {
+        let dir = TestDir::new("atomic-replace");
+        let destination = dir.join("Demo.AppDir");
+        dir.write("Demo.AppDir/old", "old");
+
+        let pending = PendingDir::create(&destination).unwrap();
+        fs::write(pending.path().join("new"), "new").unwrap();
+        pending.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(destination.join("new")).unwrap(), "new");
+        assert!(!destination.join("old").exists());
+        // Nem o novo nem o anterior ficam para trás com nome pendente
+        let leftovers: Vec<_> = fs::read_dir(dir.path()).unwrap().filter_map(|e| e.ok()).collect();
+        assert_eq!(leftovers.len(), 1, "{:?}", leftovers);
+    }
+
+    #[test]
+    fn failed_commit_restores_previous_destination() {
+        let dir = TestDir::new("atomic-restore");
+        let destination = dir.join("Demo.AppDir");
+        dir.write("Demo.AppDir/old", "old");
+
+        // Sem o diretório pendente, o rename para o destino falha
+        let pending = PendingDir {
+            path: dir.join("missing"),
+            destination: destination.clone(),
+            committed: false,
+        };
+        assert!(pending.commit().is_err());
+
+        assert_eq!(fs::read_to_string(destination.join("old")).unwrap(), "old");
+    }
+}
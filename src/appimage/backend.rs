@@ -0,0 +1,387 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{gettext, gettext_f};
+use crate::AppImageMetadata;
+
+use super::compression::Compression;
+use super::error::{BuildError, BuildResult, IoResultExt};
+use super::log::BuildLog;
+use super::reproducible::squashfs_options;
+use super::{
+    begin_stage, directory_size, find_appimage_in, preflight, run_with_progress, squashfs, BuildEvent, BuildStage,
+    CancelFlag,
+};
+
+// Ferramenta que transforma o AppDir montado no AppImage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackendKind {
+    // cargo appimage, ou appimagetool quando há compressão definida
+    #[default]
+    Auto,
+    CargoAppimage,
+    Appimagetool,
+    // Runtime + SquashFS gravados pelo próprio programa
+    Native,
+}
+
+impl BackendKind {
+    pub const ALL: [BackendKind; 4] = [
+        BackendKind::Auto,
+        BackendKind::CargoAppimage,
+        BackendKind::Appimagetool,
+        BackendKind::Native,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BackendKind::Auto => gettext("Automatic"),
+            BackendKind::CargoAppimage => "cargo appimage",
+            BackendKind::Appimagetool => "appimagetool",
+            BackendKind::Native => gettext("Native (no external tools)"),
+        }
+    }
+
+    // Identificador usado na linha de comando e nas receitas
+    pub fn id(self) -> &'static str {
+        match self {
+            BackendKind::Auto => "auto",
+            BackendKind::CargoAppimage => "cargo-appimage",
+            BackendKind::Appimagetool => "appimagetool",
+            BackendKind::Native => "native",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.id() == id)
+    }
+
+    // Aceita opções de compressão (e, portanto, builds reprodutíveis)
+    pub fn supports_compression(self) -> bool {
+        self != BackendKind::CargoAppimage
+    }
+}
+
+// O que o backend recebe para compactar
+pub(super) struct PackageJob<'a> {
+    pub(super) appdir: &'a Path,
+    // Projeto cargo com o AppDir em assets/; ausente ao gerar de um AppDir existente
+    pub(super) project_dir: Option<&'a Path>,
+    // Onde a imagem intermediária é gravada antes de ir para o destino
+    pub(super) work_dir: &'a Path,
+    pub(super) compression: Option<Compression>,
+    pub(super) epoch: Option<u64>,
+    // Runtime gravado no início do AppImage, quando disponível
+    pub(super) runtime: Option<PathBuf>,
+    pub(super) log: &'a BuildLog<'a>,
+    pub(super) cancel: &'a CancelFlag,
+}
+
+pub(super) trait Backend {
+    // Arquivos que o backend precisa além do AppDir, gravados no projeto
+    fn prepare(&self, _metadata: &AppImageMetadata, _project_dir: &Path, _package_name: &str) -> BuildResult<()> {
+        Ok(())
+    }
+
+    // Compacta o AppDir e retorna o caminho do AppImage gerado no diretório de trabalho
+    fn package(&self, job: &PackageJob) -> BuildResult<PathBuf>;
+}
+
+// `kind` já resolvido (ver BuildOptions::resolved_backend)
+pub(super) fn select(kind: BackendKind) -> &'static dyn Backend {
+    match kind {
+        BackendKind::CargoAppimage => &CargoAppimage,
+        BackendKind::Auto | BackendKind::Appimagetool => &Appimagetool,
+        BackendKind::Native => &Native,
+    }
+}
+
+pub(super) struct CargoAppimage;
+
+impl Backend for CargoAppimage {
+    fn prepare(&self, metadata: &AppImageMetadata, project_dir: &Path, package_name: &str) -> BuildResult<()> {
+        // Criar main.rs dummy (necessário para compilar)
+        let src_dir = project_dir.join("src");
+        fs::create_dir_all(&src_dir).at_path(&src_dir)?;
+        let main_rs = src_dir.join("main.rs");
+        fs::write(&main_rs, "fn main() {}\n").at_path(&main_rs)?;
+
+        write_cargo_project(metadata, project_dir, package_name)
+    }
+
+    fn package(&self, job: &PackageJob) -> BuildResult<PathBuf> {
+        let (log, cancel) = (job.log, job.cancel);
+        let Some(work_dir) = job.project_dir else {
+            return Err(BuildError::invalid_input(
+                super::InputField::Backend,
+                gettext("cargo appimage only packs AppDirs assembled by this program"),
+            ));
+        };
+
+        // Verificar se cargo-appimage está instalado
+        if preflight::find_cargo_subcommand("appimage").is_none() {
+            return Err(BuildError::MissingTool {
+                tool: "cargo-appimage",
+                install_hint: preflight::CARGO_APPIMAGE_HINT,
+            });
+        }
+
+        begin_stage(BuildStage::Compressing, log, cancel)?;
+
+        // Executar cargo appimage, acompanhando o tamanho da imagem sendo gravada
+        log.info(gettext_f("Running cargo appimage in: {path}", &[("path", &work_dir.display())]));
+        let appdir_size = directory_size(job.appdir);
+        let appimage_dir = work_dir.join("target").join("appimage");
+        let mut command = Command::new("cargo");
+        command.arg("appimage").current_dir(work_dir);
+        let output = run_with_progress(
+            "cargo appimage",
+            &mut command,
+            cancel,
+            || {
+                let written = find_appimage_in(&appimage_dir)
+                    .and_then(|path| fs::metadata(path).ok())
+                    .map(|meta| meta.len())
+                    .unwrap_or(0);
+                log.event(BuildEvent::Progress {
+                    stage: BuildStage::Compressing,
+                    processed: written,
+                    total: appdir_size,
+                });
+            },
+            |stream, line| log.output(stream, line),
+        )?;
+
+        if !output.status.success() {
+            log.error(gettext_f("cargo appimage exited with {status}", &[("status", &output.status)]));
+
+            return Err(BuildError::ExternalTool {
+                tool: "cargo appimage".to_string(),
+                exit_code: output.status.code(),
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            });
+        }
+
+        log.event(BuildEvent::StageFinished(BuildStage::Compressing));
+
+        // Procurar pelo AppImage gerado em vários lugares
+        let search_paths = vec![
+            work_dir.to_path_buf(),
+            work_dir.join("target"),
+            work_dir.join("target").join("appimage"),
+        ];
+
+        let mut found_appimage = None;
+
+        for search_path in &search_paths {
+            if search_path.exists() {
+                log.info(gettext_f("Looking in: {path}", &[("path", &search_path.display())]));
+
+                if let Ok(entries) = fs::read_dir(search_path) {
+                    for entry in entries.filter_map(|e| e.ok()) {
+                        let path = entry.path();
+                        log.info(gettext_f("  Found: {path}", &[("path", &path.display())]));
+
+                        if path.extension().and_then(|s| s.to_str()) == Some("AppImage") {
+                            found_appimage = Some(path);
+                            break;
+                        }
+                    }
+                }
+            }
+
+            if found_appimage.is_some() {
+                break;
+            }
+        }
+
+        if let Some(appimage_file) = found_appimage {
+            log.info(gettext_f("AppImage found: {path}", &[("path", &appimage_file.display())]));
+            Ok(appimage_file)
+        } else {
+            log.warn(gettext("Work directory contents:"));
+            if let Ok(entries) = fs::read_dir(work_dir) {
+                for entry in entries.filter_map(|e| e.ok()) {
+                    log.warn(format!("  {}", entry.path().display()));
+                }
+            }
+
+            Err(BuildError::OutputNotFound {
+                work_dir: work_dir.to_path_buf(),
+            })
+        }
+    }
+}
+
+fn write_cargo_project(metadata: &AppImageMetadata, work_dir: &Path, package_name: &str) -> BuildResult<()> {
+    // Criar Cargo.toml
+    let cargo_toml = work_dir.join("Cargo.toml");
+    let mut cargo_content = format!(
+        r#"[package]
+name = "{}"
+version = "{}"
+edition = "2021"
+"#,
+        package_name,
+        if metadata.version.is_empty() {
+            "1.0.0"
+        } else {
+            &metadata.version
+        }
+    );
+
+    if !metadata.author.is_empty() {
+        cargo_content.push_str(&format!("authors = [\"{}\"]\n", metadata.author));
+    }
+
+    if !metadata.comment.is_empty() {
+        cargo_content.push_str(&format!("description = \"{}\"\n", metadata.comment));
+    }
+
+    cargo_content.push_str(
+        r#"
+[package.metadata.appimage]
+assets = ["assets/usr", "assets/.DirIcon"]
+
+[profile.release]
+opt-level = 3
+lto = true
+strip = true
+"#,
+    );
+
+    fs::write(&cargo_toml, cargo_content).at_path(&cargo_toml)
+}
+
+// Compacta o AppDir com o appimagetool. Com `epoch`, fixa dono, datas e atributos
+// da imagem SquashFS para que o resultado seja reprodutível
+pub(super) struct Appimagetool;
+
+impl Backend for Appimagetool {
+    fn package(&self, job: &PackageJob) -> BuildResult<PathBuf> {
+        let (appdir, log, cancel) = (job.appdir, job.log, job.cancel);
+        begin_stage(BuildStage::Compressing, log, cancel)?;
+
+        let appimage_file = job.work_dir.join("output.AppImage");
+        let appdir_size = directory_size(appdir);
+        let mut command = Command::new("appimagetool");
+        if let Some(compression) = &job.compression {
+            log.info(gettext_f("Compression: {compression}", &[("compression", &compression.describe())]));
+            command.args(compression.appimagetool_args());
+        }
+        if let Some(epoch) = job.epoch {
+            command.env("SOURCE_DATE_EPOCH", epoch.to_string());
+            for option in squashfs_options(epoch) {
+                command.arg(format!("--mksquashfs-opt={}", option));
+            }
+        }
+        if let Some(runtime) = &job.runtime {
+            log.info(format!("Runtime: {}", runtime.display()));
+            command.arg("--runtime-file").arg(runtime);
+        }
+        command.arg(appdir).arg(&appimage_file);
+        // appimagetool não detecta a arquitetura sem binários ELF conhecidos no AppDir
+        if std::env::var_os("ARCH").is_none() {
+            command.env("ARCH", std::env::consts::ARCH);
+        }
+
+        log.info(gettext_f("Running appimagetool in: {path}", &[("path", &appdir.display())]));
+        let output = run_with_progress(
+            "appimagetool",
+            &mut command,
+            cancel,
+            || {
+                let written = fs::metadata(&appimage_file).map(|meta| meta.len()).unwrap_or(0);
+                log.event(BuildEvent::Progress {
+                    stage: BuildStage::Compressing,
+                    processed: written,
+                    total: appdir_size,
+                });
+            },
+            |stream, line| log.output(stream, line),
+        )?;
+
+        if !output.status.success() {
+            log.error(gettext_f("appimagetool exited with {status}", &[("status", &output.status)]));
+            return Err(BuildError::ExternalTool {
+                tool: "appimagetool".to_string(),
+                exit_code: output.status.code(),
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            });
+        }
+
+        log.event(BuildEvent::StageFinished(BuildStage::Compressing));
+
+        if appimage_file.is_file() {
+            Ok(appimage_file)
+        } else {
+            Err(BuildError::OutputNotFound {
+                work_dir: job.work_dir.to_path_buf(),
+            })
+        }
+    }
+}
+
+// Grava o runtime e, logo após, a imagem SquashFS do AppDir, como o appimagetool
+// faz, sem depender de ferramentas externas. Só o runtime precisa estar instalado
+pub(super) struct Native;
+
+impl Backend for Native {
+    fn package(&self, job: &PackageJob) -> BuildResult<PathBuf> {
+        let (appdir, log, cancel) = (job.appdir, job.log, job.cancel);
+        let Some(runtime) = &job.runtime else {
+            return Err(BuildError::MissingTool {
+                tool: gettext("AppImage runtime"),
+                install_hint: preflight::RUNTIME_HINT,
+            });
+        };
+
+        begin_stage(BuildStage::Compressing, log, cancel)?;
+
+        let compression = job.compression.unwrap_or_else(Compression::tool_default);
+        log.info(format!("Runtime: {}", runtime.display()));
+        log.info(gettext_f("Compression: {compression}", &[("compression", &compression.describe())]));
+
+        let appimage_file = job.work_dir.join("output.AppImage");
+        let mut output = BufWriter::new(File::create(&appimage_file).at_path(&appimage_file)?);
+        let mut runtime_source = File::open(runtime).at_path(runtime)?;
+        io::copy(&mut runtime_source, &mut output).at_path(&appimage_file)?;
+
+        // Com `epoch`, a data da imagem é fixa; as dos arquivos já foram normalizadas
+        let mkfs_time = job.epoch.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        });
+        let appdir_size = directory_size(appdir);
+        let image_size = squashfs::write_image(
+            appdir,
+            &mut output,
+            &appimage_file,
+            &compression,
+            mkfs_time.min(u32::MAX as u64) as u32,
+            &mut |processed| {
+                log.event(BuildEvent::Progress {
+                    stage: BuildStage::Compressing,
+                    processed,
+                    total: appdir_size,
+                })
+            },
+            cancel,
+        )?;
+        output.flush().at_path(&appimage_file)?;
+        log.info(gettext_f("SquashFS image: {bytes} bytes", &[("bytes", &image_size)]));
+
+        log.event(BuildEvent::StageFinished(BuildStage::Compressing));
+        Ok(appimage_file)
+    }
+}
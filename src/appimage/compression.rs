@@ -0,0 +1,248 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::{BuildError, BuildResult, InputField};
+use crate::i18n::gettext_f;
+
+// Tamanhos de bloco aceitos pelo mksquashfs (potências de dois de 4 KiB a 1 MiB)
+const MIN_BLOCK_SIZE: u32 = 4 * 1024;
+const MAX_BLOCK_SIZE: u32 = 1024 * 1024;
+pub const DEFAULT_BLOCK_SIZE: u32 = 128 * 1024;
+pub const BLOCK_SIZES: [u32; 5] = [64 * 1024, 128 * 1024, 256 * 1024, 512 * 1024, 1024 * 1024];
+
+// Quanto do binário é comprimido para estimar taxa e velocidade
+const SAMPLE_CHUNKS: u64 = 4;
+const SAMPLE_CHUNK_SIZE: u64 = 256 * 1024;
+
+// Runtime do AppImage gravado antes da imagem SquashFS
+const RUNTIME_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionAlgorithm {
+    Gzip,
+    Xz,
+    Zstd,
+    Lz4,
+}
+
+impl CompressionAlgorithm {
+    pub const ALL: [CompressionAlgorithm; 4] = [
+        CompressionAlgorithm::Gzip,
+        CompressionAlgorithm::Xz,
+        CompressionAlgorithm::Zstd,
+        CompressionAlgorithm::Lz4,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            CompressionAlgorithm::Gzip => "gzip",
+            CompressionAlgorithm::Xz => "xz",
+            CompressionAlgorithm::Zstd => "zstd",
+            CompressionAlgorithm::Lz4 => "lz4",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|algorithm| algorithm.id() == id)
+    }
+
+    // Níveis aceitos pelo mksquashfs (mínimo, máximo, padrão); xz e lz4 não têm nível
+    pub fn level_range(self) -> Option<(u32, u32, u32)> {
+        match self {
+            CompressionAlgorithm::Gzip => Some((1, 9, 9)),
+            CompressionAlgorithm::Zstd => Some((1, 22, 15)),
+            CompressionAlgorithm::Xz | CompressionAlgorithm::Lz4 => None,
+        }
+    }
+}
+
+// Compressão da imagem SquashFS. Sem ela (None nas opções) vale o padrão da ferramenta
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Compression {
+    pub algorithm: CompressionAlgorithm,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_size: Option<u32>,
+}
+
+impl Compression {
+    pub fn new(algorithm: CompressionAlgorithm) -> Self {
+        Compression {
+            algorithm,
+            level: None,
+            block_size: None,
+        }
+    }
+
+    // Usada em builds reprodutíveis sem compressão escolhida
+    pub fn reproducible_default() -> Self {
+        Compression::new(CompressionAlgorithm::Gzip)
+    }
+
+    // Padrão do appimagetool, usado nas estimativas e pelo backend nativo
+    pub fn tool_default() -> Self {
+        Compression::new(CompressionAlgorithm::Gzip)
+    }
+
+    pub fn effective_level(&self) -> Option<u32> {
+        let (_, _, default) = self.algorithm.level_range()?;
+        Some(self.level.unwrap_or(default))
+    }
+
+    pub fn effective_block_size(&self) -> u32 {
+        self.block_size.unwrap_or(DEFAULT_BLOCK_SIZE)
+    }
+
+    pub fn validate(&self) -> BuildResult<()> {
+        let invalid = |message: String| Err(BuildError::invalid_input(InputField::Compression, message));
+
+        match (self.level, self.algorithm.level_range()) {
+            (Some(level), Some((min, max, _))) if level < min || level > max => {
+                return invalid(gettext_f(
+                    "level {level} outside the {min}–{max} range of {algorithm}",
+                    &[("level", &level), ("min", &min), ("max", &max), ("algorithm", &self.algorithm.id())],
+                ));
+            }
+            (Some(_), None) => {
+                return invalid(gettext_f(
+                    "{algorithm} does not take a compression level",
+                    &[("algorithm", &self.algorithm.id())],
+                ));
+            }
+            _ => {}
+        }
+
+        if let Some(block_size) = self.block_size {
+            if !block_size.is_power_of_two() || !(MIN_BLOCK_SIZE..=MAX_BLOCK_SIZE).contains(&block_size) {
+                return invalid(gettext_f(
+                    "invalid block size {size} (power of two between 4 KiB and 1 MiB)",
+                    &[("size", &block_size)],
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Argumentos do appimagetool com todos os parâmetros explícitos, para que o
+    // resultado não dependa dos padrões da versão instalada
+    pub(super) fn appimagetool_args(&self) -> Vec<String> {
+        let mut args = vec!["--comp".to_string(), self.algorithm.id().to_string()];
+        if let Some(level) = self.effective_level() {
+            args.push("--mksquashfs-opt=-Xcompression-level".to_string());
+            args.push(format!("--mksquashfs-opt={}", level));
+        }
+        args.push("--mksquashfs-opt=-b".to_string());
+        args.push(format!("--mksquashfs-opt={}", self.effective_block_size()));
+        args
+    }
+
+    pub fn describe(&self) -> String {
+        let mut description = self.algorithm.id().to_string();
+        if let Some(level) = self.effective_level() {
+            description.push_str(&gettext_f(" level {level}", &[("level", &level)]));
+        }
+        description.push_str(&gettext_f(
+            ", {size} KiB blocks",
+            &[("size", &(self.effective_block_size() / 1024))],
+        ));
+        description
+    }
+}
+
+// Resultado da compressão de uma amostra do binário
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompressionSample {
+    // Tamanho comprimido / tamanho original
+    pub ratio: f64,
+    // Velocidade estimada do mksquashfs, considerando todos os núcleos
+    pub bytes_per_second: f64,
+}
+
+impl CompressionSample {
+    // Tamanho estimado do AppImage: o conteúdo que comprime como a amostra, o que
+    // já está comprimido (como ícones PNG) e o runtime
+    pub fn estimate_output(&self, compressible: u64, incompressible: u64) -> u64 {
+        (compressible as f64 * self.ratio).ceil() as u64 + incompressible + RUNTIME_SIZE
+    }
+
+    pub fn seconds_for(&self, input_size: u64) -> f64 {
+        if self.bytes_per_second <= 0.0 {
+            0.0
+        } else {
+            input_size as f64 / self.bytes_per_second
+        }
+    }
+}
+
+// Comprime trechos espalhados do arquivo, bloco a bloco como o mksquashfs faz,
+// para estimar a taxa de compressão e a velocidade
+pub fn sample_compression(path: &Path, compression: &Compression) -> io::Result<CompressionSample> {
+    let mut file = File::open(path)?;
+    let length = file.metadata()?.len();
+
+    let mut sample = Vec::new();
+    if length <= SAMPLE_CHUNKS * SAMPLE_CHUNK_SIZE {
+        file.read_to_end(&mut sample)?;
+    } else {
+        let stride = length / SAMPLE_CHUNKS;
+        for chunk in 0..SAMPLE_CHUNKS {
+            file.seek(SeekFrom::Start(chunk * stride))?;
+            (&mut file).take(SAMPLE_CHUNK_SIZE).read_to_end(&mut sample)?;
+        }
+    }
+
+    if sample.is_empty() {
+        return Ok(CompressionSample {
+            ratio: 1.0,
+            bytes_per_second: 0.0,
+        });
+    }
+
+    let started = Instant::now();
+    let mut compressed = 0;
+    for block in sample.chunks(compression.effective_block_size() as usize) {
+        compressed += compress_block(block, compression)?.len();
+    }
+    let elapsed = started.elapsed().as_secs_f64().max(1e-6);
+
+    let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    Ok(CompressionSample {
+        // Blocos que não comprimem são gravados sem compressão
+        ratio: (compressed as f64 / sample.len() as f64).min(1.0),
+        bytes_per_second: sample.len() as f64 / elapsed * cores as f64,
+    })
+}
+
+// Comprime um bloco no formato que o SquashFS grava (zlib para gzip, xz com CRC32)
+pub(super) fn compress_block(block: &[u8], compression: &Compression) -> io::Result<Vec<u8>> {
+    let level = compression.effective_level();
+    match compression.algorithm {
+        CompressionAlgorithm::Gzip => {
+            let level = flate2::Compression::new(level.unwrap_or(9));
+            let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), level);
+            encoder.write_all(block)?;
+            encoder.finish()
+        }
+        CompressionAlgorithm::Xz => {
+            // O decompressor xz do kernel só aceita a verificação CRC32
+            let mut options = xz2::stream::LzmaOptions::new_preset(6).map_err(io::Error::from)?;
+            options.dict_size(compression.effective_block_size().max(MIN_BLOCK_SIZE));
+            let mut filters = xz2::stream::Filters::new();
+            filters.lzma2(&options);
+            let stream = xz2::stream::Stream::new_stream_encoder(&filters, xz2::stream::Check::Crc32)
+                .map_err(io::Error::from)?;
+            let mut encoder = xz2::write::XzEncoder::new_stream(Vec::new(), stream);
+            encoder.write_all(block)?;
+            encoder.finish()
+        }
+        CompressionAlgorithm::Zstd => zstd::bulk::compress(block, level.unwrap_or(15) as i32),
+        CompressionAlgorithm::Lz4 => Ok(lz4_flex::block::compress(block)),
+    }
+}
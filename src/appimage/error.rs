@@ -0,0 +1,235 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::{gettext, gettext_f};
+
+// Campo do formulário associado a uma entrada inválida
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputField {
+    Binary,
+    Icon,
+    Name,
+    Exec,
+    Categories,
+    OutputFolder,
+    DesktopFile,
+    MetainfoFile,
+    AppDir,
+    Compression,
+    FileName,
+    Backend,
+    AppImage,
+}
+
+impl InputField {
+    pub fn label(self) -> &'static str {
+        match self {
+            InputField::Binary => gettext("Binary"),
+            InputField::Icon => gettext("Icon"),
+            InputField::Name => gettext("Name"),
+            InputField::Exec => gettext("Command"),
+            InputField::Categories => gettext("Categories"),
+            InputField::OutputFolder => gettext("Output folder"),
+            InputField::DesktopFile => gettext(".desktop file"),
+            InputField::MetainfoFile => gettext("Metainfo file"),
+            InputField::AppDir => "AppDir",
+            InputField::Compression => gettext("Compression"),
+            InputField::FileName => gettext("File name"),
+            InputField::Backend => "Backend",
+            InputField::AppImage => "AppImage",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BuildError {
+    MissingTool {
+        tool: &'static str,
+        install_hint: &'static str,
+    },
+    InvalidInput {
+        field: InputField,
+        message: String,
+    },
+    IconDecode {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    CopyVerification {
+        label: String,
+        expected: String,
+        actual: String,
+    },
+    ExternalTool {
+        tool: String,
+        exit_code: Option<i32>,
+        stdout: String,
+        stderr: String,
+    },
+    OutputNotFound {
+        work_dir: PathBuf,
+    },
+    OutputExists {
+        path: PathBuf,
+    },
+    OutputBusy {
+        path: PathBuf,
+    },
+    PermissionDenied {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Cancelled,
+}
+
+impl BuildError {
+    pub(crate) fn invalid_input(field: InputField, message: impl Into<String>) -> Self {
+        BuildError::InvalidInput {
+            field,
+            message: message.into(),
+        }
+    }
+
+    // Erros de E/S com permissão negada ganham variante própria
+    pub(crate) fn io(path: &Path, source: io::Error) -> Self {
+        let path = path.to_path_buf();
+        if source.kind() == io::ErrorKind::PermissionDenied {
+            BuildError::PermissionDenied { path, source }
+        } else {
+            BuildError::Io { path, source }
+        }
+    }
+
+    // Campo do formulário que deve receber o foco para corrigir o erro
+    pub fn field(&self) -> Option<InputField> {
+        match self {
+            BuildError::InvalidInput { field, .. } => Some(*field),
+            BuildError::IconDecode { .. } => Some(InputField::Icon),
+            _ => None,
+        }
+    }
+
+    // Comando sugerido para resolver o erro
+    pub fn install_hint(&self) -> Option<&'static str> {
+        match self {
+            BuildError::MissingTool { install_hint, .. } => Some(gettext(install_hint)),
+            _ => None,
+        }
+    }
+
+    // Mensagem completa, incluindo as causas encadeadas
+    pub fn describe(&self) -> String {
+        let mut message = self.to_string();
+        let mut source = self.source();
+        while let Some(cause) = source {
+            message.push_str(&format!(": {}", cause));
+            source = cause.source();
+        }
+        message
+    }
+
+    // Diretório mantido para investigação após a falha
+    pub fn kept_work_dir(&self) -> Option<&Path> {
+        match self {
+            BuildError::OutputNotFound { work_dir } => Some(work_dir),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::MissingTool { tool, install_hint } => {
+                let message = gettext_f(
+                    "{tool} is not installed!\n\nTo install: {hint}",
+                    &[("tool", tool), ("hint", &gettext(install_hint))],
+                );
+                f.write_str(&message)
+            }
+            BuildError::InvalidInput { field, message } => {
+                write!(f, "{}: {}", field.label(), message)
+            }
+            BuildError::IconDecode { path, .. } => {
+                f.write_str(&gettext_f("Failed to decode the icon {path}", &[("path", &path.display())]))
+            }
+            BuildError::CopyVerification {
+                label,
+                expected,
+                actual,
+            } => f.write_str(&gettext_f(
+                "MD5 check failed for {label} ({expected} != {actual})",
+                &[("label", label), ("expected", expected), ("actual", actual)],
+            )),
+            BuildError::ExternalTool {
+                tool,
+                exit_code,
+                stdout,
+                stderr,
+            } => {
+                match exit_code {
+                    Some(code) => {
+                        f.write_str(&gettext_f("{tool} exited with code {code}", &[("tool", tool), ("code", code)]))?
+                    }
+                    None => f.write_str(&gettext_f("{tool} was killed by a signal", &[("tool", tool)]))?,
+                }
+                // Sem stderr, a causa costuma estar no fim da saída padrão
+                let detail = if stderr.trim().is_empty() { stdout } else { stderr };
+                let detail = detail.trim();
+                if !detail.is_empty() {
+                    write!(f, ":\n{}", detail)?;
+                }
+                Ok(())
+            }
+            BuildError::OutputNotFound { work_dir } => f.write_str(&gettext_f(
+                "The AppImage was not found after the build. Project at: {path}",
+                &[("path", &work_dir.display())],
+            )),
+            BuildError::OutputExists { path } => {
+                f.write_str(&gettext_f("{path} already exists", &[("path", &path.display())]))
+            }
+            BuildError::OutputBusy { path } => f.write_str(&gettext_f(
+                "{path} is already being built by another build",
+                &[("path", &path.display())],
+            )),
+            BuildError::PermissionDenied { path, .. } => {
+                f.write_str(&gettext_f("Permission denied: {path}", &[("path", &path.display())]))
+            }
+            BuildError::Io { path, .. } => {
+                f.write_str(&gettext_f("I/O error in {path}", &[("path", &path.display())]))
+            }
+            BuildError::Cancelled => f.write_str(gettext("Cancelled")),
+        }
+    }
+}
+
+impl Error for BuildError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BuildError::IconDecode { source, .. } => Some(source),
+            BuildError::PermissionDenied { source, .. } | BuildError::Io { source, .. } => {
+                Some(source)
+            }
+            _ => None,
+        }
+    }
+}
+
+pub type BuildResult<T> = Result<T, BuildError>;
+
+// Anexa o caminho envolvido a um erro de E/S
+pub(super) trait IoResultExt<T> {
+    fn at_path(self, path: &Path) -> BuildResult<T>;
+}
+
+impl<T> IoResultExt<T> for io::Result<T> {
+    fn at_path(self, path: &Path) -> BuildResult<T> {
+        self.map_err(|source| BuildError::io(path, source))
+    }
+}
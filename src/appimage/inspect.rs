@@ -0,0 +1,106 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::error::{BuildError, BuildResult, InputField, IoResultExt};
+use super::integration::extract_desktop_entry;
+use super::size::format_size;
+use super::workdir::WorkDir;
+use super::compute_md5;
+use crate::i18n::{gettext, gettext_f};
+
+const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+
+// Assinatura da especificação AppImage nos bytes 8 a 10 do cabeçalho ELF: "AI" + tipo
+const APPIMAGE_MAGIC_OFFSET: usize = 8;
+
+// Campo e_machine do cabeçalho ELF
+const ELF_MACHINE_OFFSET: usize = 18;
+
+// Informações de um AppImage existente, sem executar o aplicativo
+#[derive(Debug, Clone)]
+pub struct AppImageInfo {
+    pub path: PathBuf,
+    pub size: u64,
+    pub md5: String,
+    // 1 ou 2; None quando o arquivo não tem a assinatura de AppImage
+    pub appimage_type: Option<u8>,
+    pub architecture: Option<&'static str>,
+    // .desktop embutido, ou o motivo de não ter sido extraído
+    pub desktop_entry: Result<String, String>,
+}
+
+impl AppImageInfo {
+    pub fn summary(&self) -> String {
+        let kind = match self.appimage_type {
+            Some(kind) => gettext_f("type {kind} AppImage", &[("kind", &kind)]),
+            None => gettext("not an AppImage (no AI signature in the header)").to_string(),
+        };
+        let mut lines = vec![
+            self.path.display().to_string(),
+            gettext_f("Type: {kind}", &[("kind", &kind)]),
+            gettext_f("Size: {size}", &[("size", &format_size(self.size))]),
+            format!("MD5: {}", self.md5),
+        ];
+        if let Some(architecture) = self.architecture {
+            lines.insert(2, gettext_f("Architecture: {architecture}", &[("architecture", &architecture)]));
+        }
+        if let Err(reason) = &self.desktop_entry {
+            lines.push(format!(".desktop: {}", reason));
+        }
+        lines.join("\n")
+    }
+}
+
+// Lê o cabeçalho, calcula o MD5 e extrai o .desktop (só de arquivos com a
+// assinatura de AppImage, já que a extração executa o arquivo)
+pub fn inspect_appimage(appimage: &Path) -> BuildResult<AppImageInfo> {
+    if !appimage.is_file() {
+        return Err(BuildError::invalid_input(
+            InputField::AppImage,
+            gettext_f("{path} not found", &[("path", &appimage.display())]),
+        ));
+    }
+    let path = appimage.canonicalize().at_path(appimage)?;
+    let size = fs::metadata(&path).at_path(&path)?.len();
+
+    let mut header = [0u8; 20];
+    let read = File::open(&path).and_then(|mut file| file.read(&mut header)).at_path(&path)?;
+    let header = &header[..read];
+    let is_elf = header.starts_with(ELF_MAGIC);
+    let appimage_type = header
+        .get(APPIMAGE_MAGIC_OFFSET..APPIMAGE_MAGIC_OFFSET + 3)
+        .filter(|magic| is_elf && magic.starts_with(b"AI"))
+        .map(|magic| magic[2]);
+    let architecture = header
+        .get(ELF_MACHINE_OFFSET..ELF_MACHINE_OFFSET + 2)
+        .filter(|_| is_elf)
+        .and_then(|machine| elf_architecture(u16::from_le_bytes([machine[0], machine[1]])));
+
+    let desktop_entry = match appimage_type {
+        Some(_) => WorkDir::create(&std::env::temp_dir(), "inspect")
+            .and_then(|work_dir| extract_desktop_entry(&path, work_dir.path()))
+            .map(|(_, content)| content)
+            .map_err(|err| err.describe()),
+        None => Err(gettext("not extracted").to_string()),
+    };
+
+    Ok(AppImageInfo {
+        md5: compute_md5(&path)?,
+        path,
+        size,
+        appimage_type,
+        architecture,
+        desktop_entry,
+    })
+}
+
+fn elf_architecture(machine: u16) -> Option<&'static str> {
+    match machine {
+        0x03 => Some("i686"),
+        0x28 => Some("armhf"),
+        0x3e => Some("x86_64"),
+        0xb7 => Some("aarch64"),
+        _ => None,
+    }
+}
@@ -0,0 +1,399 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::freedesktop;
+use crate::i18n::{gettext, gettext_f};
+
+use super::atomic::{self, PendingFile};
+use super::error::{BuildError, BuildResult, InputField, IoResultExt};
+use super::preflight::find_in_path;
+use super::reproducible::sorted_entries;
+use super::workdir::WorkDir;
+use super::make_executable;
+
+// Prefixo dos arquivos instalados, como no appimaged: appimagekit_<md5 da URI do AppImage>
+const INTEGRATION_PREFIX: &str = "appimagekit_";
+
+// Pasta criada pelo runtime com --appimage-extract
+const EXTRACT_DIR: &str = "squashfs-root";
+
+// Pastas usadas na integração
+#[derive(Debug, Clone)]
+pub struct IntegrationDirs {
+    // Onde ficam os AppImages integrados (~/Applications)
+    pub applications: PathBuf,
+    // $XDG_DATA_HOME, com applications/ e icons/
+    pub data: PathBuf,
+}
+
+impl IntegrationDirs {
+    pub fn for_user() -> BuildResult<Self> {
+        let home = dirs::home_dir().ok_or_else(|| {
+            BuildError::io(
+                Path::new("~"),
+                io::Error::new(io::ErrorKind::NotFound, gettext("home folder not found")),
+            )
+        })?;
+        Ok(IntegrationDirs {
+            applications: home.join("Applications"),
+            data: dirs::data_dir().unwrap_or_else(|| home.join(".local/share")),
+        })
+    }
+
+    fn desktop_dir(&self) -> PathBuf {
+        self.data.join("applications")
+    }
+
+    fn icons_dir(&self) -> PathBuf {
+        self.data.join("icons").join("hicolor")
+    }
+}
+
+// Arquivos instalados por uma integração
+#[derive(Debug, Clone)]
+pub struct Integration {
+    pub appimage: PathBuf,
+    pub desktop_file: PathBuf,
+    pub icons: Vec<PathBuf>,
+}
+
+// Copia o AppImage para a pasta de aplicativos (se ainda não estiver lá) e instala
+// o .desktop e os ícones extraídos dele. Integrar de novo substitui a anterior
+pub fn integrate(appimage: &Path, dirs: &IntegrationDirs) -> BuildResult<Integration> {
+    if !appimage.is_file() {
+        return Err(BuildError::invalid_input(
+            InputField::AppImage,
+            gettext_f("{path} not found", &[("path", &appimage.display())]),
+        ));
+    }
+
+    // Tudo é extraído e validado antes de tocar na integração anterior e na
+    // pasta de aplicativos: um AppImage inválido não deixa nada pela metade
+    let work_dir = WorkDir::create(&std::env::temp_dir(), "integrate")?;
+    let (desktop_source, content) = extract_desktop_entry(appimage, work_dir.path())?;
+    if let Err(problems) = freedesktop::validate_integrated_entry(&content) {
+        return Err(BuildError::invalid_input(
+            InputField::AppImage,
+            gettext_f(
+                "invalid .desktop file {name} in the AppImage:\n- {problems}",
+                &[
+                    ("name", &desktop_source.file_name().unwrap_or_default().to_string_lossy()),
+                    ("problems", &problems.join("\n- ")),
+                ],
+            ),
+        ));
+    }
+    let root = work_dir.path().join(EXTRACT_DIR);
+
+    // Icon com caminho absoluto não é um nome de tema
+    let icon_name = freedesktop::desktop_entry_icon(&content).filter(|icon| !icon.contains('/'));
+    if let Some(icon) = &icon_name {
+        extract(appimage, work_dir.path(), "usr/share/icons/*")?;
+        extract(appimage, work_dir.path(), &format!("{}.*", icon))?;
+    }
+
+    let installed = install_appimage(appimage, dirs)?;
+    let prefix = integration_prefix(&installed);
+    remove_files_with_prefix(dirs, &prefix)?;
+
+    let icons = match &icon_name {
+        Some(icon) => install_icons(&root, icon, &format!("{}_", prefix), dirs)?,
+        None => Vec::new(),
+    };
+
+    let installed_icon = icon_name
+        .filter(|_| !icons.is_empty())
+        .map(|icon| format!("{}_{}", prefix, icon));
+    let desktop_file = dirs.desktop_dir().join(format!(
+        "{}-{}",
+        prefix,
+        desktop_source.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    fs::create_dir_all(dirs.desktop_dir()).at_path(&dirs.desktop_dir())?;
+    let entry = freedesktop::integrated_desktop_entry(&content, &installed, installed_icon.as_deref());
+    atomic::write_file(&desktop_file, entry.as_bytes())?;
+
+    update_caches(dirs);
+    Ok(Integration {
+        appimage: installed,
+        desktop_file,
+        icons,
+    })
+}
+
+// Remove o .desktop e os ícones da integração e a cópia do AppImage feita por
+// `integrate`. Um AppImage que já estava na pasta de aplicativos é mantido.
+// Retorna os arquivos removidos
+pub fn remove_integration(appimage: &Path, dirs: &IntegrationDirs) -> BuildResult<Vec<PathBuf>> {
+    let installed = installed_path(appimage, dirs);
+    let mut removed = remove_files_with_prefix(dirs, &integration_prefix(&installed))?;
+
+    if !is_same_file(appimage, &installed) && installed.is_file() {
+        fs::remove_file(&installed).at_path(&installed)?;
+        removed.push(installed);
+    }
+
+    if !removed.is_empty() {
+        update_caches(dirs);
+    }
+    Ok(removed)
+}
+
+fn installed_path(appimage: &Path, dirs: &IntegrationDirs) -> PathBuf {
+    let in_applications = appimage
+        .parent()
+        .and_then(|parent| parent.canonicalize().ok())
+        .is_some_and(|parent| dirs.applications.canonicalize().is_ok_and(|dir| dir == parent));
+    if in_applications {
+        appimage.to_path_buf()
+    } else {
+        dirs.applications.join(appimage.file_name().unwrap_or_default())
+    }
+}
+
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+// Cópia verificada pelo tamanho, gravada por arquivo pendente como a saída da geração
+fn install_appimage(appimage: &Path, dirs: &IntegrationDirs) -> BuildResult<PathBuf> {
+    let installed = installed_path(appimage, dirs);
+    if !is_same_file(appimage, &installed) {
+        fs::create_dir_all(&dirs.applications).at_path(&dirs.applications)?;
+        let mut pending = PendingFile::create(&installed)?;
+        let mut source = File::open(appimage).at_path(appimage)?;
+        let copied = io::copy(&mut source, &mut pending).at_path(&installed)?;
+        let expected = source.metadata().at_path(appimage)?.len();
+        if copied != expected {
+            return Err(BuildError::CopyVerification {
+                label: gettext("integrated AppImage").to_string(),
+                expected: expected.to_string(),
+                actual: copied.to_string(),
+            });
+        }
+        pending.sync()?;
+        make_executable(pending.path())?;
+        pending.commit()?;
+    }
+    installed.canonicalize().at_path(&installed)
+}
+
+// appimagekit_<md5 de file:///caminho/do/AppImage>, a mesma chave das miniaturas
+fn integration_prefix(installed: &Path) -> String {
+    let path = installed.canonicalize().unwrap_or_else(|_| installed.to_path_buf());
+    let uri = url::Url::from_file_path(&path)
+        .map(|url| url.to_string())
+        .unwrap_or_else(|()| format!("file://{}", path.display()));
+    format!("{}{:x}", INTEGRATION_PREFIX, md5::compute(uri.as_bytes()))
+}
+
+// Extrai o .desktop da raiz do AppImage para <dir>/squashfs-root e retorna o
+// caminho e o conteúdo
+pub(super) fn extract_desktop_entry(appimage: &Path, dir: &Path) -> BuildResult<(PathBuf, String)> {
+    extract(appimage, dir, "*.desktop")?;
+    let desktop_file = sorted_entries(&dir.join(EXTRACT_DIR))
+        .unwrap_or_default()
+        .into_iter()
+        .find(|path| path.extension().is_some_and(|ext| ext == "desktop") && path.is_file())
+        .ok_or_else(|| {
+            BuildError::invalid_input(InputField::AppImage, gettext("the AppImage has no .desktop file at its root"))
+        })?;
+    let content = fs::read_to_string(&desktop_file).at_path(&desktop_file)?;
+    Ok((desktop_file, content))
+}
+
+// Extrai os arquivos que casam com `pattern` para <dir>/squashfs-root, sem FUSE
+fn extract(appimage: &Path, dir: &Path, pattern: &str) -> BuildResult<()> {
+    let output = Command::new(appimage)
+        .arg("--appimage-extract")
+        .arg(pattern)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()
+        .at_path(appimage)?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(BuildError::ExternalTool {
+            tool: format!("{} --appimage-extract", appimage.display()),
+            exit_code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        })
+    }
+}
+
+// Ícones do tema hicolor com o nome do Icon; sem eles, o ícone da raiz do AppDir
+fn install_icons(root: &Path, icon: &str, prefix: &str, dirs: &IntegrationDirs) -> BuildResult<Vec<PathBuf>> {
+    let mut icons = Vec::new();
+    let hicolor = root.join("usr/share/icons/hicolor");
+    for size_dir in sorted_entries(&hicolor).unwrap_or_default() {
+        let apps = size_dir.join("apps");
+        for file in sorted_entries(&apps).unwrap_or_default() {
+            if file.file_stem().is_some_and(|stem| stem == icon) && file.is_file() {
+                let size = size_dir.file_name().unwrap_or_default();
+                icons.push(install_icon(&file, &dirs.icons_dir().join(size).join("apps"), prefix)?);
+            }
+        }
+    }
+    if !icons.is_empty() {
+        return Ok(icons);
+    }
+
+    for (extension, fallback_dir) in [("png", None), ("svg", Some("scalable"))] {
+        let file = root.join(format!("{}.{}", icon, extension));
+        if !file.is_file() {
+            continue;
+        }
+        let size = match fallback_dir {
+            Some(dir) => dir.to_string(),
+            None => match image::image_dimensions(&file) {
+                Ok((width, height)) => format!("{}x{}", width, height),
+                Err(_) => continue,
+            },
+        };
+        icons.push(install_icon(&file, &dirs.icons_dir().join(size).join("apps"), prefix)?);
+        break;
+    }
+    Ok(icons)
+}
+
+fn install_icon(file: &Path, apps_dir: &Path, prefix: &str) -> BuildResult<PathBuf> {
+    fs::create_dir_all(apps_dir).at_path(apps_dir)?;
+    let destination = apps_dir.join(format!("{}{}", prefix, file.file_name().unwrap_or_default().to_string_lossy()));
+    fs::copy(file, &destination).at_path(&destination)?;
+    Ok(destination)
+}
+
+// .desktop e ícones de uma integração, identificados pelo prefixo
+fn remove_files_with_prefix(dirs: &IntegrationDirs, prefix: &str) -> BuildResult<Vec<PathBuf>> {
+    let mut candidates = sorted_entries(&dirs.desktop_dir()).unwrap_or_default();
+    for size_dir in sorted_entries(&dirs.icons_dir()).unwrap_or_default() {
+        candidates.extend(sorted_entries(&size_dir.join("apps")).unwrap_or_default());
+    }
+
+    let mut removed = Vec::new();
+    for path in candidates {
+        let matches = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(&format!("{}-", prefix)) || name.starts_with(&format!("{}_", prefix)));
+        if matches {
+            fs::remove_file(&path).at_path(&path)?;
+            removed.push(path);
+        }
+    }
+    Ok(removed)
+}
+
+// Atualiza os caches do menu e dos ícones quando as ferramentas existem
+fn update_caches(dirs: &IntegrationDirs) {
+    let commands: [(&str, &[&str], PathBuf); 2] = [
+        ("update-desktop-database", &["-q"], dirs.desktop_dir()),
+        ("gtk-update-icon-cache", &["-q", "-t", "-f"], dirs.icons_dir()),
+    ];
+    for (tool, args, dir) in commands {
+        if let (Some(program), true) = (find_in_path(tool), dir.is_dir()) {
+            let _ = Command::new(program)
+                .args(args)
+                .arg(&dir)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TestDir;
+
+    // Pastas de aplicativos e dados exclusivas do teste, removidas ao final
+    struct TestDirs {
+        root: TestDir,
+        dirs: IntegrationDirs,
+    }
+
+    impl TestDirs {
+        fn new(name: &str) -> Self {
+            let root = TestDir::new(&format!("integration-{}", name));
+            let dirs = IntegrationDirs {
+                applications: root.join("Applications"),
+                data: root.join("data"),
+            };
+            TestDirs { root, dirs }
+        }
+
+        // AppImage falso: um script que atende a --appimage-extract como o runtime
+        fn fake_appimage(&self, desktop: &str) -> PathBuf {
+            let script = format!(
+                "#!/bin/sh\n[ \"$1\" = --appimage-extract ] || exit 1\n\
+                 mkdir -p squashfs-root/usr/share/icons/hicolor/48x48/apps\n\
+                 printf png > squashfs-root/usr/share/icons/hicolor/48x48/apps/demo.png\n\
+                 cat > squashfs-root/demo.desktop <<'EOF'\n{}EOF\n",
+                desktop
+            );
+            let path = self.root.write("Demo.AppImage", script);
+            make_executable(&path).unwrap();
+            path
+        }
+    }
+
+    const DESKTOP: &str = "[Desktop Entry]\nType=Application\nName=Demo\nExec=demo %U\nIcon=demo\nCategories=Utility;\n";
+
+    #[test]
+    fn integrate_and_remove_round_trip() {
+        let test = TestDirs::new("round-trip");
+        let appimage = test.fake_appimage(DESKTOP);
+
+        let integration = integrate(&appimage, &test.dirs).unwrap();
+        assert_eq!(integration.appimage, test.dirs.applications.canonicalize().unwrap().join("Demo.AppImage"));
+        assert!(integration.appimage.is_file());
+        assert_eq!(integration.icons.len(), 1);
+        assert!(integration.icons[0].starts_with(test.dirs.data.join("icons/hicolor/48x48/apps")));
+
+        let entry = fs::read_to_string(&integration.desktop_file).unwrap();
+        assert!(entry.contains(&format!("Exec=\"{}\" %U", integration.appimage.display())), "{}", entry);
+        assert!(entry.contains(&format!("TryExec={}", integration.appimage.display())), "{}", entry);
+        let icon = integration.icons[0].file_stem().unwrap().to_string_lossy();
+        assert!(entry.contains(&format!("Icon={}\n", icon)), "{}", entry);
+
+        let mut removed = remove_integration(&appimage, &test.dirs).unwrap();
+        removed.sort();
+        let mut expected = vec![integration.desktop_file, integration.appimage];
+        expected.extend(integration.icons);
+        expected.sort();
+        assert_eq!(removed, expected);
+        assert!(removed.iter().all(|path| !path.exists()));
+        // O original, fora da pasta de aplicativos, continua no lugar
+        assert!(appimage.is_file());
+    }
+
+    #[test]
+    fn invalid_desktop_entry_keeps_previous_integration() {
+        let test = TestDirs::new("invalid");
+        let appimage = test.fake_appimage(DESKTOP);
+        let integration = integrate(&appimage, &test.dirs).unwrap();
+        let installed = fs::read(&integration.appimage).unwrap();
+
+        let appimage = test.fake_appimage("[Desktop Entry]\nType=Application\nName=Demo\n");
+        let result = integrate(&appimage, &test.dirs);
+        assert!(
+            matches!(result, Err(BuildError::InvalidInput { field: InputField::AppImage, .. })),
+            "{:?}",
+            result
+        );
+
+        // Nada da integração anterior foi removido ou substituído
+        assert!(integration.desktop_file.is_file());
+        assert!(integration.icons.iter().all(|icon| icon.is_file()));
+        assert_eq!(fs::read(&integration.appimage).unwrap(), installed);
+    }
+}
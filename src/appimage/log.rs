@@ -0,0 +1,216 @@
+use std::cell::RefCell;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::BuildEvent;
+use crate::i18n::{gettext, gettext_f};
+
+// Quantos arquivos de log manter em $XDG_STATE_HOME/appimage-creator/logs
+const MAX_LOG_FILES: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Info,
+    Warning,
+    Error,
+    // Saída capturada de processos externos (stdout/stderr)
+    Output,
+}
+
+impl LogLevel {
+    pub fn label(self) -> &'static str {
+        match self {
+            LogLevel::Info => gettext("INFO"),
+            LogLevel::Warning => gettext("WARN"),
+            LogLevel::Error => gettext("ERROR"),
+            LogLevel::Output => gettext("OUTPUT"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub level: LogLevel,
+    pub timestamp: SystemTime,
+    pub elapsed: Duration,
+    pub message: String,
+}
+
+impl LogRecord {
+    pub fn format_line(&self) -> String {
+        format!(
+            "{} [+{:>6.1}s] {:<5} {}",
+            format_time_of_day(self.timestamp),
+            self.elapsed.as_secs_f64(),
+            self.level.label(),
+            self.message
+        )
+    }
+}
+
+// Registro de uma geração: repassa eventos à interface e grava cada
+// registro no arquivo de log da execução
+pub(super) struct BuildLog<'a> {
+    on_event: &'a dyn Fn(BuildEvent),
+    started_at: Instant,
+    file: RefCell<Option<File>>,
+}
+
+impl<'a> BuildLog<'a> {
+    // Log só com os eventos, sem arquivo
+    pub(super) fn new(on_event: &'a dyn Fn(BuildEvent)) -> Self {
+        BuildLog {
+            on_event,
+            started_at: Instant::now(),
+            file: RefCell::new(None),
+        }
+    }
+
+    pub(super) fn open(on_event: &'a dyn Fn(BuildEvent)) -> Self {
+        let log = BuildLog::new(on_event);
+        // Os testes não gravam nem limpam os logs do usuário
+        if cfg!(test) {
+            return log;
+        }
+
+        match create_log_file() {
+            Ok((file, path)) => {
+                log.file.replace(Some(file));
+                log.info(gettext_f("Log saved to: {path}", &[("path", &path.display())]));
+            }
+            Err(err) => log.warn(gettext_f("Could not create the log file: {error}", &[("error", &err)])),
+        }
+
+        log
+    }
+
+    pub(super) fn event(&self, event: BuildEvent) {
+        match &event {
+            BuildEvent::StageStarted(stage) => self.info(format!("{}...", stage.label())),
+            BuildEvent::StageFinished(stage) => self.info(gettext_f("{stage} done", &[("stage", &stage.label())])),
+            _ => {}
+        }
+        (self.on_event)(event);
+    }
+
+    pub(super) fn info(&self, message: impl Into<String>) {
+        self.record(LogLevel::Info, message.into());
+    }
+
+    pub(super) fn warn(&self, message: impl Into<String>) {
+        self.record(LogLevel::Warning, message.into());
+    }
+
+    pub(super) fn error(&self, message: impl Into<String>) {
+        self.record(LogLevel::Error, message.into());
+    }
+
+    pub(super) fn output(&self, stream: &str, line: &str) {
+        self.record(LogLevel::Output, format!("{}: {}", stream, line));
+    }
+
+    fn record(&self, level: LogLevel, message: String) {
+        let record = LogRecord {
+            level,
+            timestamp: SystemTime::now(),
+            elapsed: self.started_at.elapsed(),
+            message,
+        };
+
+        if let Some(file) = self.file.borrow_mut().as_mut() {
+            let _ = writeln!(file, "{}", record.format_line());
+        }
+
+        (self.on_event)(BuildEvent::Log(record));
+    }
+}
+
+pub fn log_dir() -> Option<PathBuf> {
+    dirs::state_dir().map(|dir| dir.join("appimage-creator").join("logs"))
+}
+
+fn create_log_file() -> std::io::Result<(File, PathBuf)> {
+    let dir = log_dir().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            gettext("user state directory not found"),
+        )
+    })?;
+    fs::create_dir_all(&dir)?;
+    prune_old_logs(&dir);
+
+    let stamp = format_file_timestamp(SystemTime::now());
+    let mut path = dir.join(format!("{}.log", stamp));
+    let mut suffix = 1;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.log", stamp, suffix));
+        suffix += 1;
+    }
+
+    let file = File::create(&path)?;
+    Ok((file, path))
+}
+
+fn prune_old_logs(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    // Nomes com timestamp ordenam cronologicamente
+    let mut logs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("log"))
+        .collect();
+    logs.sort();
+
+    let excess = (logs.len() + 1).saturating_sub(MAX_LOG_FILES);
+    for old in logs.into_iter().take(excess) {
+        let _ = fs::remove_file(old);
+    }
+}
+
+// Data/hora UTC decomposta a partir de um SystemTime
+pub(super) fn utc_parts(time: SystemTime) -> (i64, u32, u32, u64, u64, u64) {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let days = (secs / 86_400) as i64;
+    let seconds_of_day = secs % 86_400;
+
+    // Conversão de dias desde 1970-01-01 para data civil (algoritmo de H. Hinnant)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (
+        year,
+        month,
+        day,
+        seconds_of_day / 3_600,
+        (seconds_of_day % 3_600) / 60,
+        seconds_of_day % 60,
+    )
+}
+
+fn format_file_timestamp(time: SystemTime) -> String {
+    let (year, month, day, hour, minute, second) = utc_parts(time);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}-{:02}-{:02}Z",
+        year, month, day, hour, minute, second
+    )
+}
+
+fn format_time_of_day(time: SystemTime) -> String {
+    let (_, _, _, hour, minute, second) = utc_parts(time);
+    format!("{:02}:{:02}:{:02}", hour, minute, second)
+}
@@ -0,0 +1,386 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{gettext, gettext_f};
+use crate::AppImageMetadata;
+
+use super::error::{BuildError, BuildResult, InputField, IoResultExt};
+use super::log::{utc_parts, BuildLog};
+use super::workdir::sanitize_package_name;
+use super::{appdir_output_path, default_app_id, md5_output_path, OutputMode};
+
+pub const DEFAULT_FILE_NAME_TEMPLATE: &str = "{name}";
+const APPIMAGE_EXTENSION: &str = ".AppImage";
+
+// Variáveis aceitas no modelo do nome do arquivo
+pub const FILE_NAME_VARIABLES: [&str; 6] = ["name", "package", "version", "arch", "app_id", "date"];
+
+// Caracteres proibidos em nomes de arquivo no Linux ou em sistemas de arquivos
+// comuns em pendrives (FAT, NTFS)
+const ILLEGAL_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+// Separadores removidos junto com variáveis vazias ("{name}-{version}" sem versão)
+const SEPARATORS: &[char] = &['-', '_', '.', ' '];
+
+// Limite de bytes de um nome de arquivo na maioria dos sistemas de arquivos
+const MAX_FILE_NAME_BYTES: usize = 255;
+
+// O que fazer quando o arquivo de saída já existe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExistingOutput {
+    // Não gerar; a interface pergunta antes e a linha de comando pede --force
+    #[default]
+    Fail,
+    Overwrite,
+    // Nome.AppImage -> Nome-1.AppImage, Nome-2.AppImage...
+    Increment,
+    // O arquivo anterior é renomeado para Nome.AppImage.bak
+    Backup,
+}
+
+impl ExistingOutput {
+    pub const ALL: [ExistingOutput; 4] = [
+        ExistingOutput::Fail,
+        ExistingOutput::Overwrite,
+        ExistingOutput::Increment,
+        ExistingOutput::Backup,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExistingOutput::Fail => gettext("Ask"),
+            ExistingOutput::Overwrite => gettext("Replace"),
+            ExistingOutput::Increment => gettext("Add a number (Name-1.AppImage)"),
+            ExistingOutput::Backup => gettext("Keep a backup (.bak)"),
+        }
+    }
+
+    // Identificador usado na linha de comando
+    pub fn id(self) -> &'static str {
+        match self {
+            ExistingOutput::Fail => "fail",
+            ExistingOutput::Overwrite => "overwrite",
+            ExistingOutput::Increment => "increment",
+            ExistingOutput::Backup => "backup",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|policy| policy.id() == id)
+    }
+}
+
+// Nome do arquivo a partir do modelo, ex: "{name}-{version}-{arch}" ->
+// "Editor-1.2.0-x86_64.AppImage". A extensão é acrescentada quando falta; {date}
+// vem de `epoch`, quando informado
+pub fn render_file_name(template: &str, metadata: &AppImageMetadata, epoch: Option<u64>) -> BuildResult<String> {
+    let invalid = |message: String| Err(BuildError::invalid_input(InputField::FileName, message));
+
+    let template = if template.trim().is_empty() {
+        DEFAULT_FILE_NAME_TEMPLATE
+    } else {
+        template.trim()
+    };
+
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            return invalid(gettext_f("'{' without a matching '}' in \"{template}\"", &[("template", &template)]));
+        };
+        let variable = &rest[start + 1..start + end];
+        let Some(value) = variable_value(variable, metadata, epoch) else {
+            return invalid(gettext_f(
+                "unknown variable {{variable}} (available: {variables})",
+                &[
+                    ("variable", &variable),
+                    ("variables", &FILE_NAME_VARIABLES.map(|v| format!("{{{}}}", v)).join(", ")),
+                ],
+            ));
+        };
+        if value.is_empty() {
+            rendered.truncate(rendered.trim_end_matches(SEPARATORS).len());
+        }
+        rendered.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+    rendered.push_str(rest);
+
+    let sanitized: String = rendered
+        .chars()
+        .map(|c| if ILLEGAL_CHARS.contains(&c) || c.is_control() { '_' } else { c })
+        .collect();
+    // ".AppImage" sozinho não é um nome; "App.appimage" vira "App.AppImage"
+    let stem = strip_appimage_extension(&sanitized).trim_matches(SEPARATORS);
+    if stem.is_empty() {
+        return invalid(gettext_f("the template \"{template}\" results in an empty name", &[("template", &template)]));
+    }
+    let file_name = format!("{}{}", stem, APPIMAGE_EXTENSION);
+    if file_name.len() > MAX_FILE_NAME_BYTES {
+        return invalid(gettext_f(
+            "name longer than {max} bytes: {name}",
+            &[("max", &MAX_FILE_NAME_BYTES), ("name", &file_name)],
+        ));
+    }
+    Ok(file_name)
+}
+
+fn strip_appimage_extension(name: &str) -> &str {
+    let split = name.len().saturating_sub(APPIMAGE_EXTENSION.len());
+    match (name.get(..split), name.get(split..)) {
+        (Some(stem), Some(extension)) if extension.eq_ignore_ascii_case(APPIMAGE_EXTENSION) => stem,
+        _ => name,
+    }
+}
+
+fn variable_value(variable: &str, metadata: &AppImageMetadata, epoch: Option<u64>) -> Option<String> {
+    let package_name = sanitize_package_name(&metadata.name);
+    let value = match variable {
+        "name" => metadata.name.trim().to_string(),
+        "package" => package_name,
+        "version" => metadata.version.trim().to_string(),
+        "arch" => std::env::var("ARCH").unwrap_or_else(|_| std::env::consts::ARCH.to_string()),
+        "app_id" => default_app_id(metadata, &package_name),
+        "date" => file_name_date(epoch),
+        _ => return None,
+    };
+    Some(value)
+}
+
+// AAAA-MM-DD em UTC; `epoch` ou SOURCE_DATE_EPOCH, quando definidos, fixam a
+// data como nos builds reprodutíveis
+fn file_name_date(epoch: Option<u64>) -> String {
+    let time = epoch
+        .or_else(|| std::env::var("SOURCE_DATE_EPOCH").ok()?.trim().parse::<u64>().ok())
+        .map(|epoch| UNIX_EPOCH + Duration::from_secs(epoch))
+        .unwrap_or_else(SystemTime::now);
+    let (year, month, day, ..) = utc_parts(time);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Caminhos gravados pela geração no modo escolhido
+pub fn output_artifacts(output_path: &Path, mode: OutputMode) -> Vec<PathBuf> {
+    let mut artifacts = Vec::new();
+    if mode.builds_appimage() {
+        artifacts.push(output_path.to_path_buf());
+        artifacts.push(md5_output_path(output_path));
+    }
+    if mode.exports_appdir() {
+        artifacts.push(appdir_output_path(output_path));
+    }
+    artifacts
+}
+
+// Artefatos que a geração substituiria
+pub fn existing_outputs(output_path: &Path, mode: OutputMode) -> Vec<PathBuf> {
+    output_artifacts(output_path, mode)
+        .into_iter()
+        .filter(|path| fs::symlink_metadata(path).is_ok())
+        .collect()
+}
+
+// Caminhos reservados pelas gerações em andamento neste processo: duas gerações
+// da fila nunca gravam no mesmo destino, mesmo antes de o primeiro existir
+static RESERVED_OUTPUTS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+// Reserva do caminho final de uma geração, liberada quando ela termina
+pub(super) struct OutputReservation {
+    path: PathBuf,
+}
+
+impl OutputReservation {
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for OutputReservation {
+    fn drop(&mut self) {
+        let mut reserved = RESERVED_OUTPUTS.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(index) = reserved.iter().position(|path| *path == self.path) {
+            reserved.swap_remove(index);
+        }
+    }
+}
+
+// Caminho efetivamente usado pela geração, conforme a política para arquivos
+// existentes, reservado até o fim da geração
+pub(super) fn resolve_output_path(
+    output_path: &Path,
+    mode: OutputMode,
+    policy: ExistingOutput,
+    log: &BuildLog,
+) -> BuildResult<OutputReservation> {
+    let mut reserved = RESERVED_OUTPUTS.lock().unwrap_or_else(PoisonError::into_inner);
+    let path = choose_output_path(output_path, mode, policy, &reserved, log)?;
+    reserved.push(path.clone());
+    Ok(OutputReservation { path })
+}
+
+fn choose_output_path(
+    output_path: &Path,
+    mode: OutputMode,
+    policy: ExistingOutput,
+    reserved: &[PathBuf],
+    log: &BuildLog,
+) -> BuildResult<PathBuf> {
+    let is_reserved = |path: &Path| reserved.iter().any(|other| other == path);
+    let busy = is_reserved(output_path);
+    let existing = existing_outputs(output_path, mode);
+    let first = match existing.first() {
+        Some(first) => first.as_path(),
+        None if busy => output_path,
+        None => return Ok(output_path.to_path_buf()),
+    };
+
+    match policy {
+        // Outra geração ainda vai gravar neste caminho: substituí-lo ou fazer
+        // backup perderia o resultado dela
+        ExistingOutput::Fail | ExistingOutput::Overwrite | ExistingOutput::Backup if busy => {
+            Err(BuildError::OutputBusy {
+                path: output_path.to_path_buf(),
+            })
+        }
+        ExistingOutput::Fail => Err(BuildError::OutputExists { path: first.to_path_buf() }),
+        ExistingOutput::Overwrite => {
+            log.warn(gettext_f("{path} will be replaced", &[("path", &first.display())]));
+            Ok(output_path.to_path_buf())
+        }
+        ExistingOutput::Backup => Ok(output_path.to_path_buf()),
+        ExistingOutput::Increment => {
+            let file_name = output_path.file_name().unwrap_or_default().to_string_lossy();
+            let stem = file_name.strip_suffix(APPIMAGE_EXTENSION).unwrap_or(&file_name);
+            let free = (1..)
+                .map(|n| output_path.with_file_name(format!("{}-{}{}", stem, n, APPIMAGE_EXTENSION)))
+                .find(|candidate| !is_reserved(candidate) && existing_outputs(candidate, mode).is_empty())
+                .unwrap_or_else(|| output_path.to_path_buf());
+            log.info(gettext_f(
+                "{path} already exists; building {free}",
+                &[("path", &first.display()), ("free", &free.display())],
+            ));
+            Ok(free)
+        }
+    }
+}
+
+// Renomeia os artefatos existentes para <nome>.bak antes de gravar os novos,
+// substituindo um backup anterior
+pub(super) fn backup_existing(output_path: &Path, mode: OutputMode, log: &BuildLog) -> BuildResult<()> {
+    for path in existing_outputs(output_path, mode) {
+        let mut backup = path.clone().into_os_string();
+        backup.push(".bak");
+        let backup = PathBuf::from(backup);
+
+        match fs::symlink_metadata(&backup) {
+            Ok(meta) if meta.is_dir() => fs::remove_dir_all(&backup).at_path(&backup)?,
+            Ok(_) => fs::remove_file(&backup).at_path(&backup)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(BuildError::io(&backup, err)),
+        }
+        fs::rename(&path, &backup).at_path(&path)?;
+        log.info(gettext_f(
+            "Backup of {path} at {backup}",
+            &[("path", &path.display()), ("backup", &backup.display())],
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::appimage::BuildEvent;
+
+    fn metadata(name: &str, version: &str) -> AppImageMetadata {
+        AppImageMetadata {
+            name: name.to_string(),
+            version: version.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn render(template: &str, metadata: &AppImageMetadata) -> String {
+        render_file_name(template, metadata, None).unwrap()
+    }
+
+    #[test]
+    fn render_file_name_fills_variables() {
+        let editor = metadata("My Editor", "1.2.0");
+        let arch = variable_value("arch", &editor, None).unwrap();
+        assert_eq!(render("", &editor), "My Editor.AppImage");
+        assert_eq!(render("{package}-{version}", &editor), "my-editor-1.2.0.AppImage");
+        assert_eq!(
+            render("{name}-{version}-{arch}", &editor),
+            format!("My Editor-1.2.0-{}.AppImage", arch)
+        );
+        assert_eq!(
+            render_file_name("{package}_{date}", &editor, Some(1_700_000_000)).unwrap(),
+            "my-editor_2023-11-14.AppImage"
+        );
+    }
+
+    #[test]
+    fn render_file_name_drops_separators_of_empty_variables() {
+        let unversioned = metadata("Editor", "");
+        assert_eq!(render("{name}-{version}", &unversioned), "Editor.AppImage");
+        assert_eq!(render("{version}_{name}", &unversioned), "Editor.AppImage");
+        assert_eq!(render("{name}-{version}.AppImage", &unversioned), "Editor.AppImage");
+    }
+
+    #[test]
+    fn render_file_name_sanitizes_and_normalizes_extension() {
+        assert_eq!(render("{name}", &metadata("a/b:c", "")), "a_b_c.AppImage");
+        assert_eq!(render("{name}", &metadata("Tab\there", "")), "Tab_here.AppImage");
+        assert_eq!(render("{name}.appimage", &metadata("App", "")), "App.AppImage");
+        assert_eq!(render("{name}.", &metadata("App", "")), "App.AppImage");
+    }
+
+    #[test]
+    fn render_file_name_rejects_invalid_templates() {
+        let app = metadata("App", "");
+        for template in ["{name", "{nome}", "{version}", ".AppImage", "-{version}-"] {
+            let result = render_file_name(template, &app, None);
+            assert!(
+                matches!(result, Err(BuildError::InvalidInput { field: InputField::FileName, .. })),
+                "\"{}\" deveria ser recusado: {:?}",
+                template,
+                result
+            );
+        }
+        let long = metadata(&"a".repeat(MAX_FILE_NAME_BYTES), "");
+        assert!(render_file_name("{name}", &long, None).is_err());
+    }
+
+    #[test]
+    fn queued_builds_reserve_distinct_outputs() {
+        let on_event = |_: BuildEvent| {};
+        let log = BuildLog::new(&on_event);
+        let output = std::env::temp_dir()
+            .join(format!("appimage-creator-naming-{}", std::process::id()))
+            .join("App.AppImage");
+
+        let first = resolve_output_path(&output, OutputMode::AppImage, ExistingOutput::Increment, &log).unwrap();
+        assert_eq!(first.path(), output);
+
+        // O primeiro ainda não gravou nada, mas o caminho já está reservado
+        let second = resolve_output_path(&output, OutputMode::AppImage, ExistingOutput::Increment, &log).unwrap();
+        assert_eq!(second.path(), output.with_file_name("App-1.AppImage"));
+
+        for policy in [ExistingOutput::Fail, ExistingOutput::Overwrite, ExistingOutput::Backup] {
+            let result = resolve_output_path(&output, OutputMode::AppImage, policy, &log);
+            assert!(matches!(result, Err(BuildError::OutputBusy { .. })), "{:?} deveria recusar", policy);
+        }
+
+        // Ao terminar, a reserva é liberada
+        drop(first);
+        let again = resolve_output_path(&output, OutputMode::AppImage, ExistingOutput::Backup, &log).unwrap();
+        assert_eq!(again.path(), output);
+    }
+}
@@ -0,0 +1,748 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{gettext, gettext_f};
+use crate::AppImageMetadata;
+
+use super::archive::{ArWriter, TarWriter};
+use super::atomic::{self, PendingFile};
+use super::error::{BuildResult, IoResultExt};
+use super::log::BuildLog;
+use super::portable::{self, TarCompression};
+use super::reproducible::sorted_entries;
+use super::workdir::sanitize_package_name;
+use super::{begin_stage, compute_md5, BuildEvent, BuildStage, CancelFlag, APPIMAGE_ICON_NAME};
+
+// Versão usada quando os metadados não informam uma (a mesma do Cargo.toml gerado)
+const DEFAULT_VERSION: &str = "1.0.0";
+
+// Limites do snapcraft para o nome e o resumo
+const MAX_SNAP_NAME: usize = 40;
+const MAX_SNAP_SUMMARY: usize = 78;
+
+// Os arquivos gerados ficam em inglês, qualquer que seja o idioma da interface
+const RPM_SPEC_TEMPLATE: &str = r#"# Generated by AppImage Creator. To build the package in this folder:
+#   rpmbuild -bb --define "_sourcedir $PWD" --define "_rpmdir $PWD" {spec}
+%global debug_package %{nil}
+
+Name:           {name}
+Version:        {version}
+Release:        1
+Summary:        {summary}
+License:        {license}{url}
+Source0:        {source}
+BuildArch:      {arch}
+
+%description
+{description}
+
+%prep
+%setup -q
+
+%install
+mkdir -p %{buildroot}
+cp -a usr %{buildroot}/
+
+%files
+{files}
+"#;
+
+const FLATPAK_MANIFEST_TEMPLATE: &str = r#"# Skeleton generated by AppImage Creator: review the runtime and permissions.
+# To build: flatpak-builder --user --install build {manifest}
+app-id: {app_id}
+runtime: org.freedesktop.Platform
+runtime-version: '24.08'
+sdk: org.freedesktop.Sdk
+command: {exec}
+finish-args:
+  - --share=ipc
+  - --socket=fallback-x11
+  - --socket=wayland
+  - --device=dri
+modules:
+  - name: {name}
+    buildsystem: simple
+    build-commands:
+{build_commands}
+    sources:
+      - type: archive
+        path: {source}
+"#;
+
+const SNAPCRAFT_TEMPLATE: &str = r#"# Skeleton generated by AppImage Creator: review base, extensions and plugs.
+# To build, run `snapcraft` in the folder that contains snap/
+name: {name}
+base: core22
+version: {version}
+summary: {summary}
+description: {description}{license}{website}
+grade: stable
+confinement: strict
+
+apps:
+  {name}:
+    command: {command}
+    desktop: {desktop}
+    # Remove if the application does not use GTK
+    extensions: [gnome]
+    plugs: [home, network]
+
+parts:
+  {name}:
+    plugin: dump
+    source: {source}
+"#;
+
+// Formatos gerados a partir do mesmo AppDir, além do AppImage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageFormat {
+    Deb,
+    // .spec e arquivo de fontes para o rpmbuild
+    Rpm,
+    // Manifesto para o flatpak-builder
+    Flatpak,
+    // snap/snapcraft.yaml
+    Snap,
+    // AppDir com lançador e install.sh, para sistemas sem FUSE
+    #[serde(rename = "tar.zst")]
+    PortableZst,
+    #[serde(rename = "tar.gz")]
+    PortableGz,
+}
+
+impl PackageFormat {
+    pub const ALL: [PackageFormat; 6] = [
+        PackageFormat::Deb,
+        PackageFormat::Rpm,
+        PackageFormat::Flatpak,
+        PackageFormat::Snap,
+        PackageFormat::PortableZst,
+        PackageFormat::PortableGz,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PackageFormat::Deb => gettext(".deb package"),
+            PackageFormat::Rpm => gettext(".rpm spec"),
+            PackageFormat::Flatpak => gettext("Flatpak manifest"),
+            PackageFormat::Snap => "snapcraft.yaml",
+            PackageFormat::PortableZst => gettext("Portable .tar.zst archive"),
+            PackageFormat::PortableGz => gettext("Portable .tar.gz archive"),
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            PackageFormat::Deb => gettext("Ready-to-install package for Debian, Ubuntu and derivatives"),
+            PackageFormat::Rpm => gettext("Spec and sources to build the package with rpmbuild (Fedora, openSUSE)"),
+            PackageFormat::Flatpak => gettext("Manifest skeleton for flatpak-builder"),
+            PackageFormat::Snap => gettext("Project skeleton for snapcraft"),
+            PackageFormat::PortableZst => gettext("AppDir with a launcher and install.sh, for systems without FUSE"),
+            PackageFormat::PortableGz => gettext("Like the .tar.zst, compressed with gzip for older systems"),
+        }
+    }
+
+    // Identificador usado na linha de comando
+    pub fn id(self) -> &'static str {
+        match self {
+            PackageFormat::Deb => "deb",
+            PackageFormat::Rpm => "rpm",
+            PackageFormat::Flatpak => "flatpak",
+            PackageFormat::Snap => "snap",
+            PackageFormat::PortableZst => "tar.zst",
+            PackageFormat::PortableGz => "tar.gz",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.id() == id)
+    }
+
+    // rpm, Flatpak e Snap compilam a partir de um arquivo com a árvore usr/
+    fn uses_source_archive(self) -> bool {
+        matches!(self, PackageFormat::Rpm | PackageFormat::Flatpak | PackageFormat::Snap)
+    }
+
+    fn tar_compression(self) -> Option<TarCompression> {
+        match self {
+            PackageFormat::PortableZst => Some(TarCompression::Zstd),
+            PackageFormat::PortableGz => Some(TarCompression::Gzip),
+            _ => None,
+        }
+    }
+}
+
+// Nomes dos arquivos de cada formato, derivados dos metadados
+struct PackageNames {
+    package: String,
+    version: String,
+}
+
+impl PackageNames {
+    fn new(metadata: &AppImageMetadata) -> Self {
+        let version = metadata.version.trim();
+        PackageNames {
+            package: sanitize_package_name(&metadata.name),
+            version: if version.is_empty() {
+                DEFAULT_VERSION.to_string()
+            } else {
+                version.to_string()
+            },
+        }
+    }
+
+    // Nomes de pacote Debian e Snap não aceitam '_'
+    fn deb_package(&self) -> String {
+        self.package.replace('_', "-")
+    }
+
+    fn snap_name(&self) -> String {
+        let mut name = self.deb_package();
+        name.truncate(MAX_SNAP_NAME);
+        name.trim_end_matches('-').to_string()
+    }
+
+    // Versões Debian começam com dígito e usam apenas [A-Za-z0-9.+~-]
+    fn deb_version(&self) -> String {
+        let version: String = self
+            .version
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || ".+~-".contains(c) { c } else { '+' })
+            .collect();
+        if version.starts_with(|c: char| c.is_ascii_digit()) {
+            version
+        } else {
+            format!("0~{}", version)
+        }
+    }
+
+    // O rpm não aceita '-' na versão
+    fn rpm_version(&self) -> String {
+        self.version
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || "._+~".contains(c) { c } else { '_' })
+            .collect()
+    }
+
+    fn deb_file(&self) -> String {
+        format!("{}_{}_{}.deb", self.deb_package(), self.deb_version(), deb_arch())
+    }
+
+    // Diretório raiz dentro do arquivo de fontes, como o %setup do rpm espera
+    fn source_dir(&self) -> String {
+        format!("{}-{}", self.package, self.rpm_version())
+    }
+
+    fn source_archive(&self) -> String {
+        format!("{}.tar.gz", self.source_dir())
+    }
+
+    // Diretório raiz do arquivo portátil, com a arquitetura como no AppImage
+    fn portable_dir(&self) -> String {
+        format!("{}-{}", self.source_dir(), std::env::consts::ARCH)
+    }
+
+    fn artifacts(&self, output_folder: &Path, format: PackageFormat) -> Vec<PathBuf> {
+        let mut artifacts = match format {
+            PackageFormat::Deb => vec![output_folder.join(self.deb_file())],
+            PackageFormat::Rpm => vec![output_folder.join(format!("{}.spec", self.package))],
+            PackageFormat::Flatpak => vec![output_folder.join(format!("{}.flatpak.yml", self.package))],
+            PackageFormat::Snap => vec![output_folder.join("snap").join("snapcraft.yaml")],
+            PackageFormat::PortableZst | PackageFormat::PortableGz => {
+                let extension = format.tar_compression().map(TarCompression::extension).unwrap_or_default();
+                let archive = format!("{}.{}", self.portable_dir(), extension);
+                vec![
+                    output_folder.join(&archive),
+                    output_folder.join(format!("{}.md5", archive)),
+                ]
+            }
+        };
+        if format.uses_source_archive() {
+            artifacts.push(output_folder.join(self.source_archive()));
+        }
+        artifacts
+    }
+}
+
+// Arquivos gravados na pasta de saída para os formatos escolhidos
+pub fn package_outputs(output_folder: &Path, metadata: &AppImageMetadata, formats: &[PackageFormat]) -> Vec<PathBuf> {
+    let names = PackageNames::new(metadata);
+    let mut outputs = Vec::new();
+    for format in formats {
+        for artifact in names.artifacts(output_folder, *format) {
+            if !outputs.contains(&artifact) {
+                outputs.push(artifact);
+            }
+        }
+    }
+    outputs
+}
+
+// AppDir montado pela geração e o que se sabe sobre ele
+pub(super) struct PackageSource<'a> {
+    pub(super) metadata: &'a AppImageMetadata,
+    pub(super) appdir: &'a Path,
+    pub(super) icon_name: &'a str,
+    pub(super) desktop_file_name: &'a str,
+    pub(super) app_id: &'a str,
+    // Diretório de trabalho para arquivos intermediários
+    pub(super) work_dir: &'a Path,
+    pub(super) epoch: Option<u64>,
+}
+
+impl PackageSource<'_> {
+    // O ícone canônico do AppImage ("icon.png") não vai para os pacotes: instalado
+    // no sistema, conflitaria com o de outros aplicativos
+    fn skips(&self, relative: &Path) -> bool {
+        self.icon_name != APPIMAGE_ICON_NAME
+            && relative == Path::new(ICONS_DIR).join(format!("{}.png", APPIMAGE_ICON_NAME))
+    }
+
+    fn usr_dir(&self) -> PathBuf {
+        self.appdir.join("usr")
+    }
+
+    // Arquivos e links da árvore usr/ empacotada, relativos a usr/
+    fn files(&self) -> BuildResult<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        collect_files(&self.usr_dir(), &self.usr_dir(), &|relative| self.skips(relative), &mut files)?;
+        Ok(files)
+    }
+
+    fn description(&self) -> String {
+        let comment = self.metadata.comment.trim();
+        if comment.is_empty() {
+            format!("{} application", self.metadata.name.trim())
+        } else {
+            comment.to_string()
+        }
+    }
+
+    fn metainfo_file_name(&self) -> Option<String> {
+        sorted_entries(&self.usr_dir().join("share/metainfo"))
+            .ok()?
+            .first()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+    }
+}
+
+const ICONS_DIR: &str = "share/icons/hicolor/256x256/apps";
+
+fn collect_files(root: &Path, dir: &Path, skip: &dyn Fn(&Path) -> bool, files: &mut Vec<PathBuf>) -> BuildResult<()> {
+    for entry in sorted_entries(dir)? {
+        let relative = entry.strip_prefix(root).unwrap_or(&entry).to_path_buf();
+        if skip(&relative) {
+            continue;
+        }
+        let meta = fs::symlink_metadata(&entry).at_path(&entry)?;
+        if meta.is_dir() {
+            collect_files(root, &entry, skip, files)?;
+        } else {
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
+
+// Gera os formatos escolhidos na pasta de saída e retorna os arquivos gravados
+pub(super) fn write_packages(
+    source: &PackageSource,
+    output_folder: &Path,
+    formats: &[PackageFormat],
+    log: &BuildLog,
+    cancel: &CancelFlag,
+) -> BuildResult<Vec<PathBuf>> {
+    begin_stage(BuildStage::Packages, log, cancel)?;
+    let names = PackageNames::new(source.metadata);
+    let mut written = Vec::new();
+
+    if formats.iter().any(|format| format.uses_source_archive()) {
+        let path = output_folder.join(names.source_archive());
+        write_source_archive(source, &names, &path)?;
+        log.info(gettext_f("Sources for rpm/Flatpak/Snap: {path}", &[("path", &path.display())]));
+        written.push(path);
+    }
+
+    for format in formats {
+        cancel.check()?;
+        let artifacts = names.artifacts(output_folder, *format);
+        let path = artifacts[0].clone();
+        match format {
+            PackageFormat::Deb => write_deb(source, &names, &path)?,
+            PackageFormat::Rpm => atomic::write_file(&path, rpm_spec(source, &names, &path)?.as_bytes())?,
+            PackageFormat::Flatpak => atomic::write_file(&path, flatpak_manifest(source, &names, &path).as_bytes())?,
+            PackageFormat::Snap => {
+                if let Some(snap_dir) = path.parent() {
+                    fs::create_dir_all(snap_dir).at_path(snap_dir)?;
+                }
+                atomic::write_file(&path, snapcraft_yaml(source, &names).as_bytes())?
+            }
+            PackageFormat::PortableZst | PackageFormat::PortableGz => portable::write_portable(
+                source,
+                &names.portable_dir(),
+                &names.package,
+                format.tar_compression().unwrap_or(TarCompression::Zstd),
+                &path,
+                &artifacts[1],
+            )?,
+        }
+        log.info(format!("{}: {}", format.label(), path.display()));
+        for artifact in artifacts {
+            if !written.contains(&artifact) {
+                written.push(artifact);
+            }
+        }
+    }
+
+    log.event(BuildEvent::StageFinished(BuildStage::Packages));
+    Ok(written)
+}
+
+// <pacote>-<versão>/usr/... compactado com gzip
+fn write_source_archive(source: &PackageSource, names: &PackageNames, path: &Path) -> BuildResult<()> {
+    let mut pending = PendingFile::create(path)?;
+    {
+        let encoder = GzEncoder::new(BufWriter::new(&mut pending), flate2::Compression::default());
+        let mut tar = TarWriter::new(encoder, path, source.epoch);
+        let name = Path::new(&names.source_dir()).join("usr");
+        tar.append_tree(&source.usr_dir(), &name, &|relative| source.skips(relative))?;
+        let encoder = tar.finish()?;
+        encoder.finish().and_then(|mut out| out.flush()).at_path(path)?;
+    }
+    pending.sync()?;
+    pending.commit()
+}
+
+// .deb: arquivo ar com debian-binary, control.tar.gz e data.tar.gz, instalando
+// a árvore usr/ do AppDir em /usr
+fn write_deb(source: &PackageSource, names: &PackageNames, path: &Path) -> BuildResult<()> {
+    let files = source.files()?;
+    let usr_dir = source.usr_dir();
+
+    let mut md5sums = String::new();
+    let mut installed_size = 0;
+    for relative in &files {
+        let file = usr_dir.join(relative);
+        let meta = fs::symlink_metadata(&file).at_path(&file)?;
+        if meta.is_file() {
+            md5sums.push_str(&format!("{}  usr/{}\n", compute_md5(&file)?, relative.display()));
+            installed_size += meta.len();
+        }
+    }
+
+    let metadata = source.metadata;
+    let description = source.description();
+    let mut control = format!(
+        "Package: {}\nVersion: {}\nArchitecture: {}\nMaintainer: {}\nInstalled-Size: {}\nSection: misc\nPriority: optional\n",
+        names.deb_package(),
+        names.deb_version(),
+        deb_arch(),
+        if metadata.author.trim().is_empty() {
+            "Unknown"
+        } else {
+            metadata.author.trim()
+        },
+        installed_size.div_ceil(1024),
+    );
+    if !metadata.website.trim().is_empty() {
+        control.push_str(&format!("Homepage: {}\n", metadata.website.trim()));
+    }
+    control.push_str(&format!("Description: {}\n", single_line(&description)));
+
+    let mut control_tar = TarWriter::new(
+        GzEncoder::new(Vec::new(), flate2::Compression::default()),
+        path,
+        source.epoch,
+    );
+    control_tar.append_bytes("./control", 0o644, control.as_bytes())?;
+    control_tar.append_bytes("./md5sums", 0o644, md5sums.as_bytes())?;
+    let control_tar = control_tar.finish()?.finish().at_path(path)?;
+
+    // data.tar.gz pode ser grande: vai para o diretório de trabalho antes do ar
+    let data_path = source.work_dir.join("data.tar.gz");
+    {
+        let file = File::create(&data_path).at_path(&data_path)?;
+        let encoder = GzEncoder::new(BufWriter::new(file), flate2::Compression::default());
+        let mut tar = TarWriter::new(encoder, &data_path, source.epoch);
+        tar.append_tree(&usr_dir, Path::new("usr"), &|relative| source.skips(relative))?;
+        tar.finish()?.finish().and_then(|mut out| out.flush()).at_path(&data_path)?;
+    }
+    let data_size = fs::metadata(&data_path).at_path(&data_path)?.len();
+
+    let mut pending = PendingFile::create(path)?;
+    {
+        let mut ar = ArWriter::new(BufWriter::new(&mut pending), source.epoch).at_path(path)?;
+        ar.append("debian-binary", 4, &mut &b"2.0\n"[..]).at_path(path)?;
+        ar.append("control.tar.gz", control_tar.len() as u64, &mut control_tar.as_slice())
+            .at_path(path)?;
+        let mut data = File::open(&data_path).at_path(&data_path)?;
+        ar.append("data.tar.gz", data_size, &mut data).at_path(path)?;
+        ar.finish().flush().at_path(path)?;
+    }
+    let _ = fs::remove_file(&data_path);
+    pending.sync()?;
+    pending.commit()
+}
+
+fn rpm_spec(source: &PackageSource, names: &PackageNames, path: &Path) -> BuildResult<String> {
+    let metadata = source.metadata;
+    let files: Vec<String> = source
+        .files()?
+        .iter()
+        .map(|relative| format!("\"/usr/{}\"", relative.display()))
+        .collect();
+    let license = metadata.license.trim();
+    let website = metadata.website.trim();
+    let url = if website.is_empty() {
+        String::new()
+    } else {
+        format!("\nURL:            {}", website)
+    };
+
+    Ok(fill_template(
+        RPM_SPEC_TEMPLATE,
+        &[
+            ("spec", &path.file_name().unwrap_or_default().to_string_lossy()),
+            ("name", &names.package),
+            ("version", &names.rpm_version()),
+            ("summary", &single_line(&source.description())),
+            ("license", if license.is_empty() { "LicenseRef-Unknown" } else { license }),
+            ("url", &url),
+            ("source", &names.source_archive()),
+            ("arch", rpm_arch()),
+            ("description", &source.description()),
+            ("files", &files.join("\n")),
+        ],
+    ))
+}
+
+// Flatpak exige que .desktop, ícone e metainfo usem o ID do aplicativo
+fn flatpak_manifest(source: &PackageSource, names: &PackageNames, path: &Path) -> String {
+    let app_id = source.app_id;
+    let applications = "/app/share/applications";
+    let icons = format!("/app/{}", ICONS_DIR);
+    let desktop = format!("{}/{}.desktop", applications, app_id);
+
+    let mut commands = vec!["cp -a usr/. /app/".to_string()];
+    if source.desktop_file_name != format!("{}.desktop", app_id) {
+        commands.push(format!(
+            "mv {} {}",
+            shell_quote(&format!("{}/{}", applications, source.desktop_file_name)),
+            shell_quote(&desktop)
+        ));
+    }
+    if source.icon_name != app_id {
+        commands.push(format!(
+            "mv {} {}",
+            shell_quote(&format!("{}/{}.png", icons, source.icon_name)),
+            shell_quote(&format!("{}/{}.png", icons, app_id))
+        ));
+        commands.push(format!("desktop-file-edit --set-icon={} {}", app_id, shell_quote(&desktop)));
+    }
+    if let Some(metainfo) = source.metainfo_file_name() {
+        let target = format!("/app/share/metainfo/{}.metainfo.xml", app_id);
+        if metainfo != format!("{}.metainfo.xml", app_id) {
+            commands.push(format!(
+                "mv {} {}",
+                shell_quote(&format!("/app/share/metainfo/{}", metainfo)),
+                shell_quote(&target)
+            ));
+        }
+        commands.push(format!(
+            "sed -i 's|<launchable type=\"desktop-id\">.*</launchable>|<launchable type=\"desktop-id\">{}.desktop</launchable>|' {}",
+            app_id,
+            shell_quote(&target)
+        ));
+    }
+
+    let build_commands: Vec<String> = commands
+        .iter()
+        .map(|command| format!("      - {}", yaml_string(command)))
+        .collect();
+
+    fill_template(
+        FLATPAK_MANIFEST_TEMPLATE,
+        &[
+            ("manifest", &path.file_name().unwrap_or_default().to_string_lossy()),
+            ("app_id", app_id),
+            ("exec", &yaml_string(&source.metadata.exec)),
+            ("name", &names.package),
+            ("build_commands", &build_commands.join("\n")),
+            ("source", &yaml_string(&names.source_archive())),
+        ],
+    )
+}
+
+fn snapcraft_yaml(source: &PackageSource, names: &PackageNames) -> String {
+    let metadata = source.metadata;
+    let summary: String = single_line(&source.description()).chars().take(MAX_SNAP_SUMMARY).collect();
+    let license = metadata.license.trim();
+    let license = if license.is_empty() {
+        String::new()
+    } else {
+        format!("\nlicense: {}", yaml_string(license))
+    };
+    let website = metadata.website.trim();
+    let website = if website.is_empty() {
+        String::new()
+    } else {
+        format!("\nwebsite: {}", yaml_string(website))
+    };
+
+    fill_template(
+        SNAPCRAFT_TEMPLATE,
+        &[
+            ("name", &names.snap_name()),
+            ("version", &yaml_string(&names.version)),
+            ("summary", &yaml_string(&summary)),
+            ("description", &yaml_string(&source.description())),
+            ("license", &license),
+            ("website", &website),
+            ("command", &yaml_string(&format!("usr/bin/{}", metadata.exec))),
+            ("desktop", &yaml_string(&format!("usr/share/applications/{}", source.desktop_file_name))),
+            // O projeto fica na pasta de saída, ao lado do arquivo de fontes
+            ("source", &yaml_string(&names.source_archive())),
+        ],
+    )
+}
+
+// Preenche os campos {nome} do modelo em uma só passagem, sem reler os valores
+// inseridos: um "{arch}" no texto do usuário fica como está. Chaves que não
+// estão em `values` (como o %{buildroot} do rpm) também são mantidas
+fn fill_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let key = &after[..end];
+            values.iter().find(|(name, _)| *name == key).map(|(_, value)| (*value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                rendered.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+// Strings JSON são strings YAML válidas, com todos os escapes necessários
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("'{}'", value.replace('\'', "''")))
+}
+
+pub(super) fn shell_quote(value: &str) -> String {
+    if value.chars().all(|c| c.is_ascii_alphanumeric() || "/._-".contains(c)) {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+pub(super) fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Arquiteturas com nomes diferentes no Debian e no rpm
+fn deb_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "x86" => "i386",
+        "arm" => "armhf",
+        "powerpc64" => "ppc64el",
+        arch => arch,
+    }
+}
+
+fn rpm_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86" => "i686",
+        "arm" => "armv7hl",
+        "powerpc64" => "ppc64le",
+        arch => arch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(name: &str, version: &str) -> PackageNames {
+        PackageNames::new(&AppImageMetadata {
+            name: name.to_string(),
+            version: version.to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn fill_template_is_single_pass() {
+        let template = "Name: {name}\nArch: {arch}\n%{buildroot} {unknown} {open";
+        let rendered = fill_template(template, &[("name", "{arch} {name}"), ("arch", "x86_64")]);
+        assert_eq!(rendered, "Name: {arch} {name}\nArch: x86_64\n%{buildroot} {unknown} {open");
+    }
+
+    #[test]
+    fn deb_version_is_valid() {
+        assert_eq!(names("App", "1.2.0").deb_version(), "1.2.0");
+        assert_eq!(names("App", "1.0-beta_2").deb_version(), "1.0-beta+2");
+        assert_eq!(names("App", "v2.0").deb_version(), "0~v2.0");
+        assert_eq!(names("App", "").deb_version(), DEFAULT_VERSION);
+    }
+
+    #[test]
+    fn rpm_version_has_no_dashes() {
+        assert_eq!(names("App", "1.2.0").rpm_version(), "1.2.0");
+        assert_eq!(names("App", "1.0-beta 2").rpm_version(), "1.0_beta_2");
+        assert_eq!(names("App", "2.0~rc1+git").rpm_version(), "2.0~rc1+git");
+    }
+
+    #[test]
+    fn snap_name_is_truncated() {
+        let long = names(&"a".repeat(MAX_SNAP_NAME + 10), "");
+        assert_eq!(long.snap_name(), "a".repeat(MAX_SNAP_NAME));
+
+        // O corte não deixa '-' no final
+        let dashed = names(&format!("{} b", "a".repeat(MAX_SNAP_NAME - 1)), "");
+        assert_eq!(dashed.snap_name(), "a".repeat(MAX_SNAP_NAME - 1));
+        assert!(names("My_App", "").snap_name().chars().all(|c| c != '_'));
+    }
+
+    #[test]
+    fn snapcraft_yaml_keeps_user_placeholders() {
+        let metadata = AppImageMetadata {
+            name: "Demo".to_string(),
+            comment: "Prints {command} and {source}".to_string(),
+            exec: "demo".to_string(),
+            ..Default::default()
+        };
+        let source = PackageSource {
+            metadata: &metadata,
+            appdir: Path::new("/nonexistent"),
+            icon_name: "demo",
+            desktop_file_name: "demo.desktop",
+            app_id: "io.example.Demo",
+            work_dir: Path::new("/nonexistent"),
+            epoch: None,
+        };
+
+        let yaml = snapcraft_yaml(&source, &PackageNames::new(&metadata));
+        assert!(yaml.contains("description: \"Prints {command} and {source}\"\n"), "{}", yaml);
+        assert!(yaml.contains("command: \"usr/bin/demo\"\n"), "{}", yaml);
+    }
+}
@@ -0,0 +1,125 @@
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use flate2::write::GzEncoder;
+
+use super::archive::TarWriter;
+use super::atomic::PendingFile;
+use super::error::{BuildResult, IoResultExt};
+use super::packages::{shell_quote, single_line, PackageSource};
+use super::{compute_md5, write_md5_file};
+
+// Lançador no topo do arquivo: executa o AppDir extraído, sem FUSE
+const LAUNCHER_TEMPLATE: &str = r#"#!/bin/sh
+HERE="$(dirname "$(readlink -f "$0")")"
+exec "$HERE/AppDir/AppRun" "$@"
+"#;
+
+const INSTALL_TEMPLATE: &str = r#"#!/bin/sh
+# Installs the shortcut and icons of {name} for the current user, pointing to
+# this folder (do not move it after installing). To uninstall: ./install.sh --uninstall
+set -e
+HERE="$(dirname "$(readlink -f "$0")")"
+DATA_DIR="${XDG_DATA_HOME:-$HOME/.local/share}"
+APP_NAME={app_name}
+DESKTOP_NAME={desktop}
+ICON_NAME={icon}
+LAUNCHER="$HERE/{launcher}"
+
+for icon in "$HERE"/AppDir/usr/share/icons/hicolor/*/apps/"$ICON_NAME".*; do
+    [ -e "$icon" ] || continue
+    apps_dir="$DATA_DIR/icons/hicolor/$(basename "$(dirname "$(dirname "$icon")")")/apps"
+    if [ "$1" = "--uninstall" ]; then
+        rm -f "$apps_dir/$(basename "$icon")"
+    else
+        mkdir -p "$apps_dir"
+        cp "$icon" "$apps_dir/"
+    fi
+done
+
+if [ "$1" = "--uninstall" ]; then
+    rm -f "$DATA_DIR/applications/$DESKTOP_NAME"
+    echo "$APP_NAME removed from $DATA_DIR"
+else
+    mkdir -p "$DATA_DIR/applications"
+    # Exec aponta para o lançador desta pasta, entre aspas (o caminho pode ter espaços)
+    exec_path="$(printf '%s' "$LAUNCHER" | sed 's/[\\&|]/\\&/g')"
+    sed -e "s|^Exec=[^ ]*|Exec=\"$exec_path\"|" -e '/^TryExec=/d' \
+        "$HERE/AppDir/$DESKTOP_NAME" > "$DATA_DIR/applications/$DESKTOP_NAME"
+    echo "$APP_NAME installed in $DATA_DIR"
+fi
+
+if command -v update-desktop-database >/dev/null 2>&1; then
+    update-desktop-database -q "$DATA_DIR/applications" || true
+fi
+if command -v gtk-update-icon-cache >/dev/null 2>&1; then
+    gtk-update-icon-cache -q -t "$DATA_DIR/icons/hicolor" || true
+fi
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TarCompression {
+    Zstd,
+    Gzip,
+}
+
+impl TarCompression {
+    pub(super) fn extension(self) -> &'static str {
+        match self {
+            TarCompression::Zstd => "tar.zst",
+            TarCompression::Gzip => "tar.gz",
+        }
+    }
+}
+
+// <dir>/AppDir com o AppDir completo, <dir>/<launcher> e <dir>/install.sh.
+// O hash do arquivo vai para <arquivo>.md5, como no AppImage
+pub(super) fn write_portable(
+    source: &PackageSource,
+    dir_name: &str,
+    launcher: &str,
+    compression: TarCompression,
+    path: &Path,
+    md5_path: &Path,
+) -> BuildResult<()> {
+    let install = INSTALL_TEMPLATE
+        .replace("{name}", &single_line(&source.metadata.name))
+        .replace("{app_name}", &shell_quote(source.metadata.name.trim()))
+        .replace("{desktop}", &shell_quote(source.desktop_file_name))
+        .replace("{icon}", &shell_quote(source.icon_name))
+        .replace("{launcher}", launcher);
+
+    let mut pending = PendingFile::create(path)?;
+    {
+        let output = BufWriter::new(&mut pending);
+        match compression {
+            TarCompression::Zstd => {
+                let encoder = zstd::stream::write::Encoder::new(output, zstd::DEFAULT_COMPRESSION_LEVEL).at_path(path)?;
+                let encoder = append_contents(TarWriter::new(encoder, path, source.epoch), source, dir_name, launcher, &install)?;
+                encoder.finish().and_then(|mut out| out.flush()).at_path(path)?;
+            }
+            TarCompression::Gzip => {
+                let encoder = GzEncoder::new(output, flate2::Compression::default());
+                let encoder = append_contents(TarWriter::new(encoder, path, source.epoch), source, dir_name, launcher, &install)?;
+                encoder.finish().and_then(|mut out| out.flush()).at_path(path)?;
+            }
+        }
+    }
+    pending.sync()?;
+    let hash = compute_md5(pending.path())?;
+    pending.commit()?;
+    write_md5_file(md5_path, path, &hash)
+}
+
+fn append_contents<W: Write>(
+    mut tar: TarWriter<W>,
+    source: &PackageSource,
+    dir_name: &str,
+    launcher: &str,
+    install: &str,
+) -> BuildResult<W> {
+    tar.append_bytes(&format!("{}/{}", dir_name, launcher), 0o755, LAUNCHER_TEMPLATE.as_bytes())?;
+    tar.append_bytes(&format!("{}/install.sh", dir_name), 0o755, install.as_bytes())?;
+    tar.append_tree(source.appdir, &Path::new(dir_name).join("AppDir"), &|_| false)?;
+    tar.finish()
+}
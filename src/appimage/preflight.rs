@@ -0,0 +1,317 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use super::compression::Compression;
+use super::{BackendKind, BuildOptions, OutputMode};
+use crate::i18n::{gettext, gettext_f, gettext_noop};
+
+// Instruções de instalação exibidas para cada ferramenta ausente
+pub(super) const CARGO_HINT: &str = "https://rustup.rs";
+pub(super) const CARGO_APPIMAGE_HINT: &str = "cargo install cargo-appimage";
+pub(super) const APPIMAGETOOL_HINT: &str = "https://github.com/AppImage/appimagetool/releases";
+pub(super) const RUNTIME_HINT: &str = gettext_noop(
+    "https://github.com/AppImage/type2-runtime/releases (save it to ~/.local/share/appimage-creator/runtime-<architecture>)",
+);
+const MKSQUASHFS_HINT: &str =
+    gettext_noop("sudo apt install squashfs-tools (or your distribution's squashfs-tools package)");
+const FUSE_HINT: &str = gettext_noop("sudo apt install fuse3 (or your distribution's fuse3 package)");
+
+// Resultado da verificação de uma ferramenta
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolCheck {
+    pub name: &'static str,
+    pub purpose: &'static str,
+    // Necessária para a geração com as opções atuais
+    pub required: bool,
+    pub path: Option<PathBuf>,
+    pub version: Option<String>,
+    // O que falta, quando a ferramenta não está utilizável
+    pub problem: Option<String>,
+    pub install_hint: String,
+}
+
+impl ToolCheck {
+    pub fn is_ok(&self) -> bool {
+        self.problem.is_none()
+    }
+
+    // "✓" disponível, "✗" ausente e necessária, "⚠" ausente mas opcional
+    pub fn symbol(&self) -> &'static str {
+        match (self.is_ok(), self.required) {
+            (true, _) => "✓",
+            (false, true) => "✗",
+            (false, false) => "⚠",
+        }
+    }
+
+    pub fn status(&self) -> String {
+        match (&self.problem, &self.version, &self.path) {
+            (Some(problem), _, _) => problem.clone(),
+            (None, Some(version), _) => version.clone(),
+            (None, None, Some(path)) => path.display().to_string(),
+            (None, None, None) => gettext("available").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreflightReport {
+    // Como o AppImage será compactado com as opções atuais
+    pub pipeline: String,
+    pub checks: Vec<ToolCheck>,
+}
+
+impl PreflightReport {
+    pub fn missing_required(&self) -> Vec<&ToolCheck> {
+        self.checks.iter().filter(|check| check.required && !check.is_ok()).collect()
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.missing_required().is_empty()
+    }
+}
+
+// Verifica as ferramentas usadas pela geração. Todas são listadas; as que as
+// opções atuais não usam aparecem como opcionais
+pub fn check_environment(options: &BuildOptions) -> PreflightReport {
+    let builds_appimage = options.output_mode.builds_appimage();
+    let backend = options.resolved_backend();
+    let uses_cargo_appimage = builds_appimage && backend == BackendKind::CargoAppimage;
+    // O cargo appimage também chama o appimagetool; só o backend nativo dispensa
+    let uses_appimagetool = builds_appimage && backend != BackendKind::Native;
+    let uses_native = builds_appimage && backend == BackendKind::Native;
+
+    let compression = options.squashfs_compression();
+    let pipeline = if !builds_appimage {
+        gettext_f("{mode} (no external tools)", &[("mode", &OutputMode::AppDirOnly.label())])
+    } else {
+        match backend {
+            BackendKind::Native => gettext_f(
+                "native SquashFS writer ({compression})",
+                &[("compression", &compression.unwrap_or_else(Compression::tool_default).describe())],
+            ),
+            BackendKind::CargoAppimage => gettext("cargo appimage (which calls appimagetool)").to_string(),
+            _ => match compression {
+                Some(compression) => format!("appimagetool ({})", compression.describe()),
+                None => gettext("appimagetool (default compression)").to_string(),
+            },
+        }
+    };
+
+    let appimagetool = check_appimagetool(uses_appimagetool);
+    // O appimagetool distribuído como AppImage traz o próprio mksquashfs
+    let appimagetool_is_appimage = appimagetool.path.as_deref().is_some_and(is_appimage);
+
+    PreflightReport {
+        pipeline,
+        checks: vec![
+            check_cargo(uses_cargo_appimage),
+            check_cargo_appimage(uses_cargo_appimage),
+            appimagetool,
+            check_mksquashfs(uses_appimagetool, appimagetool_is_appimage),
+            check_runtime(uses_native),
+            check_fuse(),
+        ],
+    }
+}
+
+// Subcomandos do cargo são executáveis cargo-<nome> no PATH ou em $CARGO_HOME/bin.
+// `cargo appimage --version` não serve: o cargo executa mesmo sem o subcomando
+pub(super) fn find_cargo_subcommand(name: &str) -> Option<PathBuf> {
+    let program = format!("cargo-{}", name);
+    find_in_path(&program).or_else(|| {
+        let cargo_home = std::env::var_os("CARGO_HOME")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))?;
+        let path = cargo_home.join("bin").join(&program);
+        is_executable_file(&path).then_some(path)
+    })
+}
+
+// Runtime do AppImage usado sem acesso à rede: versões recentes do appimagetool
+// baixam o runtime a cada geração quando nenhum é informado
+pub(super) fn runtime_file() -> Option<PathBuf> {
+    runtime_path().filter(|path| path.is_file())
+}
+
+fn runtime_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| {
+        dir.join("appimage-creator")
+            .join(format!("runtime-{}", std::env::consts::ARCH))
+    })
+}
+
+fn check_cargo(required: bool) -> ToolCheck {
+    let path = find_in_path("cargo");
+    ToolCheck {
+        name: "cargo",
+        purpose: gettext("Runs cargo appimage"),
+        required,
+        version: path.as_deref().and_then(|p| command_version(p, &["--version"])),
+        problem: path.is_none().then(|| gettext("not found").to_string()),
+        path,
+        install_hint: gettext(CARGO_HINT).to_string(),
+    }
+}
+
+fn check_cargo_appimage(required: bool) -> ToolCheck {
+    let path = find_cargo_subcommand("appimage");
+    ToolCheck {
+        name: "cargo-appimage",
+        purpose: gettext("Packs the AppImage with the cargo appimage backend"),
+        required,
+        version: path.as_ref().and_then(|_| installed_crate_version("cargo-appimage")),
+        problem: path.is_none().then(|| gettext("not found").to_string()),
+        path,
+        install_hint: gettext(CARGO_APPIMAGE_HINT).to_string(),
+    }
+}
+
+fn check_appimagetool(required: bool) -> ToolCheck {
+    let path = find_in_path("appimagetool");
+    ToolCheck {
+        name: "appimagetool",
+        purpose: gettext("Builds the SquashFS image and writes the AppImage"),
+        required,
+        version: path.as_deref().and_then(|p| command_version(p, &["--version"])),
+        problem: path.is_none().then(|| gettext("not found").to_string()),
+        path,
+        install_hint: gettext(APPIMAGETOOL_HINT).to_string(),
+    }
+}
+
+fn check_mksquashfs(uses_appimagetool: bool, embedded: bool) -> ToolCheck {
+    let path = find_in_path("mksquashfs");
+    let problem = match (&path, embedded) {
+        (Some(_), _) => None,
+        (None, true) => Some(gettext("not found (appimagetool as an AppImage ships its own)").to_string()),
+        (None, false) => Some(gettext("not found (needed by appimagetool installed as a package)").to_string()),
+    };
+    ToolCheck {
+        name: "mksquashfs",
+        purpose: gettext("Creates the SquashFS image for appimagetool"),
+        required: uses_appimagetool && !embedded,
+        version: path.as_deref().and_then(|p| command_version(p, &["-version"])),
+        problem,
+        path,
+        install_hint: gettext(MKSQUASHFS_HINT).to_string(),
+    }
+}
+
+fn check_runtime(required: bool) -> ToolCheck {
+    let expected = runtime_path();
+    let path = runtime_file();
+    let install_hint = match &expected {
+        Some(expected) => gettext_f(
+            "download runtime-{arch} from https://github.com/AppImage/type2-runtime/releases to {path}",
+            &[("arch", &std::env::consts::ARCH), ("path", &expected.display())],
+        ),
+        None => "https://github.com/AppImage/type2-runtime/releases".to_string(),
+    };
+    ToolCheck {
+        name: "runtime",
+        purpose: gettext("Executable header of the AppImage, written by the native backend or passed to appimagetool"),
+        required,
+        version: None,
+        problem: path.is_none().then(|| {
+            if required {
+                gettext("not found; required by the native backend").to_string()
+            } else {
+                gettext("not found; recent appimagetool versions download it from the internet").to_string()
+            }
+        }),
+        path,
+        install_hint,
+    }
+}
+
+// AppImages montam a imagem com FUSE; sem ele, só rodam com --appimage-extract-and-run
+fn check_fuse() -> ToolCheck {
+    let fusermount = find_fusermount();
+    ToolCheck {
+        name: "FUSE",
+        purpose: gettext("Runs the built AppImage for testing"),
+        required: false,
+        version: fusermount.as_deref().and_then(|p| command_version(p, &["-V"])),
+        problem: fuse_problem(),
+        path: fusermount,
+        install_hint: gettext(FUSE_HINT).to_string(),
+    }
+}
+
+fn find_fusermount() -> Option<PathBuf> {
+    find_in_path("fusermount3").or_else(|| find_in_path("fusermount"))
+}
+
+// Motivo pelo qual AppImages não podem ser montados neste sistema, se houver
+pub(super) fn fuse_problem() -> Option<String> {
+    if !Path::new("/dev/fuse").exists() {
+        Some(gettext("/dev/fuse does not exist; AppImages only run with --appimage-extract-and-run").to_string())
+    } else if find_fusermount().is_none() {
+        Some(gettext("fusermount not found; AppImages only run with --appimage-extract-and-run").to_string())
+    } else {
+        None
+    }
+}
+
+pub(super) fn find_in_path(program: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|path| is_executable_file(path))
+}
+
+#[cfg(unix)]
+fn is_executable_file(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable_file(path: &Path) -> bool {
+    path.is_file()
+}
+
+// Primeira linha da saída de `programa --version` (ou equivalente). Um
+// appimagetool em AppImage é extraído em vez de montado, para funcionar sem FUSE
+fn command_version(program: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .env("APPIMAGE_EXTRACT_AND_RUN", "1")
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+// Versão registrada pelo `cargo install` ("cargo-appimage v2.4.0:")
+fn installed_crate_version(crate_name: &str) -> Option<String> {
+    let output = Command::new("cargo")
+        .args(["install", "--list"])
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    let prefix = format!("{} v", crate_name);
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix(&prefix))
+        .map(|rest| format!("{} {}", crate_name, rest.trim_end_matches(':')))
+}
+
+// Cabeçalho ELF com a assinatura de AppImage tipo 2 ("AI\x02" no byte 8)
+fn is_appimage(path: &Path) -> bool {
+    let mut header = [0u8; 11];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|()| header.starts_with(b"\x7fELF") && &header[8..11] == b"AI\x02")
+}
@@ -0,0 +1,135 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::error::{BuildResult, IoResultExt};
+use crate::i18n::gettext_f;
+
+// Momento registrado em builds reprodutíveis: o das opções, SOURCE_DATE_EPOCH
+// quando definido (e válido) ou, na falta deles, a data de modificação de `fallback`
+pub(super) fn source_date_epoch(fixed: Option<u64>, fallback: &Path) -> (u64, EpochSource) {
+    if let Some(epoch) = fixed {
+        return (epoch, EpochSource::Options);
+    }
+    if let Ok(value) = std::env::var("SOURCE_DATE_EPOCH") {
+        match value.trim().parse::<u64>() {
+            Ok(epoch) => return (epoch, EpochSource::Environment),
+            Err(_) => {
+                return (
+                    mtime_epoch(fallback),
+                    EpochSource::InvalidEnvironment(value, fallback.to_path_buf()),
+                )
+            }
+        }
+    }
+    (mtime_epoch(fallback), EpochSource::File(fallback.to_path_buf()))
+}
+
+pub(super) enum EpochSource {
+    Options,
+    Environment,
+    InvalidEnvironment(String, PathBuf),
+    File(PathBuf),
+}
+
+impl EpochSource {
+    pub(super) fn describe(&self, epoch: u64) -> String {
+        match self {
+            EpochSource::Options | EpochSource::Environment => format!("SOURCE_DATE_EPOCH={}", epoch),
+            EpochSource::InvalidEnvironment(value, path) => gettext_f(
+                "invalid SOURCE_DATE_EPOCH ({value}); using {epoch} (date of {path})",
+                &[("value", &format!("{:?}", value)), ("epoch", &epoch), ("path", &path.display())],
+            ),
+            EpochSource::File(path) => gettext_f(
+                "SOURCE_DATE_EPOCH not set; using {epoch} (date of {path})",
+                &[("epoch", &epoch), ("path", &path.display())],
+            ),
+        }
+    }
+}
+
+fn mtime_epoch(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Datas e permissões fixas em toda a árvore, para que o conteúdo não dependa
+// da máquina nem do momento da geração. Diretórios e executáveis ficam 0755,
+// os demais arquivos 0644; links simbólicos são mantidos como estão
+pub(super) fn normalize_tree(root: &Path, epoch: u64) -> BuildResult<()> {
+    let time = UNIX_EPOCH + Duration::from_secs(epoch);
+    normalize_entry(root, time)
+}
+
+fn normalize_entry(path: &Path, time: SystemTime) -> BuildResult<()> {
+    let meta = fs::symlink_metadata(path).at_path(path)?;
+    if meta.file_type().is_symlink() {
+        return Ok(());
+    }
+
+    if meta.is_dir() {
+        set_mode(path, 0o755)?;
+        for child in sorted_entries(path)? {
+            normalize_entry(&child, time)?;
+        }
+        // Por último: alterar os filhos atualiza a data do diretório
+        File::open(path).and_then(|dir| dir.set_modified(time)).at_path(path)?;
+        return Ok(());
+    }
+
+    set_mode(path, if is_executable(&meta) { 0o755 } else { 0o644 })?;
+    File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified(time))
+        .at_path(path)
+}
+
+// Entradas de um diretório em ordem de nome, para percorrer sempre na mesma ordem
+pub(super) fn sorted_entries(dir: &Path) -> BuildResult<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(dir)
+        .at_path(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .at_path(dir)?;
+    entries.sort();
+    Ok(entries)
+}
+
+// Opções do mksquashfs que fixam dono, datas e atributos estendidos
+pub(super) fn squashfs_options(epoch: u64) -> Vec<String> {
+    vec![
+        "-all-root".to_string(),
+        "-no-xattrs".to_string(),
+        "-all-time".to_string(),
+        epoch.to_string(),
+        "-mkfs-time".to_string(),
+        epoch.to_string(),
+    ]
+}
+
+#[cfg(unix)]
+fn is_executable(meta: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_meta: &fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> BuildResult<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode)).at_path(path)
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> BuildResult<()> {
+    Ok(())
+}
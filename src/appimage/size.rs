@@ -0,0 +1,185 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::gettext_f;
+use crate::AppImageMetadata;
+
+use super::compression::CompressionSample;
+use super::error::{BuildResult, IoResultExt};
+use super::reproducible::sorted_entries;
+use super::workdir::sanitize_package_name;
+use super::APPIMAGE_ICON_NAME;
+
+// Quantos arquivos listar no relatório de tamanho
+const REPORT_LARGEST: usize = 5;
+
+// Tamanho aproximado de cada arquivo de texto gerado (AppRun, .desktop, metainfo)
+const GENERATED_TEXT_SIZE: u64 = 1024;
+
+// Arquivo do AppDir, com o caminho relativo à raiz
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppDirEntry {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SizeEstimate {
+    pub appdir_size: u64,
+    pub output_size: u64,
+    pub seconds: f64,
+}
+
+// Conteúdo previsto do AppDir. Cópias idênticas são gravadas uma única vez pelo
+// mksquashfs, e arquivos já comprimidos (ícones PNG) praticamente não diminuem
+struct PlannedContent {
+    compressible: u64,
+    incompressible: u64,
+    copies: u64,
+}
+
+// Estima o AppImage a partir de todos os arquivos que a geração colocará no AppDir
+pub fn estimate(metadata: &AppImageMetadata, sample: &CompressionSample) -> SizeEstimate {
+    let planned = planned_appdir(metadata);
+    let unique = planned.compressible + planned.incompressible;
+
+    SizeEstimate {
+        appdir_size: unique + planned.copies,
+        output_size: sample.estimate_output(planned.compressible, planned.incompressible),
+        seconds: sample.seconds_for(unique),
+    }
+}
+
+// Segue a montagem de `build_appimage`: binário, AppRun, .desktop (em
+// usr/share/applications e na raiz), metainfo, o ícone e suas cópias
+fn planned_appdir(metadata: &AppImageMetadata) -> PlannedContent {
+    let file_size = |path: &str| fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    let text_size = |imported: &str| {
+        if metadata.use_imported_files && !imported.is_empty() {
+            file_size(imported)
+        } else {
+            GENERATED_TEXT_SIZE
+        }
+    };
+
+    let desktop_size = text_size(&metadata.desktop_file_path);
+    let compressible = file_size(&metadata.binary_path)
+        + GENERATED_TEXT_SIZE
+        + desktop_size
+        + text_size(&metadata.metainfo_file_path);
+
+    // O PNG convertido só é conhecido após a conversão; o ícone original é a
+    // melhor aproximação. Cópias: ícone canônico e .DirIcon, mais o ícone nomeado
+    // e o da raiz quando o nome difere do canônico
+    let icon_size = file_size(&metadata.icon_path);
+    let icon_copies = if sanitize_package_name(&metadata.name) == APPIMAGE_ICON_NAME { 2 } else { 4 };
+
+    PlannedContent {
+        compressible,
+        incompressible: icon_size,
+        copies: desktop_size + icon_size * icon_copies,
+    }
+}
+
+// Tamanhos medidos ao fim da geração
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizeReport {
+    pub appdir_size: u64,
+    // Ausente quando apenas o AppDir foi gerado
+    pub output_size: Option<u64>,
+    // Maiores arquivos do AppDir, do maior para o menor
+    pub largest: Vec<AppDirEntry>,
+}
+
+impl SizeReport {
+    // Tamanho do AppImage em relação ao AppDir
+    pub fn compression_ratio(&self) -> Option<f64> {
+        let output_size = self.output_size?;
+        if self.appdir_size == 0 {
+            return None;
+        }
+        Some(output_size as f64 / self.appdir_size as f64)
+    }
+
+    pub fn summary(&self) -> String {
+        match (self.output_size, self.compression_ratio()) {
+            (Some(output_size), Some(ratio)) => gettext_f(
+                "{size} AppDir compressed to {output} ({percent}% of the original)",
+                &[
+                    ("size", &format_size(self.appdir_size)),
+                    ("output", &format_size(output_size)),
+                    ("percent", &format!("{:.0}", ratio * 100.0)),
+                ],
+            ),
+            _ => gettext_f("{size} AppDir", &[("size", &format_size(self.appdir_size))]),
+        }
+    }
+
+    // Uma linha por arquivo: tamanho, fatia do AppDir e caminho
+    pub fn largest_lines(&self) -> Vec<String> {
+        self.largest
+            .iter()
+            .map(|entry| {
+                let share = if self.appdir_size == 0 {
+                    0.0
+                } else {
+                    entry.size as f64 / self.appdir_size as f64 * 100.0
+                };
+                format!("{:>9}  {:>5.1}%  {}", format_size(entry.size), share, entry.path.display())
+            })
+            .collect()
+    }
+}
+
+pub(super) fn size_report(appdir: &Path, output: Option<&Path>) -> BuildResult<SizeReport> {
+    let mut entries = Vec::new();
+    collect_files(appdir, appdir, &mut entries)?;
+
+    let appdir_size = entries.iter().map(|entry| entry.size).sum();
+    entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    entries.truncate(REPORT_LARGEST);
+
+    let output_size = match output {
+        Some(path) => Some(fs::metadata(path).at_path(path)?.len()),
+        None => None,
+    };
+
+    Ok(SizeReport {
+        appdir_size,
+        output_size,
+        largest: entries,
+    })
+}
+
+// Links simbólicos não ocupam espaço próprio e não são seguidos
+fn collect_files(root: &Path, dir: &Path, entries: &mut Vec<AppDirEntry>) -> BuildResult<()> {
+    for path in sorted_entries(dir)? {
+        let meta = fs::symlink_metadata(&path).at_path(&path)?;
+        if meta.is_dir() {
+            collect_files(root, &path, entries)?;
+        } else if meta.is_file() {
+            entries.push(AppDirEntry {
+                path: path.strip_prefix(root).unwrap_or(&path).to_path_buf(),
+                size: meta.len(),
+            });
+        }
+    }
+    Ok(())
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+    const GB: f64 = MB * 1024.0;
+
+    let size = bytes as f64;
+    if size >= GB {
+        format!("{:.2} GB", size / GB)
+    } else if size >= MB {
+        format!("{:.1} MB", size / MB)
+    } else if size >= KB {
+        format!("{:.0} KB", size / KB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
@@ -0,0 +1,954 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use super::compression::{compress_block, Compression, CompressionAlgorithm};
+use super::error::{BuildError, BuildResult, IoResultExt};
+use super::reproducible::sorted_entries;
+use super::{compute_md5, CancelFlag};
+use crate::i18n::gettext;
+
+// Escritor de imagens SquashFS 4.0, usado pelo backend nativo. Formato mínimo
+// aceito pelo kernel e pelo squashfuse do runtime: sem fragmentos (o fim de cada
+// arquivo ocupa um bloco menor), sem xattrs, sem tabela de exportação e com todos
+// os arquivos pertencendo ao root. Arquivos idênticos são gravados uma única vez
+
+const MAGIC: u32 = 0x7371_7368;
+const SUPERBLOCK_SIZE: usize = 96;
+const METADATA_BLOCK_SIZE: usize = 8192;
+const METADATA_UNCOMPRESSED: u16 = 0x8000;
+const DATA_UNCOMPRESSED: u32 = 1 << 24;
+const NO_FRAGMENT: u32 = u32::MAX;
+const NO_XATTR: u32 = u32::MAX;
+const NO_TABLE: u64 = u64::MAX;
+// A imagem termina alinhada, como a do mksquashfs
+const DEVICE_BLOCK_SIZE: u64 = 4096;
+
+const FLAG_NO_FRAGMENTS: u16 = 0x0010;
+const FLAG_DUPLICATES: u16 = 0x0040;
+const FLAG_NO_XATTRS: u16 = 0x0200;
+const FLAG_COMPRESSOR_OPTIONS: u16 = 0x0400;
+
+const BASIC_DIR: u16 = 1;
+const BASIC_FILE: u16 = 2;
+const BASIC_SYMLINK: u16 = 3;
+const EXTENDED_DIR: u16 = 8;
+const EXTENDED_FILE: u16 = 9;
+
+// Cabeçalhos de diretório agrupam no máximo 256 entradas
+const MAX_DIR_HEADER_ENTRIES: usize = 256;
+
+struct Metadata {
+    mode: u16,
+    mtime: u32,
+}
+
+struct Dir {
+    metadata: Metadata,
+    children: Vec<(Vec<u8>, Node)>,
+    // Inodes da subárvore, incluindo o do próprio diretório
+    inode_count: u32,
+}
+
+struct RegularFile {
+    path: PathBuf,
+    metadata: Metadata,
+    size: u64,
+    blocks_start: u64,
+    block_sizes: Vec<u32>,
+}
+
+enum Node {
+    Dir(Dir),
+    File(RegularFile),
+    Symlink { metadata: Metadata, target: Vec<u8> },
+}
+
+impl Node {
+    fn inode_count(&self) -> u32 {
+        match self {
+            Node::Dir(dir) => dir.inode_count,
+            _ => 1,
+        }
+    }
+}
+
+// Grava a imagem de `source` a partir da posição atual de `output` (após o
+// runtime, no caso do AppImage) e retorna quantos bytes ela ocupa
+pub(super) fn write_image<W: Write + Seek>(
+    source: &Path,
+    output: &mut W,
+    output_path: &Path,
+    compression: &Compression,
+    mkfs_time: u32,
+    on_progress: &mut dyn FnMut(u64),
+    cancel: &CancelFlag,
+) -> BuildResult<u64> {
+    let mut root = scan_dir(source)?;
+
+    let base = output.stream_position().at_path(output_path)?;
+    let mut writer = ImageWriter {
+        output,
+        output_path,
+        position: 0,
+        compression: *compression,
+        processed: 0,
+        duplicates: HashMap::new(),
+        has_duplicates: false,
+    };
+
+    writer.write(&[0; SUPERBLOCK_SIZE])?;
+    let compressor_options = compressor_options(compression.algorithm);
+    if let Some(options) = &compressor_options {
+        let mut block = (options.len() as u16 | METADATA_UNCOMPRESSED).to_le_bytes().to_vec();
+        block.extend_from_slice(options);
+        writer.write(&block)?;
+    }
+
+    writer.write_data(&mut root, on_progress, cancel)?;
+
+    let mut tables = Tables {
+        inodes: MetadataWriter::new(*compression),
+        directories: MetadataWriter::new(*compression),
+        next_inode: 1,
+    };
+    let total_inodes = root.inode_count;
+    let (root_ref, _) = tables.write_dir(&root, total_inodes + 1)?;
+
+    let inode_table = tables.inodes.finish()?;
+    let directory_table = tables.directories.finish()?;
+
+    let inode_table_start = writer.position;
+    writer.write(&inode_table)?;
+    let directory_table_start = writer.position;
+    writer.write(&directory_table)?;
+
+    // Um único id (0, root) para dono e grupo de todos os arquivos
+    let mut ids = MetadataWriter::new(*compression);
+    ids.write(&0u32.to_le_bytes());
+    let id_block_start = writer.position;
+    writer.write(&ids.finish()?)?;
+    let id_table_start = writer.position;
+    writer.write(&id_block_start.to_le_bytes())?;
+
+    let bytes_used = writer.position;
+    let padding = (DEVICE_BLOCK_SIZE - bytes_used % DEVICE_BLOCK_SIZE) % DEVICE_BLOCK_SIZE;
+    writer.write(&vec![0; padding as usize])?;
+
+    let mut flags = FLAG_NO_FRAGMENTS | FLAG_NO_XATTRS;
+    if writer.has_duplicates {
+        flags |= FLAG_DUPLICATES;
+    }
+    if compressor_options.is_some() {
+        flags |= FLAG_COMPRESSOR_OPTIONS;
+    }
+
+    let block_size = compression.effective_block_size();
+    let mut superblock = Vec::with_capacity(SUPERBLOCK_SIZE);
+    superblock.extend_from_slice(&MAGIC.to_le_bytes());
+    superblock.extend_from_slice(&total_inodes.to_le_bytes());
+    superblock.extend_from_slice(&mkfs_time.to_le_bytes());
+    superblock.extend_from_slice(&block_size.to_le_bytes());
+    superblock.extend_from_slice(&0u32.to_le_bytes());
+    superblock.extend_from_slice(&compressor_id(compression.algorithm).to_le_bytes());
+    superblock.extend_from_slice(&(block_size.trailing_zeros() as u16).to_le_bytes());
+    superblock.extend_from_slice(&flags.to_le_bytes());
+    superblock.extend_from_slice(&1u16.to_le_bytes());
+    superblock.extend_from_slice(&4u16.to_le_bytes());
+    superblock.extend_from_slice(&0u16.to_le_bytes());
+    superblock.extend_from_slice(&root_ref.to_le_bytes());
+    superblock.extend_from_slice(&bytes_used.to_le_bytes());
+    superblock.extend_from_slice(&id_table_start.to_le_bytes());
+    superblock.extend_from_slice(&NO_TABLE.to_le_bytes());
+    superblock.extend_from_slice(&inode_table_start.to_le_bytes());
+    superblock.extend_from_slice(&directory_table_start.to_le_bytes());
+    // Sem fragmentos a tabela é vazia; aponta para o fim das tabelas de diretório
+    superblock.extend_from_slice(&id_block_start.to_le_bytes());
+    superblock.extend_from_slice(&NO_TABLE.to_le_bytes());
+
+    let end = base + writer.position;
+    let output = writer.output;
+    output.seek(SeekFrom::Start(base)).at_path(output_path)?;
+    output.write_all(&superblock).at_path(output_path)?;
+    output.seek(SeekFrom::Start(end)).at_path(output_path)?;
+
+    Ok(end - base)
+}
+
+fn compressor_id(algorithm: CompressionAlgorithm) -> u16 {
+    match algorithm {
+        CompressionAlgorithm::Gzip => 1,
+        CompressionAlgorithm::Xz => 4,
+        CompressionAlgorithm::Lz4 => 5,
+        CompressionAlgorithm::Zstd => 6,
+    }
+}
+
+// O lz4 exige as opções do compressor (versão 1, o formato "legacy" do kernel)
+fn compressor_options(algorithm: CompressionAlgorithm) -> Option<Vec<u8>> {
+    match algorithm {
+        CompressionAlgorithm::Lz4 => {
+            let mut options = 1u32.to_le_bytes().to_vec();
+            options.extend_from_slice(&0u32.to_le_bytes());
+            Some(options)
+        }
+        _ => None,
+    }
+}
+
+fn scan_dir(path: &Path) -> BuildResult<Dir> {
+    let meta = fs::symlink_metadata(path).at_path(path)?;
+    let mut children = Vec::new();
+    for child in sorted_entries(path)? {
+        let name = name_bytes(child.file_name().unwrap_or_default());
+        if name.len() > 256 {
+            return Err(BuildError::io(
+                &child,
+                io::Error::new(io::ErrorKind::InvalidInput, gettext("name longer than 256 bytes")),
+            ));
+        }
+
+        let child_meta = fs::symlink_metadata(&child).at_path(&child)?;
+        let node = if child_meta.file_type().is_symlink() {
+            let target = fs::read_link(&child).at_path(&child)?;
+            Node::Symlink {
+                metadata: node_metadata(&child_meta),
+                target: name_bytes(target.as_os_str()),
+            }
+        } else if child_meta.is_dir() {
+            Node::Dir(scan_dir(&child)?)
+        } else if child_meta.is_file() {
+            Node::File(RegularFile {
+                path: child.clone(),
+                metadata: node_metadata(&child_meta),
+                size: child_meta.len(),
+                blocks_start: 0,
+                block_sizes: Vec::new(),
+            })
+        } else {
+            return Err(BuildError::io(
+                &child,
+                io::Error::new(io::ErrorKind::InvalidInput, gettext("unsupported file type in the AppDir")),
+            ));
+        };
+        children.push((name, node));
+    }
+
+    // Entradas em ordem de bytes, como exige o formato
+    children.sort_by(|a, b| a.0.cmp(&b.0));
+    let inode_count = 1 + children.iter().map(|(_, node)| node.inode_count()).sum::<u32>();
+    Ok(Dir {
+        metadata: node_metadata(&meta),
+        children,
+        inode_count,
+    })
+}
+
+fn node_metadata(meta: &fs::Metadata) -> Metadata {
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs().min(u32::MAX as u64) as u32)
+        .unwrap_or(0);
+    Metadata {
+        mode: file_mode(meta),
+        mtime,
+    }
+}
+
+#[cfg(unix)]
+fn file_mode(meta: &fs::Metadata) -> u16 {
+    use std::os::unix::fs::PermissionsExt;
+    (meta.permissions().mode() & 0o7777) as u16
+}
+
+#[cfg(not(unix))]
+fn file_mode(meta: &fs::Metadata) -> u16 {
+    if meta.is_dir() {
+        0o755
+    } else {
+        0o644
+    }
+}
+
+#[cfg(unix)]
+fn name_bytes(name: &OsStr) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    name.as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn name_bytes(name: &OsStr) -> Vec<u8> {
+    name.to_string_lossy().as_bytes().to_vec()
+}
+
+struct ImageWriter<'a, W: Write + Seek> {
+    output: &'a mut W,
+    output_path: &'a Path,
+    // Posição relativa ao início da imagem
+    position: u64,
+    compression: Compression,
+    processed: u64,
+    // Blocos já gravados por conteúdo (tamanho e MD5)
+    duplicates: HashMap<(u64, String), (u64, Vec<u32>)>,
+    has_duplicates: bool,
+}
+
+impl<W: Write + Seek> ImageWriter<'_, W> {
+    fn write(&mut self, data: &[u8]) -> BuildResult<()> {
+        self.output.write_all(data).at_path(self.output_path)?;
+        self.position += data.len() as u64;
+        Ok(())
+    }
+
+    fn write_data(&mut self, dir: &mut Dir, on_progress: &mut dyn FnMut(u64), cancel: &CancelFlag) -> BuildResult<()> {
+        for (_, node) in &mut dir.children {
+            match node {
+                Node::Dir(child) => self.write_data(child, on_progress, cancel)?,
+                Node::File(file) => self.write_file(file, on_progress, cancel)?,
+                Node::Symlink { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn write_file(
+        &mut self,
+        file: &mut RegularFile,
+        on_progress: &mut dyn FnMut(u64),
+        cancel: &CancelFlag,
+    ) -> BuildResult<()> {
+        cancel.check()?;
+        let key = (file.size, compute_md5(&file.path)?);
+        if let Some((start, block_sizes)) = self.duplicates.get(&key) {
+            file.blocks_start = *start;
+            file.block_sizes = block_sizes.clone();
+            self.has_duplicates = true;
+            self.processed += file.size;
+            on_progress(self.processed);
+            return Ok(());
+        }
+
+        file.blocks_start = self.position;
+        let mut source = File::open(&file.path).at_path(&file.path)?;
+        let mut block = vec![0u8; self.compression.effective_block_size() as usize];
+        loop {
+            let filled = read_block(&mut source, &mut block).at_path(&file.path)?;
+            if filled == 0 {
+                break;
+            }
+            cancel.check()?;
+
+            let data = &block[..filled];
+            let compressed = compress_block(data, &self.compression).at_path(&file.path)?;
+            // Blocos que não diminuem são gravados sem compressão
+            if compressed.len() < data.len() {
+                self.write(&compressed)?;
+                file.block_sizes.push(compressed.len() as u32);
+            } else {
+                self.write(data)?;
+                file.block_sizes.push(data.len() as u32 | DATA_UNCOMPRESSED);
+            }
+
+            self.processed += filled as u64;
+            on_progress(self.processed);
+        }
+
+        self.duplicates.insert(key, (file.blocks_start, file.block_sizes.clone()));
+        Ok(())
+    }
+}
+
+// Lê até encher o bloco ou chegar ao fim do arquivo
+fn read_block(source: &mut File, block: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < block.len() {
+        match source.read(&mut block[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    Ok(filled)
+}
+
+// Tabelas de metadados: blocos de até 8 KiB, comprimidos um a um e precedidos
+// do tamanho gravado
+struct MetadataWriter {
+    compression: Compression,
+    output: Vec<u8>,
+    buffer: Vec<u8>,
+}
+
+impl MetadataWriter {
+    fn new(compression: Compression) -> Self {
+        MetadataWriter {
+            compression,
+            output: Vec::new(),
+            buffer: Vec::new(),
+        }
+    }
+
+    // Referência ao próximo byte: início do bloco na tabela e posição dentro dele
+    fn reference(&self) -> u64 {
+        ((self.output.len() as u64) << 16) | self.buffer.len() as u64
+    }
+
+    fn write(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    // Blocos completos são fechados na hora, para que as referências seguintes
+    // apontem para o bloco certo
+    fn flush_full_blocks(&mut self) -> io::Result<()> {
+        while self.buffer.len() >= METADATA_BLOCK_SIZE {
+            let block: Vec<u8> = self.buffer.drain(..METADATA_BLOCK_SIZE).collect();
+            self.write_block(&block)?;
+        }
+        Ok(())
+    }
+
+    fn write_block(&mut self, block: &[u8]) -> io::Result<()> {
+        let compressed = compress_block(block, &self.compression)?;
+        if compressed.len() < block.len() {
+            self.output.extend_from_slice(&(compressed.len() as u16).to_le_bytes());
+            self.output.extend_from_slice(&compressed);
+        } else {
+            self.output
+                .extend_from_slice(&(block.len() as u16 | METADATA_UNCOMPRESSED).to_le_bytes());
+            self.output.extend_from_slice(block);
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> BuildResult<Vec<u8>> {
+        self.flush_full_blocks().map_err(metadata_error)?;
+        if !self.buffer.is_empty() {
+            let block = std::mem::take(&mut self.buffer);
+            self.write_block(&block).map_err(metadata_error)?;
+        }
+        Ok(self.output)
+    }
+}
+
+fn metadata_error(err: io::Error) -> BuildError {
+    BuildError::io(Path::new("squashfs"), err)
+}
+
+struct DirEntry<'a> {
+    name: &'a [u8],
+    inode_type: u16,
+    reference: u64,
+    number: u32,
+}
+
+struct Tables {
+    inodes: MetadataWriter,
+    directories: MetadataWriter,
+    next_inode: u32,
+}
+
+impl Tables {
+    // Os inodes são gravados depois dos filhos (que o diretório referencia); os
+    // números seguem a mesma ordem, então o do diretório é o último da subárvore
+    fn write_dir(&mut self, dir: &Dir, parent: u32) -> BuildResult<(u64, u32)> {
+        let number = self.next_inode + dir.inode_count - 1;
+        let mut entries = Vec::with_capacity(dir.children.len());
+        let mut subdirs = 0u32;
+
+        for (name, node) in &dir.children {
+            let (inode_type, reference, child_number) = match node {
+                Node::Dir(child) => {
+                    subdirs += 1;
+                    let (reference, child_number) = self.write_dir(child, number)?;
+                    (BASIC_DIR, reference, child_number)
+                }
+                Node::File(file) => {
+                    let (reference, child_number) = self.write_file_inode(file)?;
+                    (BASIC_FILE, reference, child_number)
+                }
+                Node::Symlink { metadata, target } => {
+                    let (reference, child_number) = self.write_symlink_inode(metadata, target)?;
+                    (BASIC_SYMLINK, reference, child_number)
+                }
+            };
+            entries.push(DirEntry {
+                name,
+                inode_type,
+                reference,
+                number: child_number,
+            });
+        }
+
+        // Os filhos usaram os números anteriores; o seguinte é o deste diretório
+        debug_assert_eq!(self.next_inode, number);
+        self.next_inode += 1;
+
+        let listing_reference = self.directories.reference();
+        let listing_size = self.write_listing(&entries)?;
+        // O tamanho inclui as entradas "." e ".." implícitas
+        let file_size = listing_size + 3;
+        let block_index = (listing_reference >> 16) as u32;
+        let block_offset = (listing_reference & 0xFFFF) as u16;
+        let link_count = 2 + subdirs;
+
+        let reference = self.inodes.reference();
+        let mut inode = Vec::new();
+        if let Ok(file_size) = u16::try_from(file_size) {
+            inode_header(&mut inode, BASIC_DIR, &dir.metadata, number);
+            inode.extend_from_slice(&block_index.to_le_bytes());
+            inode.extend_from_slice(&link_count.to_le_bytes());
+            inode.extend_from_slice(&file_size.to_le_bytes());
+            inode.extend_from_slice(&block_offset.to_le_bytes());
+            inode.extend_from_slice(&parent.to_le_bytes());
+        } else {
+            inode_header(&mut inode, EXTENDED_DIR, &dir.metadata, number);
+            inode.extend_from_slice(&link_count.to_le_bytes());
+            inode.extend_from_slice(&(file_size as u32).to_le_bytes());
+            inode.extend_from_slice(&block_index.to_le_bytes());
+            inode.extend_from_slice(&parent.to_le_bytes());
+            inode.extend_from_slice(&0u16.to_le_bytes());
+            inode.extend_from_slice(&block_offset.to_le_bytes());
+            inode.extend_from_slice(&NO_XATTR.to_le_bytes());
+        }
+        self.push_inode(&inode)?;
+        Ok((reference, number))
+    }
+
+    fn write_listing(&mut self, entries: &[DirEntry]) -> BuildResult<u64> {
+        let mut size = 0u64;
+        let mut rest = entries;
+        while let Some(first) = rest.first() {
+            // Um cabeçalho cobre entradas com inodes no mesmo bloco de metadados
+            // e números próximos o bastante para um deslocamento de 16 bits
+            let block = first.reference >> 16;
+            let count = rest
+                .iter()
+                .take(MAX_DIR_HEADER_ENTRIES)
+                .take_while(|entry| {
+                    entry.reference >> 16 == block && (entry.number as i64 - first.number as i64).abs() <= i16::MAX as i64
+                })
+                .count();
+
+            let mut listing = Vec::new();
+            listing.extend_from_slice(&(count as u32 - 1).to_le_bytes());
+            listing.extend_from_slice(&(block as u32).to_le_bytes());
+            listing.extend_from_slice(&first.number.to_le_bytes());
+            for entry in &rest[..count] {
+                let offset = (entry.reference & 0xFFFF) as u16;
+                let inode_offset = (entry.number as i64 - first.number as i64) as i16;
+                listing.extend_from_slice(&offset.to_le_bytes());
+                listing.extend_from_slice(&inode_offset.to_le_bytes());
+                listing.extend_from_slice(&entry.inode_type.to_le_bytes());
+                listing.extend_from_slice(&(entry.name.len() as u16 - 1).to_le_bytes());
+                listing.extend_from_slice(entry.name);
+            }
+
+            size += listing.len() as u64;
+            self.directories.write(&listing);
+            self.directories.flush_full_blocks().map_err(metadata_error)?;
+            rest = &rest[count..];
+        }
+        Ok(size)
+    }
+
+    fn write_file_inode(&mut self, file: &RegularFile) -> BuildResult<(u64, u32)> {
+        let number = self.take_number();
+        let reference = self.inodes.reference();
+        let mut inode = Vec::new();
+        match (u32::try_from(file.blocks_start), u32::try_from(file.size)) {
+            (Ok(blocks_start), Ok(size)) => {
+                inode_header(&mut inode, BASIC_FILE, &file.metadata, number);
+                inode.extend_from_slice(&blocks_start.to_le_bytes());
+                inode.extend_from_slice(&NO_FRAGMENT.to_le_bytes());
+                inode.extend_from_slice(&0u32.to_le_bytes());
+                inode.extend_from_slice(&size.to_le_bytes());
+            }
+            _ => {
+                inode_header(&mut inode, EXTENDED_FILE, &file.metadata, number);
+                inode.extend_from_slice(&file.blocks_start.to_le_bytes());
+                inode.extend_from_slice(&file.size.to_le_bytes());
+                inode.extend_from_slice(&0u64.to_le_bytes());
+                inode.extend_from_slice(&1u32.to_le_bytes());
+                inode.extend_from_slice(&NO_FRAGMENT.to_le_bytes());
+                inode.extend_from_slice(&0u32.to_le_bytes());
+                inode.extend_from_slice(&NO_XATTR.to_le_bytes());
+            }
+        }
+        for size in &file.block_sizes {
+            inode.extend_from_slice(&size.to_le_bytes());
+        }
+        self.push_inode(&inode)?;
+        Ok((reference, number))
+    }
+
+    fn write_symlink_inode(&mut self, metadata: &Metadata, target: &[u8]) -> BuildResult<(u64, u32)> {
+        let number = self.take_number();
+        let reference = self.inodes.reference();
+        let mut inode = Vec::new();
+        inode_header(&mut inode, BASIC_SYMLINK, metadata, number);
+        inode.extend_from_slice(&1u32.to_le_bytes());
+        inode.extend_from_slice(&(target.len() as u32).to_le_bytes());
+        inode.extend_from_slice(target);
+        self.push_inode(&inode)?;
+        Ok((reference, number))
+    }
+
+    fn take_number(&mut self) -> u32 {
+        let number = self.next_inode;
+        self.next_inode += 1;
+        number
+    }
+
+    fn push_inode(&mut self, inode: &[u8]) -> BuildResult<()> {
+        self.inodes.write(inode);
+        self.inodes.flush_full_blocks().map_err(metadata_error)
+    }
+}
+
+// Cabeçalho comum: tipo, permissões, índices de dono e grupo, data e número
+fn inode_header(inode: &mut Vec<u8>, inode_type: u16, metadata: &Metadata, number: u32) {
+    inode.extend_from_slice(&inode_type.to_le_bytes());
+    inode.extend_from_slice(&metadata.mode.to_le_bytes());
+    inode.extend_from_slice(&0u16.to_le_bytes());
+    inode.extend_from_slice(&0u16.to_le_bytes());
+    inode.extend_from_slice(&metadata.mtime.to_le_bytes());
+    inode.extend_from_slice(&number.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::process::Command;
+
+    use crate::appimage::preflight::find_in_path;
+    use crate::test_util::TestDir;
+
+    // Bytes antes da imagem, como o runtime de um AppImage
+    const PREFIX: &[u8] = b"runtime";
+    const MKFS_TIME: u32 = 1_700_000_000;
+    const BLOCK_SIZE: u32 = 4096;
+
+    // Arquivo de vários blocos (o primeiro incompressível), arquivos idênticos,
+    // pasta vazia e link simbólico
+    fn sample_appdir(name: &str) -> TestDir {
+        let dir = TestDir::new(&format!("squashfs-{}", name));
+        dir.write("AppRun", b"#!/bin/sh\nexec \"$(dirname \"$0\")/usr/bin/demo\" \"$@\"\n");
+        let mut seed = 0x1234_5678u32;
+        let mut binary: Vec<u8> = (0..BLOCK_SIZE)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (seed >> 16) as u8
+            })
+            .collect();
+        binary.extend((0..6000u32).map(|i| (i % 251) as u8));
+        dir.write("usr/bin/demo", &binary);
+        dir.write("usr/share/doc/demo/README", b"demo\n");
+        dir.write("usr/share/doc/demo/COPYING", b"demo\n");
+        fs::create_dir_all(dir.join("usr/lib")).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("usr/bin/demo", dir.join("demo")).unwrap();
+        dir
+    }
+
+    fn build_image(source: &Path) -> (Vec<u8>, u64) {
+        let mut output = Cursor::new(PREFIX.to_vec());
+        output.set_position(PREFIX.len() as u64);
+        let compression = Compression {
+            block_size: Some(BLOCK_SIZE),
+            ..Compression::new(CompressionAlgorithm::Gzip)
+        };
+        let size = write_image(
+            source,
+            &mut output,
+            Path::new("test.squashfs"),
+            &compression,
+            MKFS_TIME,
+            &mut |_| {},
+            &CancelFlag::default(),
+        )
+        .unwrap();
+        (output.into_inner(), size)
+    }
+
+    fn u16_at(data: &[u8], pos: usize) -> u16 {
+        u16::from_le_bytes(data[pos..pos + 2].try_into().unwrap())
+    }
+
+    fn u32_at(data: &[u8], pos: usize) -> u32 {
+        u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap())
+    }
+
+    fn u64_at(data: &[u8], pos: usize) -> u64 {
+        u64::from_le_bytes(data[pos..pos + 8].try_into().unwrap())
+    }
+
+    fn inflate(block: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        flate2::read::ZlibDecoder::new(block).read_to_end(&mut data).unwrap();
+        data
+    }
+
+    // Tabela de metadados descomprimida e a posição de cada bloco nela
+    struct MetadataTable {
+        data: Vec<u8>,
+        blocks: HashMap<u64, usize>,
+    }
+
+    impl MetadataTable {
+        fn read(image: &[u8], start: u64, end: u64) -> Self {
+            let mut table = MetadataTable {
+                data: Vec::new(),
+                blocks: HashMap::new(),
+            };
+            let mut offset = 0;
+            while start + offset < end {
+                let pos = (start + offset) as usize;
+                let header = u16_at(image, pos);
+                let size = (header & !METADATA_UNCOMPRESSED) as usize;
+                let block = &image[pos + 2..pos + 2 + size];
+                table.blocks.insert(offset, table.data.len());
+                if header & METADATA_UNCOMPRESSED != 0 {
+                    table.data.extend_from_slice(block);
+                } else {
+                    let data = inflate(block);
+                    assert!(data.len() <= METADATA_BLOCK_SIZE);
+                    table.data.extend(data);
+                }
+                offset += 2 + size as u64;
+            }
+            assert_eq!(start + offset, end, "a tabela termina no meio de um bloco");
+            table
+        }
+
+        fn at(&self, block: u64, offset: u64) -> usize {
+            self.blocks[&block] + offset as usize
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Entry {
+        Dir,
+        File(Vec<u8>),
+        Symlink(Vec<u8>),
+    }
+
+    // Leitor mínimo, só para conferir o que o escritor grava
+    struct ImageReader<'a> {
+        image: &'a [u8],
+        inodes: MetadataTable,
+        directories: MetadataTable,
+    }
+
+    impl ImageReader<'_> {
+        fn new(image: &[u8]) -> ImageReader<'_> {
+            let inode_table = u64_at(image, 64);
+            let directory_table = u64_at(image, 72);
+            let directories_end = u64_at(image, 80);
+            ImageReader {
+                image,
+                inodes: MetadataTable::read(image, inode_table, directory_table),
+                directories: MetadataTable::read(image, directory_table, directories_end),
+            }
+        }
+
+        fn root(&self) -> Vec<(String, u16, Entry)> {
+            let mut entries = Vec::new();
+            let (inode_type, number) = self.walk(u64_at(self.image, 32), "", u32_at(self.image, 4) + 1, &mut entries);
+            assert_eq!(inode_type, BASIC_DIR);
+            assert_eq!(number, u32_at(self.image, 4), "a raiz é o último inode");
+            entries
+        }
+
+        // Lê o inode de `reference` e, se for um diretório, a listagem dele
+        fn walk(&self, reference: u64, path: &str, parent: u32, entries: &mut Vec<(String, u16, Entry)>) -> (u16, u32) {
+            let data = &self.inodes.data;
+            let pos = self.inodes.at(reference >> 16, reference & 0xFFFF);
+            let inode_type = u16_at(data, pos);
+            let mode = u16_at(data, pos + 2);
+            assert_eq!((u16_at(data, pos + 4), u16_at(data, pos + 6)), (0, 0));
+            let number = u32_at(data, pos + 12);
+
+            let entry = match inode_type {
+                BASIC_DIR => {
+                    let block_index = u32_at(data, pos + 16) as u64;
+                    let file_size = u16_at(data, pos + 24) as usize;
+                    let block_offset = u16_at(data, pos + 26) as u64;
+                    assert_eq!(u32_at(data, pos + 28), parent);
+
+                    let listing = &self.directories.data;
+                    let mut cursor = self.directories.at(block_index, block_offset);
+                    let end = cursor + file_size - 3;
+                    let mut children = Vec::new();
+                    while cursor < end {
+                        let count = u32_at(listing, cursor) + 1;
+                        let start = u32_at(listing, cursor + 4) as u64;
+                        let base_number = u32_at(listing, cursor + 8);
+                        cursor += 12;
+                        for _ in 0..count {
+                            let offset = u16_at(listing, cursor) as u64;
+                            let inode_offset = u16_at(listing, cursor + 2) as i16;
+                            let entry_type = u16_at(listing, cursor + 4);
+                            let name_len = u16_at(listing, cursor + 6) as usize + 1;
+                            let name = String::from_utf8(listing[cursor + 8..cursor + 8 + name_len].to_vec()).unwrap();
+                            cursor += 8 + name_len;
+                            children.push((start << 16 | offset, name, entry_type, base_number as i64 + inode_offset as i64));
+                        }
+                    }
+                    assert_eq!(cursor, end);
+                    assert!(children.windows(2).all(|pair| pair[0].1 < pair[1].1), "entradas fora de ordem");
+
+                    if !path.is_empty() {
+                        entries.push((path.to_string(), mode, Entry::Dir));
+                    }
+                    for (child_reference, name, entry_type, child_number) in children {
+                        let child_path = if path.is_empty() { name } else { format!("{}/{}", path, name) };
+                        let (child_type, actual_number) = self.walk(child_reference, &child_path, number, entries);
+                        assert_eq!(child_type, entry_type, "{}", child_path);
+                        assert_eq!(actual_number as i64, child_number, "{}", child_path);
+                    }
+                    return (inode_type, number);
+                }
+                BASIC_FILE => {
+                    let mut position = u32_at(data, pos + 16) as usize;
+                    assert_eq!(u32_at(data, pos + 20), NO_FRAGMENT);
+                    let size = u32_at(data, pos + 28) as usize;
+                    let mut content = Vec::new();
+                    for index in 0..size.div_ceil(BLOCK_SIZE as usize) {
+                        let block_size = u32_at(data, pos + 32 + 4 * index);
+                        let len = (block_size & !DATA_UNCOMPRESSED) as usize;
+                        let block = &self.image[position..position + len];
+                        if block_size & DATA_UNCOMPRESSED != 0 {
+                            content.extend_from_slice(block);
+                        } else {
+                            content.extend(inflate(block));
+                        }
+                        position += len;
+                    }
+                    assert_eq!(content.len(), size);
+                    Entry::File(content)
+                }
+                BASIC_SYMLINK => {
+                    let target_size = u32_at(data, pos + 20) as usize;
+                    Entry::Symlink(data[pos + 24..pos + 24 + target_size].to_vec())
+                }
+                other => panic!("tipo de inode inesperado: {}", other),
+            };
+            entries.push((path.to_string(), mode, entry));
+            (inode_type, number)
+        }
+    }
+
+    // O que o AppDir contém, na mesma ordem de bytes da imagem
+    fn expected_entries(root: &Path, path: &str, entries: &mut Vec<(String, u16, Entry)>) {
+        for child in sorted_entries(&root.join(path)).unwrap() {
+            let name = child.file_name().unwrap().to_string_lossy();
+            let child_path = if path.is_empty() { name.to_string() } else { format!("{}/{}", path, name) };
+            let meta = fs::symlink_metadata(&child).unwrap();
+            let entry = if meta.file_type().is_symlink() {
+                Entry::Symlink(name_bytes(fs::read_link(&child).unwrap().as_os_str()))
+            } else if meta.is_dir() {
+                Entry::Dir
+            } else {
+                Entry::File(fs::read(&child).unwrap())
+            };
+            entries.push((child_path.clone(), file_mode(&meta), entry));
+            if meta.is_dir() {
+                expected_entries(root, &child_path, entries);
+            }
+        }
+    }
+
+    #[test]
+    fn superblock_fields_and_offsets() {
+        let dir = sample_appdir("superblock");
+        let (data, size) = build_image(dir.path());
+
+        // A imagem começa onde o runtime termina
+        assert_eq!(&data[..PREFIX.len()], PREFIX);
+        let image = &data[PREFIX.len()..];
+        assert_eq!(image.len() as u64, size);
+        assert_eq!(size % DEVICE_BLOCK_SIZE, 0);
+
+        assert_eq!(u32_at(image, 0), MAGIC);
+        #[cfg(unix)]
+        assert_eq!(u32_at(image, 4), 12, "número de inodes");
+        assert_eq!(u32_at(image, 8), MKFS_TIME);
+        assert_eq!(u32_at(image, 12), BLOCK_SIZE);
+        assert_eq!(u32_at(image, 16), 0, "fragmentos");
+        assert_eq!(u16_at(image, 20), compressor_id(CompressionAlgorithm::Gzip));
+        assert_eq!(u16_at(image, 22), 12, "log2 do tamanho do bloco");
+        assert_eq!(u16_at(image, 24), FLAG_NO_FRAGMENTS | FLAG_NO_XATTRS | FLAG_DUPLICATES);
+        assert_eq!(u16_at(image, 26), 1, "ids");
+        assert_eq!((u16_at(image, 28), u16_at(image, 30)), (4, 0), "versão");
+        assert_eq!(u64_at(image, 56), NO_TABLE, "xattrs");
+        assert_eq!(u64_at(image, 88), NO_TABLE, "exportação");
+
+        let bytes_used = u64_at(image, 40);
+        let id_table = u64_at(image, 48);
+        let inode_table = u64_at(image, 64);
+        let directory_table = u64_at(image, 72);
+        let id_block = u64_at(image, 80);
+        assert!(SUPERBLOCK_SIZE as u64 <= inode_table);
+        assert!(inode_table < directory_table && directory_table < id_block && id_block < id_table);
+        assert_eq!(id_table + 8, bytes_used);
+        assert!(size - bytes_used < DEVICE_BLOCK_SIZE);
+        assert!(image[bytes_used as usize..].iter().all(|&byte| byte == 0), "preenchimento");
+
+        // A tabela de ids aponta para um único bloco com o id 0
+        assert_eq!(u64_at(image, id_table as usize), id_block);
+        let ids = MetadataTable::read(image, id_block, id_table);
+        assert_eq!(ids.data, 0u32.to_le_bytes());
+
+        // Raiz: referência dentro da tabela de inodes
+        let root = u64_at(image, 32);
+        assert!((root >> 16) < directory_table - inode_table);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn directory_listing_round_trip() {
+        let dir = sample_appdir("listing");
+        let (data, _) = build_image(dir.path());
+        let entries = ImageReader::new(&data[PREFIX.len()..]).root();
+
+        let mut expected = Vec::new();
+        expected_entries(dir.path(), "", &mut expected);
+        assert_eq!(entries, expected);
+        assert!(entries.iter().any(|(path, _, _)| path == "usr/lib"), "pasta vazia");
+    }
+
+    #[test]
+    #[ignore = "needs unsquashfs"]
+    fn unsquashfs_lists_image() {
+        let unsquashfs = find_in_path("unsquashfs").expect("unsquashfs não encontrado no PATH");
+        let dir = sample_appdir("unsquashfs");
+        let (data, _) = build_image(dir.path());
+        let image_path = dir.path().with_extension("squashfs");
+        fs::write(&image_path, &data[PREFIX.len()..]).unwrap();
+
+        let output = Command::new(unsquashfs)
+            .args(["-l", "-d", "root"])
+            .arg(&image_path)
+            .output()
+            .unwrap();
+        let _ = fs::remove_file(&image_path);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+        let mut listed: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.strip_prefix("root/"))
+            .map(str::to_string)
+            .collect();
+        let mut expected = Vec::new();
+        expected_entries(dir.path(), "", &mut expected);
+        let mut expected: Vec<String> = expected.into_iter().map(|(path, _, _)| path).collect();
+        listed.sort();
+        expected.sort();
+        assert_eq!(listed, expected);
+    }
+}
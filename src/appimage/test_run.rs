@@ -0,0 +1,250 @@
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::error::{BuildError, BuildResult, InputField, IoResultExt};
+use super::preflight::fuse_problem;
+#[cfg(unix)]
+use super::signal_process_group;
+use super::{CancelFlag, PROCESS_POLL_INTERVAL};
+use crate::i18n::gettext_f;
+
+// Tempo até considerar que o aplicativo iniciou e encerrá-lo (aplicativos gráficos
+// não terminam sozinhos)
+pub const DEFAULT_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Após o SIGTERM, espera antes do SIGKILL
+const TERMINATE_GRACE: Duration = Duration::from_secs(2);
+
+// Saída guardada de cada fluxo; o excedente do início é descartado
+const MAX_CAPTURED_OUTPUT: usize = 64 * 1024;
+
+const SHARED_OBJECT_ERROR: &str = ": cannot open shared object file";
+
+// Resultado da execução de teste de um AppImage
+#[derive(Debug, Clone)]
+pub struct TestRun {
+    pub command: String,
+    pub extract_and_run: bool,
+    // None quando encerrado por sinal (inclusive pelo tempo limite)
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub duration: Duration,
+    pub stdout: String,
+    pub stderr: String,
+    // Bibliotecas que o carregador não encontrou (libfoo.so.1)
+    pub missing_libraries: Vec<String>,
+}
+
+impl TestRun {
+    // Iniciou sem erro de bibliotecas e terminou com 0 ou seguia rodando no tempo limite
+    pub fn passed(&self) -> bool {
+        self.missing_libraries.is_empty() && (self.timed_out || self.exit_code == Some(0))
+    }
+
+    pub fn summary(&self) -> String {
+        let seconds = self.duration.as_secs_f64();
+        let status = if self.timed_out {
+            gettext_f(
+                "Still running after {seconds} s; stopped by the test",
+                &[("seconds", &format!("{:.0}", seconds))],
+            )
+        } else {
+            match self.exit_code {
+                Some(code) => gettext_f(
+                    "Exited with code {code} in {seconds} s",
+                    &[("code", &code), ("seconds", &format!("{:.1}", seconds))],
+                ),
+                None => gettext_f(
+                    "Killed by a signal after {seconds} s",
+                    &[("seconds", &format!("{:.1}", seconds))],
+                ),
+            }
+        };
+        if self.extract_and_run {
+            gettext_f("{status} (no FUSE, with --appimage-extract-and-run)", &[("status", &status)])
+        } else {
+            status
+        }
+    }
+
+    // stdout e stderr juntos, para exibição
+    pub fn output(&self) -> String {
+        let mut output = String::new();
+        for (label, text) in [("stdout", &self.stdout), ("stderr", &self.stderr)] {
+            if !text.trim().is_empty() {
+                output.push_str(&format!("--- {} ---\n{}\n", label, text.trim_end()));
+            }
+        }
+        output
+    }
+}
+
+// Executa o AppImage sem argumentos até que termine ou até `timeout`, capturando
+// as saídas. Sem FUSE (ou com `extract_and_run`), usa --appimage-extract-and-run
+pub fn test_appimage(appimage: &Path, timeout: Duration, extract_and_run: bool, cancel: &CancelFlag) -> BuildResult<TestRun> {
+    if !appimage.is_file() {
+        return Err(BuildError::invalid_input(
+            InputField::AppImage,
+            gettext_f("{path} not found", &[("path", &appimage.display())]),
+        ));
+    }
+    let extract_and_run = extract_and_run || fuse_problem().is_some();
+
+    // Caminho absoluto: um nome sem '/' seria procurado no PATH
+    let program = appimage.canonicalize().at_path(appimage)?;
+    let mut command = Command::new(&program);
+    if extract_and_run {
+        command.arg("--appimage-extract-and-run");
+    }
+    command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    // Grupo de processos próprio, para encerrar também o que o AppRun iniciou
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let started = Instant::now();
+    let mut child = command.spawn().at_path(appimage)?;
+    let stdout = capture(child.stdout.take());
+    let stderr = capture(child.stderr.take());
+
+    let mut timed_out = false;
+    let (status, duration) = loop {
+        if let Some(status) = child.try_wait().at_path(appimage)? {
+            break (Some(status), started.elapsed());
+        }
+        if cancel.is_cancelled() || started.elapsed() >= timeout {
+            let duration = started.elapsed();
+            timed_out = !cancel.is_cancelled();
+            #[cfg(unix)]
+            let signaled = signal_process_group(child.id(), libc::SIGTERM);
+            #[cfg(not(unix))]
+            let signaled = Ok(());
+            // Sem o SIGTERM entregue, não há por que esperar antes do SIGKILL
+            let grace = Instant::now();
+            while signaled.is_ok() && grace.elapsed() < TERMINATE_GRACE {
+                if child.try_wait().at_path(appimage)?.is_some() {
+                    break;
+                }
+                thread::sleep(PROCESS_POLL_INTERVAL);
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+            signaled.at_path(appimage)?;
+            break (None, duration);
+        }
+        thread::sleep(PROCESS_POLL_INTERVAL);
+    };
+    cancel.check()?;
+
+    // Filhos que escaparam do grupo podem manter os pipes abertos: após um tempo,
+    // usa o que já chegou
+    let readers_started = Instant::now();
+    while !(stdout.1.is_finished() && stderr.1.is_finished()) && readers_started.elapsed() < TERMINATE_GRACE {
+        thread::sleep(PROCESS_POLL_INTERVAL);
+    }
+    let stdout = take_captured(&stdout.0);
+    let stderr = take_captured(&stderr.0);
+
+    let mut command_line = appimage.display().to_string();
+    if extract_and_run {
+        command_line.push_str(" --appimage-extract-and-run");
+    }
+    Ok(TestRun {
+        command: command_line,
+        extract_and_run,
+        exit_code: status.and_then(|status| status.code()),
+        timed_out,
+        duration,
+        missing_libraries: missing_libraries(&format!("{}\n{}", stderr, stdout)),
+        stdout,
+        stderr,
+    })
+}
+
+// "app: error while loading shared libraries: libfoo.so.1: cannot open shared
+// object file: No such file or directory" -> libfoo.so.1
+fn missing_libraries(output: &str) -> Vec<String> {
+    let mut libraries = Vec::new();
+    for line in output.lines() {
+        let Some(end) = line.find(SHARED_OBJECT_ERROR) else {
+            continue;
+        };
+        let library = line[..end]
+            .rsplit([':', ' ', '\''])
+            .next()
+            .unwrap_or_default()
+            .trim();
+        if library.contains(".so") && !libraries.iter().any(|known| known == library) {
+            libraries.push(library.to_string());
+        }
+    }
+    libraries
+}
+
+// Lê o pipe em uma thread, guardando a saída em um buffer compartilhado
+fn capture<R: Read + Send + 'static>(pipe: Option<R>) -> (Arc<Mutex<Vec<u8>>>, JoinHandle<()>) {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let shared = buffer.clone();
+    let reader = thread::spawn(move || {
+        if let Some(mut pipe) = pipe {
+            let buffer = shared;
+            let mut chunk = [0u8; 8192];
+            while let Ok(read) = pipe.read(&mut chunk) {
+                if read == 0 {
+                    break;
+                }
+                let Ok(mut buffer) = buffer.lock() else {
+                    break;
+                };
+                buffer.extend_from_slice(&chunk[..read]);
+                if buffer.len() > MAX_CAPTURED_OUTPUT {
+                    let excess = buffer.len() - MAX_CAPTURED_OUTPUT;
+                    buffer.drain(..excess);
+                }
+            }
+        }
+    });
+    (buffer, reader)
+}
+
+fn take_captured(buffer: &Mutex<Vec<u8>>) -> String {
+    buffer
+        .lock()
+        .map(|buffer| String::from_utf8_lossy(&buffer).to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_libraries_from_loader_message() {
+        let output = "./usr/bin/demo: error while loading shared libraries: libfoo.so.1: \
+                      cannot open shared object file: No such file or directory\n";
+        assert_eq!(missing_libraries(output), ["libfoo.so.1"]);
+    }
+
+    #[test]
+    fn missing_libraries_are_listed_once() {
+        let output = "demo: error while loading shared libraries: libfoo.so.1: cannot open shared object file: No such file or directory\n\
+                      helper: error while loading shared libraries: libbar.so: cannot open shared object file: No such file or directory\n\
+                      demo: error while loading shared libraries: libfoo.so.1: cannot open shared object file: No such file or directory\n";
+        assert_eq!(missing_libraries(output), ["libfoo.so.1", "libbar.so"]);
+    }
+
+    #[test]
+    fn missing_libraries_ignore_other_lines() {
+        // Sem ".so" no nome, ou sem a mensagem do carregador, não é uma biblioteca
+        let output = "Gtk-WARNING: cannot open display\n\
+                      plugin: cannot open shared object file: No such file or directory\n\
+                      loaded libfoo.so.1\n";
+        assert!(missing_libraries(output).is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::error::{BuildError, BuildResult, IoResultExt};
+use crate::i18n::gettext;
+
+const WORK_DIR_PREFIX: &str = "appimage-";
+const LOCK_FILE_NAME: &str = ".appimage-creator.lock";
+const MAX_CREATE_ATTEMPTS: u32 = 100;
+
+// Diretório de trabalho exclusivo de uma geração. Enquanto existir, o lockfile
+// com o PID do processo impede que outra instância o considere abandonado; ao
+// ser descartado o diretório é removido, a menos que tenha sido mantido
+pub(super) struct WorkDir {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl WorkDir {
+    pub(super) fn create(root: &Path, package_name: &str) -> BuildResult<Self> {
+        fs::create_dir_all(root).at_path(root)?;
+        remove_stale_work_dirs(root);
+
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+
+        for attempt in 0..MAX_CREATE_ATTEMPTS {
+            let path = root.join(format!(
+                "{}{}-{}-{:x}-{}",
+                WORK_DIR_PREFIX,
+                package_name,
+                process::id(),
+                stamp,
+                attempt
+            ));
+
+            // create_dir falha se o diretório já existir, então cada geração tem o seu
+            match fs::create_dir(&path) {
+                Ok(()) => {
+                    let work_dir = WorkDir { path, keep: false };
+                    work_dir.write_lock()?;
+                    return Ok(work_dir);
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(BuildError::io(&path, err)),
+            }
+        }
+
+        Err(BuildError::io(
+            root,
+            io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                gettext("could not create a unique work directory"),
+            ),
+        ))
+    }
+
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Mantém o diretório (sem o lockfile) após o fim da geração
+    pub(super) fn keep(mut self) -> PathBuf {
+        self.keep = true;
+        self.path.clone()
+    }
+
+    fn write_lock(&self) -> BuildResult<()> {
+        let lock_path = self.path.join(LOCK_FILE_NAME);
+        let mut lock = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+            .at_path(&lock_path)?;
+        writeln!(lock, "{}", process::id()).at_path(&lock_path)
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        if self.keep {
+            let _ = fs::remove_file(self.path.join(LOCK_FILE_NAME));
+        } else {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+// Nome de pacote seguro para caminhos e para o Cargo.toml: apenas letras
+// minúsculas, dígitos, '-' e '_'
+pub(crate) fn sanitize_package_name(name: &str) -> String {
+    let mut sanitized = String::new();
+    for c in name.trim().to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            sanitized.push(c);
+        } else if !sanitized.is_empty() && !sanitized.ends_with('-') {
+            sanitized.push('-');
+        }
+    }
+
+    let sanitized = sanitized.trim_end_matches('-');
+    if sanitized.is_empty() {
+        "app".to_string()
+    } else if sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("app-{}", sanitized)
+    } else {
+        sanitized.to_string()
+    }
+}
+
+// Remove diretórios de gerações cujo processo dono não está mais em execução
+fn remove_stale_work_dirs(root: &Path) {
+    let Ok(entries) = fs::read_dir(root) else {
+        return;
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let is_work_dir = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with(WORK_DIR_PREFIX));
+        if !is_work_dir || !path.is_dir() {
+            continue;
+        }
+
+        // Sem lockfile o diretório foi mantido de propósito
+        let Ok(lock) = fs::read_to_string(path.join(LOCK_FILE_NAME)) else {
+            continue;
+        };
+        let Ok(pid) = lock.trim().parse::<u32>() else {
+            continue;
+        };
+
+        if !process_is_running(pid) {
+            let _ = fs::remove_dir_all(&path);
+        }
+    }
+}
+
+pub(super) fn process_is_running(pid: u32) -> bool {
+    let proc_dir = Path::new("/proc");
+    // Sem /proc não há como saber; nunca remover nesse caso
+    if !proc_dir.join("self").exists() {
+        return true;
+    }
+    proc_dir.join(pid.to_string()).exists()
+}
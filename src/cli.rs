@@ -0,0 +1,720 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::appimage::{
+    self, BackendKind, BuildError, BuildEvent, BuildOptions, CancelFlag, Compression, CompressionAlgorithm, ExistingOutput, LogLevel,
+    IntegrationDirs, OutputMode, PackageFormat, DEFAULT_TEST_TIMEOUT,
+};
+use crate::i18n::{gettext, gettext_f, gettext_noop};
+use crate::profile::Profile;
+use crate::recipe::Recipe;
+
+// Traduzida ao ser exibida
+const USAGE: &str = gettext_noop(
+    "Usage:
+  appimage-creator                      Opens the graphical interface
+  appimage-creator build [options]      Builds from a binary
+  appimage-creator build-appdir <APPDIR> --output <FILE>
+                                        Builds an AppImage from an existing AppDir
+  appimage-creator doctor [options]     Checks the tools used by the build
+                                        (accepts --recipe, --profile, --mode, --backend,
+                                        --reproducible and --compression)
+  appimage-creator integrate <APPIMAGE> [--remove]
+                                        Integrates the AppImage into the menu (copy in
+                                        ~/Applications, .desktop and icons in
+                                        $XDG_DATA_HOME); --remove undoes the integration
+  appimage-creator test <APPIMAGE> [--timeout <S>] [--extract-and-run]
+                                        Runs the AppImage for up to S seconds (default: 10)
+                                        and shows its output and missing libraries; without
+                                        FUSE, uses --appimage-extract-and-run
+  appimage-creator help                 Shows this help
+
+Build options:
+  --recipe <FILE>          Project TOML recipe; the other options override it
+  --profile <NAME>         Profile with author, license, website, App ID prefix,
+                           output folder and build options; fills in what the
+                           recipe and the options leave out. Read from
+                           $XDG_CONFIG_HOME/appimage-creator/profiles/<NAME>.toml
+                           or from a path
+  --binary <FILE>          Application binary (required)
+  --icon <FILE>            Icon in any supported format (required)
+  --name <NAME>            Application name (required)
+  --exec <COMMAND>         Exec command (default: binary name)
+  --categories <LIST>      Categories separated by ';' or ',' (required)
+  --version <VERSION>
+  --comment <TEXT>
+  --author <NAME>
+  --license <SPDX>
+  --website <URL>
+  -o, --output <FOLDER>    Output folder (required)
+  --file-name <TEMPLATE>   File name (default: {name}); variables: {name},
+                           {package}, {version}, {arch}, {app_id} and {date}
+  --mode <MODE>            appimage (default), appimage+appdir or appdir
+  --formats <LIST>         Additional packages separated by commas: deb, rpm,
+                           flatpak, snap, tar.zst, tar.gz (or none)
+  --work-root <FOLDER>     Root of the work directories
+  --verify-reproducible    Builds twice in reproducible mode and compares the hashes
+
+Common options:
+  --profile <NAME>         Profile (in build-appdir and doctor, only the build options)
+  --backend <NAME>         auto (default), cargo-appimage, appimagetool or native
+                           (built-in SquashFS writer; only needs the runtime)
+  --reproducible           Reproducible build (dates from SOURCE_DATE_EPOCH,
+                           fixed permissions and compression; does not use cargo appimage)
+  --compression <ALG>      gzip, xz, zstd, lz4 or default (backend default);
+                           not accepted by the cargo-appimage backend
+  --compression-level <N>  gzip (1-9) or zstd (1-22) level
+  --block-size <SIZE>      SquashFS block, from 4K to 1M (e.g. 256K)
+  --if-exists <ACTION>     If the output already exists: fail (default), overwrite,
+                           increment (Name-1.AppImage) or backup (.bak)
+  --force                  Same as --if-exists overwrite
+  -v, --verbose            Shows the full build log
+",
+);
+
+const BUILD_FLAGS: &[&str] = &[
+    "--recipe",
+    "--profile",
+    "--binary",
+    "--icon",
+    "--name",
+    "--exec",
+    "--categories",
+    "--version",
+    "--comment",
+    "--author",
+    "--license",
+    "--website",
+    "--output",
+    "--file-name",
+    "--mode",
+    "--formats",
+    "--work-root",
+    "--backend",
+    "--compression",
+    "--compression-level",
+    "--block-size",
+    "--if-exists",
+];
+
+const BUILD_APPDIR_FLAGS: &[&str] = &[
+    "--output",
+    "--profile",
+    "--work-root",
+    "--backend",
+    "--compression",
+    "--compression-level",
+    "--block-size",
+    "--if-exists",
+];
+
+const DOCTOR_FLAGS: &[&str] = &["--recipe", "--profile", "--mode", "--backend", "--compression"];
+
+const TEST_FLAGS: &[&str] = &["--timeout"];
+
+// Opções sem valor aceitas por cada comando
+const BUILD_SWITCHES: &[&str] = &["--verbose", "--reproducible", "--verify-reproducible", "--force"];
+
+const BUILD_APPDIR_SWITCHES: &[&str] = &["--verbose", "--reproducible", "--force"];
+
+const DOCTOR_SWITCHES: &[&str] = &["--reproducible"];
+
+const INTEGRATE_SWITCHES: &[&str] = &["--remove"];
+
+const TEST_SWITCHES: &[&str] = &["--extract-and-run"];
+
+#[derive(Debug)]
+enum CliError {
+    Usage(String),
+    Build(BuildError),
+    NotReproducible { first_hash: String, second_hash: String },
+    MissingTools(String),
+    TestFailed(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => f.write_str(&gettext_f(
+                "{message}\n\nRun `appimage-creator help` to see the options.",
+                &[("message", message)],
+            )),
+            CliError::Build(err) => write!(f, "{}", err.describe()),
+            CliError::MissingTools(tools) => {
+                f.write_str(&gettext_f("required tools are missing: {tools}", &[("tools", tools)]))
+            }
+            CliError::TestFailed(summary) => f.write_str(&gettext_f("the test failed: {summary}", &[("summary", summary)])),
+            CliError::NotReproducible {
+                first_hash,
+                second_hash,
+            } => f.write_str(&gettext_f(
+                "the two builds differ (MD5 {first} != {second})",
+                &[("first", first_hash), ("second", second_hash)],
+            )),
+        }
+    }
+}
+
+impl From<BuildError> for CliError {
+    fn from(err: BuildError) -> Self {
+        CliError::Build(err)
+    }
+}
+
+// Executa o comando indicado nos argumentos e retorna o código de saída, ou
+// None quando não há comando e a interface gráfica deve ser aberta
+pub(crate) fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+
+    let result = match command.as_str() {
+        "build" => build(rest),
+        "build-appdir" => build_appdir(rest),
+        "doctor" => doctor(rest),
+        "integrate" => integrate(rest),
+        "test" => test(rest),
+        "help" | "--help" | "-h" => {
+            print!("{}", gettext(USAGE));
+            Ok(())
+        }
+        "--version" | "-V" => {
+            println!("appimage-creator {}", env!("CARGO_PKG_VERSION"));
+            Ok(())
+        }
+        // Só a ausência de argumentos abre a interface gráfica
+        unknown => {
+            eprintln!("{}", gettext_f("Unknown command: {command}", &[("command", &unknown)]));
+            eprint!("\n{}", gettext(USAGE));
+            return Some(2);
+        }
+    };
+
+    Some(match result {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("{}", gettext_f("Error: {error}", &[("error", &err)]));
+            if let CliError::Build(build_err) = &err {
+                report_build_error(build_err);
+            }
+            exit_code(&err)
+        }
+    })
+}
+
+fn build(args: &[String]) -> Result<(), CliError> {
+    let parsed = ParsedArgs::parse(args, BUILD_FLAGS, BUILD_SWITCHES)?;
+    if let Some(extra) = parsed.positional.first() {
+        return Err(CliError::Usage(gettext_f("Unexpected argument: {argument}", &[("argument", extra)])));
+    }
+
+    let Recipe {
+        output_folder,
+        app: mut metadata,
+        build: mut options,
+    } = load_recipe(&parsed)?;
+
+    let text_fields = [
+        ("--binary", &mut metadata.binary_path),
+        ("--icon", &mut metadata.icon_path),
+        ("--name", &mut metadata.name),
+        ("--exec", &mut metadata.exec),
+        ("--version", &mut metadata.version),
+        ("--comment", &mut metadata.comment),
+        ("--author", &mut metadata.author),
+        ("--license", &mut metadata.license),
+        ("--website", &mut metadata.website),
+    ];
+    for (flag, field) in text_fields {
+        if let Some(value) = parsed.value(flag) {
+            *field = value.to_string();
+        }
+    }
+    if let Some(categories) = parsed.value("--categories") {
+        metadata.categories = normalize_categories(categories);
+    }
+
+    // Obrigatórios: pela receita ou pelas opções
+    let required_fields = [
+        ("--binary", &metadata.binary_path),
+        ("--icon", &metadata.icon_path),
+        ("--name", &metadata.name),
+        ("--categories", &metadata.categories),
+    ];
+    for (flag, value) in required_fields {
+        if value.trim().is_empty() {
+            return Err(CliError::Usage(gettext_f("{option} is required", &[("option", &flag)])));
+        }
+    }
+
+    if metadata.exec.is_empty() {
+        metadata.exec = Path::new(&metadata.binary_path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+    }
+
+    if let Some(id) = parsed.value("--mode") {
+        options.output_mode = OutputMode::from_id(id)
+            .ok_or_else(|| CliError::Usage(gettext_f("Unknown output mode: {mode}", &[("mode", &id)])))?;
+    }
+    if let Some(template) = parsed.value("--file-name") {
+        options.file_name = Some(template.to_string());
+    }
+    if let Some(list) = parsed.value("--formats") {
+        options.extra_formats = parse_formats(list)?;
+    }
+    apply_build_flags(&parsed, &mut options)?;
+
+    let output_folder = match parsed.value("--output") {
+        Some(folder) => PathBuf::from(folder),
+        None => output_folder
+            .ok_or_else(|| CliError::Usage(gettext_f("{option} is required", &[("option", &"--output")])))?,
+    };
+    if !output_folder.is_dir() {
+        return Err(CliError::Build(BuildError::invalid_input(
+            appimage::InputField::OutputFolder,
+            gettext_f("{path} is not a folder", &[("path", &output_folder.display())]),
+        )));
+    }
+    let output_path = options.output_path(&output_folder, &metadata)?;
+
+    let verbose = parsed.has("--verbose");
+    if parsed.has("--verify-reproducible") {
+        if !options.output_mode.builds_appimage() {
+            return Err(CliError::Usage(
+                gettext("--verify-reproducible requires a mode that builds the AppImage").to_string(),
+            ));
+        }
+        let check = appimage::verify_reproducible(
+            &metadata,
+            &output_path,
+            &options,
+            &|event| print_event(&event, verbose),
+            &CancelFlag::default(),
+        )?;
+        if !check.is_reproducible() {
+            return Err(CliError::NotReproducible {
+                first_hash: check.first_hash,
+                second_hash: check.second_hash,
+            });
+        }
+        eprintln!(
+            "{}",
+            gettext_f("Reproducible build: MD5 {hash} in both builds", &[("hash", &check.first_hash)])
+        );
+        println!("{}", check.output_path.display());
+        return Ok(());
+    }
+
+    let artifact = appimage::generate_appimage(
+        &metadata,
+        &output_path,
+        &options,
+        &|event| print_event(&event, verbose),
+        &CancelFlag::default(),
+    )?;
+
+    println!("{}", artifact.display());
+    if options.output_mode == OutputMode::KeepAppDir {
+        println!("{}", appimage::appdir_output_path(&artifact).display());
+    }
+    let output_folder = artifact.parent().unwrap_or(&output_folder);
+    for package in appimage::package_outputs(output_folder, &metadata, &options.extra_formats) {
+        println!("{}", package.display());
+    }
+    Ok(())
+}
+
+// Receita informada (ou vazia) completada pelo perfil; as opções da linha de
+// comando sobrescrevem as duas
+fn load_recipe(parsed: &ParsedArgs) -> Result<Recipe, CliError> {
+    let mut recipe = match parsed.value("--recipe") {
+        Some(path) => Recipe::load(Path::new(path)).map_err(|err| BuildError::io(Path::new(path), err))?,
+        None => Recipe::default(),
+    };
+    if let Some(name) = parsed.value("--profile") {
+        Profile::find(name)
+            .map_err(|err| BuildError::io(Path::new(name), err))?
+            .fill(&mut recipe);
+    }
+    Ok(recipe)
+}
+
+// "deb,rpm" -> [Deb, Rpm]; "none" limpa os formatos da receita
+fn parse_formats(list: &str) -> Result<Vec<PackageFormat>, CliError> {
+    let mut formats = Vec::new();
+    for id in list.split(',').map(str::trim).filter(|id| !id.is_empty() && *id != "none") {
+        let format =
+            PackageFormat::from_id(id).ok_or_else(|| CliError::Usage(gettext_f("Unknown format: {format}", &[("format", &id)])))?;
+        if !formats.contains(&format) {
+            formats.push(format);
+        }
+    }
+    Ok(formats)
+}
+
+fn build_appdir(args: &[String]) -> Result<(), CliError> {
+    let parsed = ParsedArgs::parse(args, BUILD_APPDIR_FLAGS, BUILD_APPDIR_SWITCHES)?;
+    let appdir = match parsed.positional.as_slice() {
+        [appdir] => PathBuf::from(appdir),
+        [] => return Err(CliError::Usage(gettext("Specify the AppDir").to_string())),
+        [_, extra, ..] => return Err(CliError::Usage(gettext_f("Unexpected argument: {argument}", &[("argument", extra)]))),
+    };
+
+    let output_path = PathBuf::from(parsed.required("--output")?);
+    let mut options = load_recipe(&parsed)?.build;
+    apply_build_flags(&parsed, &mut options)?;
+
+    let verbose = parsed.has("--verbose");
+    let artifact = appimage::build_appimage_from_appdir(
+        &appdir,
+        &output_path,
+        &options,
+        &|event| print_event(&event, verbose),
+        &CancelFlag::default(),
+    )?;
+
+    println!("{}", artifact.display());
+    Ok(())
+}
+
+// Lista as ferramentas com versão e caminho; falha se faltar alguma necessária
+// para a geração com as opções informadas
+fn doctor(args: &[String]) -> Result<(), CliError> {
+    let parsed = ParsedArgs::parse(args, DOCTOR_FLAGS, DOCTOR_SWITCHES)?;
+    if let Some(extra) = parsed.positional.first() {
+        return Err(CliError::Usage(gettext_f("Unexpected argument: {argument}", &[("argument", extra)])));
+    }
+
+    let mut options = load_recipe(&parsed)?.build;
+    if let Some(id) = parsed.value("--mode") {
+        options.output_mode = OutputMode::from_id(id)
+            .ok_or_else(|| CliError::Usage(gettext_f("Unknown output mode: {mode}", &[("mode", &id)])))?;
+    }
+    apply_build_flags(&parsed, &mut options)?;
+
+    let report = appimage::check_environment(&options);
+    println!("{}", gettext_f("Build: {pipeline}", &[("pipeline", &report.pipeline)]));
+    for check in &report.checks {
+        let requirement = if check.required { "" } else { gettext(" (optional)") };
+        println!("{} {}{}: {}", check.symbol(), check.name, requirement, check.status());
+        if let Some(path) = check.path.as_ref().filter(|_| check.is_ok()) {
+            println!("    {}", path.display());
+        }
+        if !check.is_ok() {
+            println!("    {}", check.purpose);
+            println!("    {}", gettext_f("To install: {hint}", &[("hint", &check.install_hint)]));
+        }
+    }
+
+    let missing: Vec<&str> = report.missing_required().iter().map(|check| check.name).collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(CliError::MissingTools(missing.join(", ")))
+    }
+}
+
+// Integra (ou, com --remove, desintegra) um AppImage ao menu do usuário
+fn integrate(args: &[String]) -> Result<(), CliError> {
+    let parsed = ParsedArgs::parse(args, &[], INTEGRATE_SWITCHES)?;
+    let appimage = match parsed.positional.as_slice() {
+        [appimage] => PathBuf::from(appimage),
+        [] => return Err(CliError::Usage(gettext("Specify the AppImage").to_string())),
+        [_, extra, ..] => return Err(CliError::Usage(gettext_f("Unexpected argument: {argument}", &[("argument", extra)]))),
+    };
+    let dirs = IntegrationDirs::for_user()?;
+
+    if parsed.has("--remove") {
+        let removed = appimage::remove_integration(&appimage, &dirs)?;
+        if removed.is_empty() {
+            eprintln!(
+                "{}",
+                gettext_f("No integration found for {path}", &[("path", &appimage.display())])
+            );
+        }
+        for path in removed {
+            println!("{}", path.display());
+        }
+    } else {
+        let integration = appimage::integrate(&appimage, &dirs)?;
+        eprintln!(
+            "==> {}",
+            gettext_f("Integrated: {path}", &[("path", &integration.appimage.display())])
+        );
+        println!("{}", integration.desktop_file.display());
+        for icon in &integration.icons {
+            println!("{}", icon.display());
+        }
+    }
+    Ok(())
+}
+
+// Executa o AppImage como o botão "Testar" da interface
+fn test(args: &[String]) -> Result<(), CliError> {
+    let parsed = ParsedArgs::parse(args, TEST_FLAGS, TEST_SWITCHES)?;
+    let appimage = match parsed.positional.as_slice() {
+        [appimage] => PathBuf::from(appimage),
+        [] => return Err(CliError::Usage(gettext("Specify the AppImage").to_string())),
+        [_, extra, ..] => return Err(CliError::Usage(gettext_f("Unexpected argument: {argument}", &[("argument", extra)]))),
+    };
+    let timeout = match parsed.value("--timeout") {
+        Some(value) => value
+            .parse::<u64>()
+            .ok()
+            .filter(|seconds| *seconds > 0)
+            .map(Duration::from_secs)
+            .ok_or_else(|| CliError::Usage(gettext_f("Invalid timeout: {value}", &[("value", &value)])))?,
+        None => DEFAULT_TEST_TIMEOUT,
+    };
+
+    let run = appimage::test_appimage(&appimage, timeout, parsed.has("--extract-and-run"), &CancelFlag::default())?;
+    eprintln!("==> {}", run.command);
+    print!("{}", run.output());
+    eprintln!("==> {}", run.summary());
+    if !run.missing_libraries.is_empty() {
+        eprintln!("{}", gettext("Missing libraries:"));
+        for library in &run.missing_libraries {
+            eprintln!("    {}", library);
+        }
+    }
+
+    if run.passed() {
+        Ok(())
+    } else {
+        Err(CliError::TestFailed(run.summary()))
+    }
+}
+
+// Opções comuns a build e build-appdir, aplicadas sobre as da receita
+fn apply_build_flags(parsed: &ParsedArgs, options: &mut BuildOptions) -> Result<(), CliError> {
+    if let Some(work_root) = parsed.value("--work-root") {
+        options.work_root = Some(PathBuf::from(work_root));
+    }
+    if parsed.has("--reproducible") {
+        options.reproducible = true;
+    }
+    if let Some(id) = parsed.value("--if-exists") {
+        options.if_exists = ExistingOutput::from_id(id)
+            .ok_or_else(|| CliError::Usage(gettext_f("Unknown action for --if-exists: {action}", &[("action", &id)])))?;
+    }
+    if parsed.has("--force") {
+        options.if_exists = ExistingOutput::Overwrite;
+    }
+    if let Some(id) = parsed.value("--backend") {
+        options.backend =
+            BackendKind::from_id(id).ok_or_else(|| CliError::Usage(gettext_f("Unknown backend: {backend}", &[("backend", &id)])))?;
+    }
+
+    // Trocar o algoritmo descarta nível e bloco anteriores
+    match parsed.value("--compression") {
+        Some("default") => options.compression = None,
+        Some(id) => {
+            let algorithm = CompressionAlgorithm::from_id(id)
+                .ok_or_else(|| CliError::Usage(gettext_f("Unknown compression: {compression}", &[("compression", &id)])))?;
+            options.compression = Some(Compression::new(algorithm));
+        }
+        None => {}
+    }
+
+    let level = parsed.value("--compression-level");
+    let block_size = parsed.value("--block-size");
+    if level.is_none() && block_size.is_none() {
+        return Ok(());
+    }
+    let Some(compression) = options.compression.as_mut() else {
+        return Err(CliError::Usage(
+            gettext("--compression-level and --block-size require --compression").to_string(),
+        ));
+    };
+    if let Some(level) = level {
+        compression.level = Some(
+            level
+                .parse()
+                .map_err(|_| CliError::Usage(gettext_f("Invalid compression level: {level}", &[("level", &level)])))?,
+        );
+    }
+    if let Some(block_size) = block_size {
+        compression.block_size = Some(parse_block_size(block_size)?);
+    }
+    Ok(())
+}
+
+// "131072", "128K" ou "1M"
+fn parse_block_size(value: &str) -> Result<u32, CliError> {
+    let trimmed = value.trim();
+    let (digits, multiplier) = match trimmed.char_indices().last() {
+        Some((index, 'K' | 'k')) => (&trimmed[..index], 1024),
+        Some((index, 'M' | 'm')) => (&trimmed[..index], 1024 * 1024),
+        _ => (trimmed, 1),
+    };
+    digits
+        .parse::<u32>()
+        .ok()
+        .and_then(|size| size.checked_mul(multiplier))
+        .ok_or_else(|| CliError::Usage(gettext_f("Invalid block size: {value}", &[("value", &value)])))
+}
+
+// Etapas e avisos vão para stderr (o erro final é impresso por `run`); com --verbose, o log completo
+fn print_event(event: &BuildEvent, verbose: bool) {
+    match event {
+        BuildEvent::StageStarted(stage) if !verbose => eprintln!("==> {}...", stage.label()),
+        BuildEvent::Log(record) if verbose || record.level == LogLevel::Warning => {
+            eprintln!("{}", record.format_line());
+        }
+        // Com --verbose o relatório já aparece no log
+        BuildEvent::SizeReport(report) if !verbose => {
+            eprintln!("==> {}", report.summary());
+            for line in report.largest_lines() {
+                eprintln!("    {}", line);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn report_build_error(err: &BuildError) {
+    if let Some(field) = err.field() {
+        eprintln!(
+            "{}",
+            gettext_f("Check the option matching \"{field}\".", &[("field", &field.label())])
+        );
+    }
+    if let BuildError::OutputExists { .. } = err {
+        eprintln!("{}", gettext("Use --force to replace it, or --if-exists increment|backup."));
+    }
+    if let Some(work_dir) = err.kept_work_dir() {
+        eprintln!("{}", gettext_f("Work directory kept at: {path}", &[("path", &work_dir.display())]));
+    }
+}
+
+fn exit_code(err: &CliError) -> i32 {
+    match err {
+        CliError::Usage(_) => 2,
+        CliError::Build(BuildError::InvalidInput { .. }) => 2,
+        CliError::Build(BuildError::MissingTool { .. }) | CliError::MissingTools(_) => 3,
+        CliError::Build(BuildError::OutputExists { .. } | BuildError::OutputBusy { .. }) => 4,
+        CliError::Build(BuildError::Cancelled) => 130,
+        CliError::Build(_) | CliError::NotReproducible { .. } | CliError::TestFailed(_) => 1,
+    }
+}
+
+// "Graphics,Viewer" ou "Graphics;Viewer" -> "Graphics;Viewer;"
+fn normalize_categories(value: &str) -> String {
+    let categories: Vec<&str> = value
+        .split([';', ','])
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .collect();
+    if categories.is_empty() {
+        String::new()
+    } else {
+        format!("{};", categories.join(";"))
+    }
+}
+
+struct ParsedArgs {
+    values: Vec<(String, String)>,
+    switches: Vec<String>,
+    positional: Vec<String>,
+}
+
+impl ParsedArgs {
+    // Aceita `--opção valor`, `--opção=valor`, os switches do comando e argumentos posicionais
+    fn parse(args: &[String], flags: &[&str], switches: &[&str]) -> Result<Self, CliError> {
+        let mut parsed = ParsedArgs {
+            values: Vec::new(),
+            switches: Vec::new(),
+            positional: Vec::new(),
+        };
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let arg = match arg.as_str() {
+                "-o" => "--output",
+                "-v" => "--verbose",
+                other => other,
+            };
+
+            if !arg.starts_with('-') {
+                parsed.positional.push(arg.to_string());
+                continue;
+            }
+
+            if switches.contains(&arg) {
+                parsed.switches.push(arg.to_string());
+                continue;
+            }
+
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (arg, None),
+            };
+            if !flags.contains(&name) {
+                return Err(CliError::Usage(gettext_f("Unknown option: {option}", &[("option", &name)])));
+            }
+
+            let value = match inline_value {
+                Some(value) => value,
+                None => iter
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| CliError::Usage(gettext_f("{option} requires a value", &[("option", &name)])))?,
+            };
+            parsed.values.push((name.to_string(), value));
+        }
+
+        Ok(parsed)
+    }
+
+    // A última ocorrência vence
+    fn value(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(flag, _)| flag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn required(&self, name: &str) -> Result<&str, CliError> {
+        self.value(name)
+            .filter(|value| !value.trim().is_empty())
+            .ok_or_else(|| CliError::Usage(gettext_f("{option} is required", &[("option", &name)])))
+    }
+
+    fn has(&self, switch: &str) -> bool {
+        self.switches.iter().any(|s| s == switch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_command_is_a_usage_error() {
+        assert_eq!(run(&[]), None);
+        assert_eq!(run(&["biuld".to_string()]), Some(2));
+        assert_eq!(run(&["--version".to_string()]), Some(0));
+    }
+
+    #[test]
+    fn switches_are_checked_per_command() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        let parsed = ParsedArgs::parse(&args(&["app.AppImage", "--remove"]), &[], INTEGRATE_SWITCHES).unwrap();
+        assert!(parsed.has("--remove"));
+
+        for (flags, switches, arg) in [
+            (&[][..], INTEGRATE_SWITCHES, "--verbose"),
+            (TEST_FLAGS, TEST_SWITCHES, "--remove"),
+            (DOCTOR_FLAGS, DOCTOR_SWITCHES, "--verify-reproducible"),
+            (BUILD_APPDIR_FLAGS, BUILD_APPDIR_SWITCHES, "--extract-and-run"),
+        ] {
+            let result = ParsedArgs::parse(&args(&["app", arg]), flags, switches);
+            assert!(matches!(result, Err(CliError::Usage(_))), "{} deveria ser recusado", arg);
+        }
+
+        assert_eq!(run(&args(&["integrate", "app.AppImage", "--extract-and-run"])), Some(2));
+    }
+}
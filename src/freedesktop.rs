@@ -0,0 +1,773 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::gettext_f;
+use crate::AppImageMetadata;
+
+const DESKTOP_ENTRY_GROUP: &str = "[Desktop Entry]";
+
+// Chaves do .desktop que viram campos do formulário ou são geradas pelo template
+const DESKTOP_MAPPED_KEYS: [&str; 8] = [
+    "Type", "Name", "Exec", "Icon", "Categories", "Version", "Comment", "Terminal",
+];
+
+// Elementos do metainfo gerados pelo template; os demais são preservados como estão
+const METAINFO_GENERATED_ELEMENTS: [&str; 4] = ["id", "project_license", "launchable", "provides"];
+
+// Profundidade máxima ao procurar o PNG do ícone referenciado pelo .desktop
+const ICON_SEARCH_DEPTH: usize = 4;
+
+// Campos extraídos de um .desktop existente. Chaves sem campo na interface
+// (Keywords, MimeType, Name[pt_BR]...) e grupos extras ficam guardados
+pub(crate) fn import_desktop_file(path: &Path) -> io::Result<AppImageMetadata> {
+    let content = fs::read_to_string(path)?;
+    let entry = parse_desktop_entry(&content).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            gettext_f(
+                "{path} does not contain the {group} group",
+                &[("path", &path.display()), ("group", &DESKTOP_ENTRY_GROUP)],
+            ),
+        )
+    })?;
+
+    let mut metadata = AppImageMetadata {
+        desktop_file_path: path.to_string_lossy().to_string(),
+        desktop_extra_groups: entry.extra_groups,
+        ..Default::default()
+    };
+
+    for (key, value) in entry.keys {
+        match key.as_str() {
+            "Name" => metadata.name = value,
+            "Exec" => metadata.exec = exec_command_name(&value).unwrap_or_default(),
+            "Comment" => metadata.comment = value,
+            "Categories" => metadata.categories = normalize_categories(&value),
+            "Icon" => {
+                if let Some(icon) = resolve_icon(path, &value) {
+                    metadata.icon_path = icon.to_string_lossy().to_string();
+                }
+            }
+            // Terminal=false e Type=Application já vêm do template
+            "Terminal" if value != "false" => metadata.desktop_extra.push((key, value)),
+            key_name if DESKTOP_MAPPED_KEYS.contains(&key_name) => {}
+            _ => metadata.desktop_extra.push((key, value)),
+        }
+    }
+
+    Ok(metadata)
+}
+
+// Campos extraídos de um AppStream metainfo/appdata existente
+pub(crate) fn import_metainfo(path: &Path) -> io::Result<AppImageMetadata> {
+    let content = fs::read_to_string(path)?;
+    let children = component_children(&content).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            gettext_f("{path} does not contain a <component> element", &[("path", &path.display())]),
+        )
+    })?;
+
+    let mut metadata = AppImageMetadata {
+        metainfo_file_path: path.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+
+    for element in children {
+        let translated = attribute(element.attrs, "xml:lang").is_some();
+        let text = unescape(element.inner.trim());
+
+        match element.name {
+            "id" => metadata.app_id = text,
+            "name" if !translated => metadata.name = text,
+            "summary" if !translated => metadata.comment = text,
+            "project_license" => metadata.license = text,
+            "url" if attribute(element.attrs, "type") == Some("homepage") => {
+                metadata.website = text;
+            }
+            "provides" => {
+                if let Some(binary) = child_elements(element.inner)
+                    .into_iter()
+                    .find(|child| child.name == "binary")
+                {
+                    metadata.exec = unescape(binary.inner.trim());
+                }
+            }
+            name if METAINFO_GENERATED_ELEMENTS.contains(&name) => {}
+            _ => {
+                match element.name {
+                    "developer_name" if !translated => metadata.author = text,
+                    "developer" => {
+                        if let Some(developer) = child_elements(element.inner)
+                            .into_iter()
+                            .find(|child| child.name == "name")
+                        {
+                            metadata.author = unescape(developer.inner.trim());
+                        }
+                    }
+                    "categories" => {
+                        let categories: Vec<String> = child_elements(element.inner)
+                            .into_iter()
+                            .filter(|child| child.name == "category")
+                            .map(|child| unescape(child.inner.trim()))
+                            .collect();
+                        metadata.categories = normalize_categories(&categories.join(";"));
+                    }
+                    _ => {}
+                }
+                metadata.metainfo_extra.push(element.raw.to_string());
+            }
+        }
+    }
+
+    Ok(metadata)
+}
+
+// Campos de um AppDir existente: o .desktop da raiz, completado pelo metainfo em
+// usr/share/metainfo e pelo binário de usr/bin indicado no Exec
+pub(crate) fn import_appdir(appdir: &Path) -> io::Result<AppImageMetadata> {
+    let files_with = |dir: &Path, suffixes: &[&str]| -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)
+            .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+            .unwrap_or_default();
+        files.retain(|path| {
+            path.is_file() && suffixes.iter().any(|suffix| path.to_string_lossy().ends_with(suffix))
+        });
+        files.sort();
+        files
+    };
+
+    let desktop_file = files_with(appdir, &[".desktop"]).into_iter().next().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            gettext_f("{path} has no .desktop file at its root", &[("path", &appdir.display())]),
+        )
+    })?;
+    let mut metadata = import_desktop_file(&desktop_file)?;
+
+    let metainfo_dir = appdir.join("usr/share/metainfo");
+    if let Some(metainfo_file) = files_with(&metainfo_dir, &[".metainfo.xml", ".appdata.xml"]).first() {
+        let metainfo = import_metainfo(metainfo_file)?;
+        let fields = [
+            (&mut metadata.name, metainfo.name),
+            (&mut metadata.exec, metainfo.exec),
+            (&mut metadata.comment, metainfo.comment),
+            (&mut metadata.categories, metainfo.categories),
+            (&mut metadata.author, metainfo.author),
+            (&mut metadata.license, metainfo.license),
+            (&mut metadata.website, metainfo.website),
+            (&mut metadata.app_id, metainfo.app_id),
+        ];
+        for (field, value) in fields {
+            if field.is_empty() {
+                *field = value;
+            }
+        }
+        metadata.metainfo_file_path = metainfo.metainfo_file_path;
+        metadata.metainfo_extra = metainfo.metainfo_extra;
+    }
+
+    let binary = appdir.join("usr/bin").join(&metadata.exec);
+    if !metadata.exec.is_empty() && binary.is_file() {
+        metadata.binary_path = binary.to_string_lossy().to_string();
+    }
+    Ok(metadata)
+}
+
+// Valida um .desktop usado sem regeneração e retorna o nome do ícone referenciado
+pub(crate) fn validate_desktop_entry(content: &str, exec: &str) -> Result<String, Vec<String>> {
+    let Some(entry) = parse_desktop_entry(content) else {
+        return Err(vec![gettext_f("missing {group} group", &[("group", &DESKTOP_ENTRY_GROUP)])]);
+    };
+
+    let value = |key: &str| entry.value(key);
+    let mut problems = launcher_problems(&entry);
+
+    if let Some(command) = value("Exec").and_then(exec_command_name).filter(|command| command != exec) {
+        problems.push(gettext_f(
+            "Exec runs '{command}', but the packaged binary is '{exec}'",
+            &[("command", &command), ("exec", &exec)],
+        ));
+    }
+
+    let icon = match value("Icon") {
+        Some(icon) if icon.contains('/') => {
+            problems.push(gettext_f("Icon must be an icon name, not a path ({icon})", &[("icon", &icon)]));
+            None
+        }
+        Some(icon) if !icon.trim().is_empty() => Some(icon.trim().to_string()),
+        _ => {
+            problems.push(gettext_f("missing {key} key", &[("key", &"Icon")]));
+            None
+        }
+    };
+
+    if value("Categories").filter(|categories| !categories.trim().is_empty()).is_none() {
+        problems.push(gettext_f("missing {key} key", &[("key", &"Categories")]));
+    }
+
+    match icon {
+        Some(icon) if problems.is_empty() => Ok(icon),
+        _ => Err(problems),
+    }
+}
+
+// Valida o .desktop de um AppImage pronto antes de integrá-lo ao menu: basta
+// que ele abra um aplicativo com nome
+pub(crate) fn validate_integrated_entry(content: &str) -> Result<(), Vec<String>> {
+    let Some(entry) = parse_desktop_entry(content) else {
+        return Err(vec![gettext_f("missing {group} group", &[("group", &DESKTOP_ENTRY_GROUP)])]);
+    };
+    let problems = launcher_problems(&entry);
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+// Tipo, nome e comando, exigidos de qualquer .desktop de aplicativo
+fn launcher_problems(entry: &DesktopEntry) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    match entry.value("Type") {
+        Some("Application") => {}
+        Some(other) => problems.push(gettext_f("Type must be Application (found: {value})", &[("value", &other)])),
+        None => problems.push(gettext_f("missing {key} key", &[("key", &"Type")])),
+    }
+
+    if entry.value("Name").filter(|name| !name.trim().is_empty()).is_none() {
+        problems.push(gettext_f("missing {key} key", &[("key", &"Name")]));
+    }
+
+    if entry.value("Exec").and_then(exec_command_name).is_none() {
+        problems.push(gettext_f("missing {key} key", &[("key", &"Exec")]));
+    }
+
+    problems
+}
+
+// Valida um metainfo usado sem regeneração e retorna o ID do componente
+pub(crate) fn validate_metainfo(content: &str, desktop_file_name: &str) -> Result<String, Vec<String>> {
+    let Some(children) = component_children(content) else {
+        return Err(vec![gettext_f("missing <{element}> element", &[("element", &"component")])]);
+    };
+
+    let find = |name: &str| {
+        children
+            .iter()
+            .find(|e| e.name == name && attribute(e.attrs, "xml:lang").is_none())
+    };
+
+    let mut problems = Vec::new();
+
+    let id = find("id").map(|e| unescape(e.inner.trim())).filter(|id| !id.is_empty());
+    if id.is_none() {
+        problems.push(gettext_f("missing <{element}> element", &[("element", &"id")]));
+    }
+
+    for required in ["name", "summary", "metadata_license"] {
+        if find(required).is_none() {
+            problems.push(gettext_f("missing <{element}> element", &[("element", &required)]));
+        }
+    }
+
+    let launchable = children
+        .iter()
+        .find(|e| e.name == "launchable" && attribute(e.attrs, "type") == Some("desktop-id"))
+        .map(|e| unescape(e.inner.trim()));
+    match launchable {
+        Some(desktop_id) if desktop_id == desktop_file_name => {}
+        Some(desktop_id) => problems.push(gettext_f(
+            "<launchable> points to '{desktop_id}', but the packaged .desktop is '{desktop_file}'",
+            &[("desktop_id", &desktop_id), ("desktop_file", &desktop_file_name)],
+        )),
+        None => problems.push(gettext_f(
+            "missing <{element}> element",
+            &[("element", &"launchable type=\"desktop-id\"")],
+        )),
+    }
+
+    match id {
+        Some(id) if problems.is_empty() => Ok(id),
+        _ => Err(problems),
+    }
+}
+
+// Aplica as chaves preservadas sobre um .desktop gerado: chaves já presentes
+// no template são substituídas, as demais acrescentadas ao grupo principal
+pub(crate) fn apply_desktop_extras(content: &str, metadata: &AppImageMetadata) -> String {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+
+    for (key, value) in &metadata.desktop_extra {
+        let prefix = format!("{}=", key);
+        let line = format!("{}={}", key, value);
+        match lines.iter_mut().find(|l| l.starts_with(&prefix)) {
+            Some(existing) => *existing = line,
+            None => lines.push(line),
+        }
+    }
+
+    let mut result = lines.join("\n");
+    result.push('\n');
+
+    if !metadata.desktop_extra_groups.trim().is_empty() {
+        result.push('\n');
+        result.push_str(metadata.desktop_extra_groups.trim_end());
+        result.push('\n');
+    }
+
+    result
+}
+
+// Valor de Icon no grupo principal de um .desktop
+pub(crate) fn desktop_entry_icon(content: &str) -> Option<String> {
+    parse_desktop_entry(content)?
+        .keys
+        .into_iter()
+        .find(|(key, _)| key == "Icon")
+        .map(|(_, value)| value)
+        .filter(|value| !value.is_empty())
+}
+
+// .desktop de um AppImage integrado ao sistema: o comando das linhas Exec (do
+// grupo principal e das ações) passa a ser o AppImage, mantendo os argumentos,
+// TryExec aponta para ele e Icon, se informado, para o ícone instalado
+pub(crate) fn integrated_desktop_entry(content: &str, appimage: &Path, icon: Option<&str>) -> String {
+    let exec = desktop_exec_quote(&appimage.to_string_lossy());
+    let mut lines = Vec::new();
+    let mut group = String::new();
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            group = trimmed.to_string();
+            lines.push(line.to_string());
+            continue;
+        }
+        let in_entry = group == DESKTOP_ENTRY_GROUP || group.starts_with("[Desktop Action ");
+        let Some((key, value)) = trimmed.split_once('=').filter(|_| in_entry) else {
+            lines.push(line.to_string());
+            continue;
+        };
+
+        match key.trim() {
+            "Exec" => {
+                let arguments = exec_arguments(value.trim());
+                if arguments.is_empty() {
+                    lines.push(format!("Exec={}", exec));
+                } else {
+                    lines.push(format!("Exec={} {}", exec, arguments));
+                }
+                if group == DESKTOP_ENTRY_GROUP {
+                    lines.push(format!("TryExec={}", appimage.to_string_lossy().replace('\\', "\\\\")));
+                }
+            }
+            "TryExec" => {}
+            "Icon" => lines.push(match icon {
+                Some(icon) => format!("Icon={}", icon),
+                None => line.to_string(),
+            }),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut result = lines.join("\n");
+    result.push('\n');
+    result
+}
+
+// Argumentos de uma linha Exec, sem o comando (que pode estar entre aspas)
+fn exec_arguments(exec: &str) -> &str {
+    let end = if let Some(quoted) = exec.strip_prefix('"') {
+        let mut escaped = false;
+        quoted
+            .char_indices()
+            .find(|&(_, c)| {
+                let closing = c == '"' && !escaped;
+                escaped = c == '\\' && !escaped;
+                closing
+            })
+            .map(|(index, _)| index + 2)
+            .unwrap_or(exec.len())
+    } else {
+        exec.find(char::is_whitespace).unwrap_or(exec.len())
+    };
+    exec[end..].trim_start()
+}
+
+// Caminho como argumento de Exec: entre aspas, com ", `, $ e \ escapados, e cada
+// \ dobrado por ser um valor do tipo string. '%' vira "%%" (não é código de campo)
+fn desktop_exec_quote(path: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in path.chars() {
+        match c {
+            '"' | '`' | '$' => {
+                quoted.push_str("\\\\");
+                quoted.push(c);
+            }
+            '\\' => quoted.push_str("\\\\\\\\"),
+            '%' => quoted.push_str("%%"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Indica se um elemento preservado do metainfo substitui o gerado pelo template
+pub(crate) fn has_metainfo_extra(metadata: &AppImageMetadata, name: &str, url_type: Option<&str>) -> bool {
+    metadata.metainfo_extra.iter().any(|raw| {
+        child_elements(raw).first().is_some_and(|element| {
+            element.name == name
+                && (url_type.is_none() || attribute(element.attrs, "type") == url_type)
+        })
+    })
+}
+
+struct DesktopEntry {
+    keys: Vec<(String, String)>,
+    extra_groups: String,
+}
+
+impl DesktopEntry {
+    fn value(&self, key: &str) -> Option<&str> {
+        self.keys.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+}
+
+fn parse_desktop_entry(content: &str) -> Option<DesktopEntry> {
+    let mut keys = Vec::new();
+    let mut extra_groups = String::new();
+    let mut in_main_group = false;
+    let mut found_main_group = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            in_main_group = trimmed == DESKTOP_ENTRY_GROUP;
+            found_main_group |= in_main_group;
+        }
+
+        if in_main_group {
+            if trimmed.starts_with('#') || trimmed.starts_with('[') {
+                continue;
+            }
+            if let Some((key, value)) = trimmed.split_once('=') {
+                keys.push((key.trim().to_string(), value.trim().to_string()));
+            }
+        } else if found_main_group {
+            // Grupos adicionais ([Desktop Action ...]) seguem como estão; o que
+            // vem antes de [Desktop Entry] é descartado
+            if !extra_groups.is_empty() || !trimmed.is_empty() {
+                extra_groups.push_str(line);
+                extra_groups.push('\n');
+            }
+        }
+    }
+
+    found_main_group.then_some(DesktopEntry { keys, extra_groups })
+}
+
+// Nome do executável de uma linha Exec, ignorando `env VAR=...` e códigos %f/%U
+fn exec_command_name(exec: &str) -> Option<String> {
+    let (mut command, mut rest) = exec_first_token(exec);
+
+    if Path::new(&command).file_name().and_then(|n| n.to_str()) == Some("env") {
+        loop {
+            let (token, next) = exec_first_token(rest);
+            if token.is_empty() {
+                return None;
+            }
+            rest = next;
+            if !token.contains('=') && !token.starts_with('-') {
+                command = token;
+                break;
+            }
+        }
+    }
+
+    Path::new(&command)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+// Primeiro argumento de uma linha Exec, sem aspas, e o restante da linha
+fn exec_first_token(exec: &str) -> (String, &str) {
+    let exec = exec.trim_start();
+    let rest = exec_arguments(exec);
+    let token = exec[..exec.len() - rest.len()].trim_end();
+    let token = match token.strip_prefix('"') {
+        Some(quoted) => {
+            let mut unquoted = String::new();
+            let mut chars = quoted.strip_suffix('"').unwrap_or(quoted).chars();
+            while let Some(c) = chars.next() {
+                unquoted.push(if c == '\\' { chars.next().unwrap_or(c) } else { c });
+            }
+            unquoted
+        }
+        None => token.to_string(),
+    };
+    (token, rest)
+}
+
+fn normalize_categories(categories: &str) -> String {
+    let list: Vec<&str> = categories
+        .split(';')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .collect();
+
+    if list.is_empty() {
+        String::new()
+    } else {
+        format!("{};", list.join(";"))
+    }
+}
+
+// Icon pode ser um caminho absoluto ou um nome; nesse caso procura um PNG
+// com esse nome perto do .desktop (ex: data/icons/hicolor/256x256/apps)
+fn resolve_icon(desktop_path: &Path, icon: &str) -> Option<PathBuf> {
+    let icon_path = Path::new(icon);
+    if icon_path.is_absolute() {
+        return icon_path.is_file().then(|| icon_path.to_path_buf());
+    }
+
+    let file_name = format!("{}.png", icon);
+    let mut best: Option<(u64, PathBuf)> = None;
+
+    for root in desktop_path.ancestors().skip(1).take(2) {
+        find_largest_file(root, &file_name, ICON_SEARCH_DEPTH, &mut best);
+        if best.is_some() {
+            break;
+        }
+    }
+
+    best.map(|(_, path)| path)
+}
+
+fn find_largest_file(dir: &Path, file_name: &str, depth: usize, best: &mut Option<(u64, PathBuf)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        if file_type.is_dir() {
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if depth > 0 && !hidden {
+                find_largest_file(&path, file_name, depth - 1, best);
+            }
+        } else if entry.file_name().to_string_lossy() == file_name {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            match best {
+                Some((best_size, _)) if *best_size >= size => {}
+                _ => *best = Some((size, path)),
+            }
+        }
+    }
+}
+
+struct XmlElement<'a> {
+    name: &'a str,
+    attrs: &'a str,
+    raw: &'a str,
+    inner: &'a str,
+}
+
+fn component_children(xml: &str) -> Option<Vec<XmlElement<'_>>> {
+    let component = child_elements(xml)
+        .into_iter()
+        .find(|element| element.name == "component")?;
+    Some(child_elements(component.inner))
+}
+
+// Lista os elementos de primeiro nível de um trecho XML (sem validar o documento)
+fn child_elements(xml: &str) -> Vec<XmlElement<'_>> {
+    let mut elements = Vec::new();
+    let mut position = 0;
+
+    while let Some(offset) = xml[position..].find('<') {
+        let start = position + offset;
+        let rest = &xml[start..];
+
+        if let Some(skip) = skip_markup(rest) {
+            position = start + skip;
+            continue;
+        }
+        if rest.starts_with("</") {
+            break;
+        }
+
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..tag_end];
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name_end = tag
+            .find(|c: char| c.is_whitespace())
+            .unwrap_or(tag.len());
+        let name = &tag[..name_end];
+        let attrs = tag[name_end..].trim();
+
+        if self_closing {
+            elements.push(XmlElement {
+                name,
+                attrs,
+                raw: &rest[..=tag_end],
+                inner: "",
+            });
+            position = start + tag_end + 1;
+            continue;
+        }
+
+        let content_start = tag_end + 1;
+        let Some((inner_end, element_end)) = find_closing_tag(&rest[content_start..]) else {
+            break;
+        };
+
+        elements.push(XmlElement {
+            name,
+            attrs,
+            raw: &rest[..content_start + element_end],
+            inner: &rest[content_start..content_start + inner_end],
+        });
+        position = start + content_start + element_end;
+    }
+
+    elements
+}
+
+// Comentários, declarações e instruções de processamento são ignorados
+fn skip_markup(rest: &str) -> Option<usize> {
+    let terminator = if rest.starts_with("<!--") {
+        "-->"
+    } else if rest.starts_with("<?") {
+        "?>"
+    } else if rest.starts_with("<!") {
+        ">"
+    } else {
+        return None;
+    };
+
+    Some(rest.find(terminator).map(|i| i + terminator.len()).unwrap_or(rest.len()))
+}
+
+// Retorna (fim do conteúdo, fim do elemento) relativos ao início do conteúdo
+fn find_closing_tag(content: &str) -> Option<(usize, usize)> {
+    let mut depth = 1usize;
+    let mut position = 0;
+
+    while let Some(offset) = content[position..].find('<') {
+        let start = position + offset;
+        let rest = &content[start..];
+
+        if let Some(skip) = skip_markup(rest) {
+            position = start + skip;
+            continue;
+        }
+
+        let tag_end = rest.find('>')?;
+        if rest.starts_with("</") {
+            depth -= 1;
+            if depth == 0 {
+                return Some((start, start + tag_end + 1));
+            }
+        } else if !rest[..tag_end].ends_with('/') {
+            depth += 1;
+        }
+        position = start + tag_end + 1;
+    }
+
+    None
+}
+
+fn attribute<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let mut search_from = 0;
+
+    while let Some(offset) = attrs[search_from..].find(name) {
+        let position = search_from + offset;
+        let before_ok = position == 0 || attrs[..position].ends_with(char::is_whitespace);
+        let rest = attrs[position + name.len()..].trim_start();
+
+        if before_ok {
+            if let Some(value) = rest.strip_prefix('=') {
+                let value = value.trim_start();
+                let quote = value.chars().next()?;
+                if quote == '"' || quote == '\'' {
+                    let value = &value[1..];
+                    return value.find(quote).map(|end| &value[..end]);
+                }
+            }
+        }
+
+        search_from = position + name.len();
+    }
+
+    None
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn desktop_entry_ignores_lines_before_main_group() {
+        let content = "# comentário\nStray=1\n\n[Desktop Entry]\nType=Application\n# nota\nName=App\n\n[Desktop Action new]\nName=New\nExec=app --new\n";
+        let entry = parse_desktop_entry(content).unwrap();
+        assert_eq!(
+            entry.keys,
+            vec![("Type".to_string(), "Application".to_string()), ("Name".to_string(), "App".to_string())]
+        );
+        assert_eq!(entry.extra_groups, "[Desktop Action new]\nName=New\nExec=app --new\n");
+    }
+
+    #[test]
+    fn desktop_entry_requires_main_group() {
+        assert!(parse_desktop_entry("[Desktop Action new]\nName=New\n").is_none());
+        assert!(parse_desktop_entry("").is_none());
+    }
+
+    #[test]
+    fn exec_command_name_skips_env_and_field_codes() {
+        assert_eq!(exec_command_name("app %U").as_deref(), Some("app"));
+        assert_eq!(exec_command_name("\"/opt/My App/my-app\" %f").as_deref(), Some("my-app"));
+        assert_eq!(exec_command_name("/usr/bin/app --flag").as_deref(), Some("app"));
+        assert_eq!(exec_command_name("env GDK_BACKEND=x11 LANG=C app --x").as_deref(), Some("app"));
+        assert_eq!(exec_command_name(""), None);
+    }
+
+    #[test]
+    fn categories_are_normalized() {
+        assert_eq!(normalize_categories("Graphics; Viewer"), "Graphics;Viewer;");
+        assert_eq!(normalize_categories("Utility;;"), "Utility;");
+        assert_eq!(normalize_categories(" ; "), "");
+    }
+
+    #[test]
+    fn validate_desktop_entry_reports_problems() {
+        let valid = "[Desktop Entry]\nType=Application\nName=App\nExec=app %U\nIcon=app\nCategories=Utility;\n";
+        assert_eq!(validate_desktop_entry(valid, "app"), Ok("app".to_string()));
+
+        let invalid = "[Desktop Entry]\nType=Link\nExec=other\nIcon=/usr/share/icons/app.png\n";
+        let problems = validate_desktop_entry(invalid, "app").unwrap_err();
+        assert_eq!(problems.len(), 5);
+    }
+}
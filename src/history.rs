@@ -0,0 +1,221 @@
+use std::time::{Duration, Instant};
+
+// Alterações mais próximas que isso (digitação em um campo) viram um só passo
+const MERGE_INTERVAL: Duration = Duration::from_millis(800);
+
+// Passos guardados para desfazer
+const MAX_UNDO_STEPS: usize = 200;
+
+// Histórico de estados do formulário para desfazer/refazer, com o estado salvo
+// para saber se há alterações pendentes
+#[derive(Debug)]
+pub(crate) struct History<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    current: T,
+    saved: T,
+    last_change: Option<Instant>,
+    // Enquanto um estado é restaurado, as alterações intermediárias não são registradas
+    restoring: bool,
+}
+
+impl<T: Clone + PartialEq> History<T> {
+    pub(crate) fn new(state: T) -> Self {
+        History {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            current: state.clone(),
+            saved: state,
+            last_change: None,
+            restoring: false,
+        }
+    }
+
+    // Novo ponto de partida (receita aberta ou formulário inicial), sem histórico
+    pub(crate) fn reset(&mut self, state: T) {
+        *self = History::new(state);
+    }
+
+    // Registra o estado atual; iguais ao anterior são ignorados
+    pub(crate) fn record(&mut self, state: T) {
+        if self.restoring || state == self.current {
+            return;
+        }
+        let now = Instant::now();
+        let merge = self
+            .last_change
+            .is_some_and(|last| now.duration_since(last) < MERGE_INTERVAL)
+            && !self.undo.is_empty()
+            && self.redo.is_empty();
+        if !merge {
+            let previous = std::mem::replace(&mut self.current, state);
+            self.undo.push(previous);
+            if self.undo.len() > MAX_UNDO_STEPS {
+                self.undo.remove(0);
+            }
+        } else {
+            self.current = state;
+        }
+        self.redo.clear();
+        self.last_change = Some(now);
+    }
+
+    // Estado a restaurar; o chamador aplica e encerra com `finish_restore`
+    pub(crate) fn undo(&mut self) -> Option<T> {
+        let state = self.undo.pop()?;
+        self.redo.push(std::mem::replace(&mut self.current, state.clone()));
+        self.begin_restore();
+        Some(state)
+    }
+
+    pub(crate) fn redo(&mut self) -> Option<T> {
+        let state = self.redo.pop()?;
+        self.undo.push(std::mem::replace(&mut self.current, state.clone()));
+        self.begin_restore();
+        Some(state)
+    }
+
+    fn begin_restore(&mut self) {
+        self.restoring = true;
+        self.last_change = None;
+    }
+
+    // Estado efetivo após restaurar (campos derivados podem diferir do guardado)
+    pub(crate) fn finish_restore(&mut self, state: T) {
+        self.restoring = false;
+        self.current = state;
+    }
+
+    pub(crate) fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub(crate) fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub(crate) fn mark_saved(&mut self) {
+        self.saved = self.current.clone();
+    }
+
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.current != self.saved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Registra como uma alteração separada, fora da janela de junção
+    fn record_step(history: &mut History<u32>, state: u32) {
+        history.last_change = None;
+        history.record(state);
+    }
+
+    #[test]
+    fn undo_and_redo_walk_the_steps() {
+        let mut history = History::new(0);
+        record_step(&mut history, 1);
+        record_step(&mut history, 2);
+
+        assert_eq!(history.undo(), Some(1));
+        history.finish_restore(1);
+        assert_eq!(history.undo(), Some(0));
+        history.finish_restore(0);
+        assert!(!history.can_undo());
+        assert_eq!(history.undo(), None);
+
+        assert_eq!(history.redo(), Some(1));
+        history.finish_restore(1);
+        assert_eq!(history.redo(), Some(2));
+        history.finish_restore(2);
+        assert!(!history.can_redo());
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    fn quick_changes_merge_into_one_step() {
+        let mut history = History::new(0);
+        history.record(1);
+        history.record(2);
+        history.record(3);
+
+        assert_eq!(history.undo(), Some(0));
+        history.finish_restore(0);
+        assert!(!history.can_undo());
+
+        // Fora da janela, cada alteração é um passo
+        let mut history = History::new(0);
+        history.record(1);
+        history.last_change = Some(Instant::now() - MERGE_INTERVAL);
+        history.record(2);
+        assert_eq!(history.undo(), Some(1));
+    }
+
+    #[test]
+    fn restoring_ignores_intermediate_changes() {
+        let mut history = History::new(0);
+        record_step(&mut history, 1);
+        record_step(&mut history, 2);
+
+        assert_eq!(history.undo(), Some(1));
+        // Os campos mudando um a um durante a restauração não viram passos
+        history.record(5);
+        history.record(6);
+        history.finish_restore(1);
+
+        assert!(history.can_redo());
+        assert_eq!(history.undo(), Some(0));
+    }
+
+    #[test]
+    fn record_clears_redo() {
+        let mut history = History::new(0);
+        record_step(&mut history, 1);
+        assert_eq!(history.undo(), Some(0));
+        history.finish_restore(0);
+        assert!(history.can_redo());
+
+        history.record(7);
+        assert!(!history.can_redo());
+        assert_eq!(history.undo(), Some(0));
+    }
+
+    #[test]
+    fn undo_steps_are_capped() {
+        let mut history = History::new(0);
+        for state in 1..=MAX_UNDO_STEPS as u32 + 10 {
+            record_step(&mut history, state);
+        }
+
+        let mut oldest = None;
+        let mut steps = 0;
+        while let Some(state) = history.undo() {
+            history.finish_restore(state);
+            oldest = Some(state);
+            steps += 1;
+        }
+        assert_eq!(steps, MAX_UNDO_STEPS);
+        // Os passos mais antigos são os descartados
+        assert_eq!(oldest, Some(10));
+    }
+
+    #[test]
+    fn dirty_until_saved() {
+        let mut history = History::new(0);
+        assert!(!history.is_dirty());
+
+        record_step(&mut history, 1);
+        assert!(history.is_dirty());
+        history.mark_saved();
+        assert!(!history.is_dirty());
+
+        assert_eq!(history.undo(), Some(0));
+        history.finish_restore(0);
+        assert!(history.is_dirty());
+        assert_eq!(history.redo(), Some(1));
+        history.finish_restore(1);
+        assert!(!history.is_dirty());
+    }
+}
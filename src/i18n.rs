@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+// Catálogos no formato .po do gettext, embutidos no binário para funcionar sem
+// instalação (inclusive de dentro de um AppImage). O inglês do código é o idioma
+// de origem; po/appimage-creator.pot é o modelo para novas traduções
+const CATALOGS: [(&str, &str); 1] = [("pt_BR", include_str!("../po/pt_BR.po"))];
+
+static CATALOG: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+// Mensagem traduzida para o idioma do ambiente; sem tradução, a própria mensagem
+pub(crate) fn gettext(msgid: &'static str) -> &'static str {
+    CATALOG
+        .get_or_init(load_catalog)
+        .get(msgid)
+        .map(String::as_str)
+        .unwrap_or(msgid)
+}
+
+// Como `gettext`, substituindo os marcadores {nome} pelos valores informados.
+// Marcadores com nome permitem que a tradução mude a ordem dos valores
+pub(crate) fn gettext_f(msgid: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = String::new();
+    let mut rest = gettext(msgid);
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after
+            .find('}')
+            .and_then(|end| args.iter().find(|(name, _)| *name == &after[..end]).map(|arg| (end, arg.1)));
+        match value {
+            Some((end, value)) => {
+                text.push_str(&value.to_string());
+                rest = &after[end + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = after;
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+// Marca para extração mensagens usadas em constantes; a tradução é feita no uso
+pub(crate) const fn gettext_noop(msgid: &'static str) -> &'static str {
+    msgid
+}
+
+fn load_catalog() -> HashMap<String, String> {
+    for language in requested_languages() {
+        let language = language.split(['.', '@']).next().unwrap_or_default();
+        // Inglês é o idioma de origem: não procura nas demais opções
+        if language == "en" || language.starts_with("en_") {
+            break;
+        }
+        let base = language.split('_').next().unwrap_or_default();
+        let catalog = CATALOGS
+            .iter()
+            .find(|(name, _)| *name == language)
+            .or_else(|| CATALOGS.iter().find(|(name, _)| name.split('_').next() == Some(base)));
+        if let Some((_, content)) = catalog {
+            return parse_po(content);
+        }
+    }
+    HashMap::new()
+}
+
+// Idiomas na ordem do gettext: LANGUAGE (lista separada por ":") e depois o
+// primeiro definido entre LC_ALL, LC_MESSAGES e LANG; "C" e "POSIX" mantêm o inglês
+fn requested_languages() -> Vec<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty());
+    let Some(locale) = locale else {
+        return Vec::new();
+    };
+    if locale == "C" || locale == "POSIX" || locale.starts_with("C.") {
+        return Vec::new();
+    }
+    let mut languages: Vec<String> = env::var("LANGUAGE")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|language| !language.is_empty())
+        .map(str::to_string)
+        .collect();
+    languages.push(locale);
+    languages
+}
+
+#[derive(Default)]
+struct PoEntry {
+    msgid: String,
+    msgstr: String,
+    fuzzy: bool,
+    in_msgstr: bool,
+}
+
+// Leitura de um .po: entradas msgid/msgstr com continuações em várias linhas.
+// Entradas "fuzzy" ou sem tradução são ignoradas, como faz o msgfmt
+fn parse_po(content: &str) -> HashMap<String, String> {
+    let mut catalog = HashMap::new();
+    let mut entry = PoEntry::default();
+    let mut flush = |entry: &mut PoEntry| {
+        let entry = std::mem::take(entry);
+        if !entry.msgid.is_empty() && !entry.msgstr.is_empty() && !entry.fuzzy {
+            catalog.insert(entry.msgid, entry.msgstr);
+        }
+    };
+
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() {
+            flush(&mut entry);
+        } else if line.starts_with('#') {
+            if entry.in_msgstr {
+                flush(&mut entry);
+            }
+            if line.starts_with("#,") && line.contains("fuzzy") {
+                entry.fuzzy = true;
+            }
+        } else if let Some(rest) = line.strip_prefix("msgid ") {
+            if entry.in_msgstr {
+                flush(&mut entry);
+            }
+            entry.msgid = unquote(rest);
+        } else if let Some(rest) = line.strip_prefix("msgstr ") {
+            entry.msgstr = unquote(rest);
+            entry.in_msgstr = true;
+        } else if line.starts_with('"') {
+            let target = if entry.in_msgstr { &mut entry.msgstr } else { &mut entry.msgid };
+            target.push_str(&unquote(line));
+        }
+    }
+    flush(&mut entry);
+    catalog
+}
+
+fn unquote(quoted: &str) -> String {
+    let inner = quoted
+        .trim()
+        .strip_prefix('"')
+        .and_then(|quoted| quoted.strip_suffix('"'))
+        .unwrap_or_default();
+    let mut text = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => text.push('\n'),
+            Some('t') => text.push('\t'),
+            Some('r') => text.push('\r'),
+            Some(other) => text.push(other),
+            None => {}
+        }
+    }
+    text
+}
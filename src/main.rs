@@ -1,28 +1,56 @@
 mod appimage;
+mod cli;
+mod freedesktop;
+mod history;
+mod i18n;
+mod profile;
+mod project;
+mod recipe;
+mod settings;
+#[cfg(test)]
+mod test_util;
 
 use gtk4::prelude::*;
 use gtk4::{
     Application, Box, Button, Entry, FileChooserAction, FileChooserDialog,
     Label, Orientation, ResponseType, ScrolledWindow, Align, ProgressBar,
-    CheckButton, CssProvider, Image, Separator,
+    CheckButton, CssProvider, Image, Separator, FileFilter, Switch, ListBox, StringList, SpinButton,
+    TextTag, TextView, WrapMode, MenuButton, Popover, DropTarget, Widget,
 };
-use gtk4::glib::{self, ControlFlow, SourceId};
-use gtk4::gdk::Display;
+use gtk4::glib;
+use gtk4::gdk::{Display, DragAction, FileList};
 use gtk4::gio;
 use libadwaita as adw;
 use libadwaita::prelude::*;
-use adw::{ApplicationWindow, HeaderBar, PreferencesGroup, ActionRow, Clamp, Toast, ToastOverlay, ExpanderRow};
+use adw::{ApplicationWindow, HeaderBar, PreferencesGroup, ActionRow, Clamp, ComboRow, Toast, ToastOverlay, ExpanderRow, WindowTitle};
 use std::cell::{RefCell, Cell};
 use std::rc::Rc;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::collections::{HashMap, VecDeque};
 use async_channel::unbounded;
 use std::fs;
 use url::Url;
+use serde::{Deserialize, Serialize};
+
+use appimage::{
+    AppImageInfo, BackendKind, BuildError, BuildEvent, BuildOptions, BuildStage, CancelFlag, Compression, CompressionAlgorithm, CompressionSample,
+    ExistingOutput, InputField, IntegrationDirs, LogLevel, LogRecord, OutputMode, PackageFormat, PreflightReport, SizeReport, TestRun,
+    BLOCK_SIZES, DEFAULT_BLOCK_SIZE, DEFAULT_FILE_NAME_TEMPLATE, DEFAULT_TEST_TIMEOUT, FILE_NAME_VARIABLES,
+};
+use appimage::format_size;
+use history::History;
+use i18n::{gettext, gettext_f, gettext_noop};
+use profile::{Profile, ProfileField};
+use project::ProjectManifest;
+use recipe::Recipe;
+use settings::{FormDefaults, Settings};
 
 const APP_ID: &str = "com.github.appimage-creator";
 
-#[derive(Debug, Clone, Default)]
+const APP_TITLE: &str = "AppImage Creator";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct AppImageMetadata {
     binary_path: String,
     icon_path: String,
@@ -34,16 +62,466 @@ pub(crate) struct AppImageMetadata {
     author: String,
     license: String,
     website: String,
+    // Preenchidos ao importar um .desktop/metainfo existente
+    app_id: String,
+    desktop_file_path: String,
+    metainfo_file_path: String,
+    desktop_extra: Vec<(String, String)>,
+    desktop_extra_groups: String,
+    metainfo_extra: Vec<String>,
+    use_imported_files: bool,
+    // Prefixo do App ID gerado quando não há um importado (ex: org.exemplo)
+    app_id_prefix: String,
+}
+
+// Atalhos de teclado das ações da janela e do aplicativo
+const ACCELERATORS: [(&str, &[&str]); 15] = [
+    ("win.generate", &["<Control>Return", "<Control>KP_Enter"]),
+    ("win.cancel", &["<Control>Escape"]),
+    ("win.open-recipe", &["<Control>o"]),
+    ("win.save-recipe", &["<Control>s"]),
+    ("win.save-recipe-as", &["<Control><Shift>s"]),
+    ("win.browse-binary", &["<Control>b"]),
+    ("win.browse-icon", &["<Control>i"]),
+    ("win.browse-output", &["<Control><Shift>o"]),
+    ("win.import-metadata", &["<Control><Shift>m"]),
+    ("win.inspect-appimage", &["<Control><Shift>a"]),
+    ("win.check-environment", &["<Control><Shift>e"]),
+    ("win.undo", &["<Control>z"]),
+    ("win.redo", &["<Control><Shift>z", "<Control>y"]),
+    ("win.show-help-overlay", &["<Control>question"]),
+    ("app.quit", &["<Control>q"]),
+];
+
+// Grupos da janela de atalhos; as teclas vêm de ACCELERATORS pelo nome da ação.
+// Os títulos são traduzidos ao montar a janela
+const SHORTCUT_GROUPS: [(&str, &[(&str, &str)]); 4] = [
+    (
+        gettext_noop("Recipe"),
+        &[
+            ("win.open-recipe", gettext_noop("Open recipe")),
+            ("win.save-recipe", gettext_noop("Save recipe")),
+            ("win.save-recipe-as", gettext_noop("Save recipe as")),
+            ("win.undo", gettext_noop("Undo")),
+            ("win.redo", gettext_noop("Redo")),
+        ],
+    ),
+    (
+        gettext_noop("Files"),
+        &[
+            ("win.browse-binary", gettext_noop("Select binary")),
+            ("win.browse-icon", gettext_noop("Select icon")),
+            ("win.browse-output", gettext_noop("Select output folder")),
+            ("win.import-metadata", gettext_noop("Import .desktop/metainfo")),
+        ],
+    ),
+    (
+        gettext_noop("Build"),
+        &[
+            ("win.generate", gettext_noop("Generate AppImage")),
+            ("win.cancel", gettext_noop("Cancel builds")),
+            ("win.inspect-appimage", gettext_noop("Inspect AppImage")),
+            ("win.check-environment", gettext_noop("Check environment")),
+        ],
+    ),
+    (
+        gettext_noop("General"),
+        &[
+            ("win.show-help-overlay", gettext_noop("Keyboard shortcuts")),
+            ("app.quit", gettext_noop("Quit")),
+        ],
+    ),
+];
+
+// Máximo de gerações executando ao mesmo tempo; as demais aguardam na fila
+const MAX_CONCURRENT_BUILDS: usize = 2;
+
+// Mensagens enviadas pela thread de geração para a interface
+enum BuildMessage {
+    Event(BuildEvent),
+    Finished(Result<PathBuf, BuildError>),
+}
+
+// Mostra um erro da geração, com o log da geração quando houver
+type ShowBuildError = Rc<dyn Fn(&BuildError, Option<&TextView>)>;
+
+// Escolhe o arquivo da receita, grava e chama o retorno informado
+type SaveRecipeAs = Rc<dyn Fn(Rc<dyn Fn()>)>;
+
+// Preenche o formulário com um arquivo solto sobre a janela ou uma linha
+type ApplyDropped = Rc<dyn Fn(&Path, DroppedKind)>;
+
+// Linha da lista de gerações com o progresso e o log de uma geração
+#[derive(Clone)]
+struct BuildJobRow {
+    row: ExpanderRow,
+    progress_bar: ProgressBar,
+    cancel_button: Button,
+    test_button: Button,
+    remove_button: Button,
+    log_view: TextView,
+}
+
+struct QueuedBuild {
+    metadata: AppImageMetadata,
+    output_path: PathBuf,
+    options: BuildOptions,
+    cancel: CancelFlag,
+    job: BuildJobRow,
+}
+
+// Fila de gerações: até MAX_CONCURRENT_BUILDS executam em paralelo, cada uma
+// em sua thread e com seu próprio canal de eventos
+struct BuildQueue {
+    pending: VecDeque<QueuedBuild>,
+    // Arquivos de saída das gerações em execução
+    running: Vec<PathBuf>,
+    list: ListBox,
+    list_container: ScrolledWindow,
+    status_label: Label,
+    toast_overlay: ToastOverlay,
+    show_build_error: ShowBuildError,
+    // Botões "Cancelar" das gerações na lista, acionados por win.cancel
+    cancel_buttons: Vec<Button>,
+    cancel_action: gio::SimpleAction,
+}
+
+impl BuildQueue {
+    fn is_building(&self, output_path: &Path) -> bool {
+        self.running.iter().any(|path| path == output_path)
+            || self.pending.iter().any(|build| build.output_path == output_path)
+    }
+
+    fn update_status(&self) {
+        let status = match (self.running.len(), self.pending.len()) {
+            (0, 0) => gettext("Package the application in a portable format").to_string(),
+            (running, 0) => gettext_f("{running} running", &[("running", &running)]),
+            (running, pending) => gettext_f(
+                "{running} running, {pending} queued",
+                &[("running", &running), ("pending", &pending)],
+            ),
+        };
+        self.status_label.set_text(&status);
+        self.list_container.set_visible(self.list.first_child().is_some());
+        self.cancel_action
+            .set_enabled(!self.running.is_empty() || !self.pending.is_empty());
+    }
 }
 
 #[derive(Debug, Clone, Default)]
 struct AppState {
     metadata: AppImageMetadata,
     output_folder: Option<PathBuf>,
+    options: BuildOptions,
+}
+
+// Amostras de compressão do binário (caminho, tamanho, compressão), calculadas em
+// segundo plano para o preview
+type SampleKey = (PathBuf, u64, Compression);
+
+#[derive(Debug, Clone, Copy)]
+enum SampleStatus {
+    Running,
+    Done(CompressionSample),
+    Failed,
+}
+
+// Controles de compressão da imagem. O primeiro item de cada lista é o padrão
+// da ferramenta, que corresponde a None
+#[derive(Clone)]
+struct CompressionWidgets {
+    row: ExpanderRow,
+    algorithm_row: ComboRow,
+    level_row: ActionRow,
+    level_spin: SpinButton,
+    block_size_row: ComboRow,
+}
+
+impl CompressionWidgets {
+    fn read(&self) -> Option<Compression> {
+        let index = (self.algorithm_row.selected() as usize).checked_sub(1)?;
+        let algorithm = *CompressionAlgorithm::ALL.get(index)?;
+        let block_size = (self.block_size_row.selected() as usize)
+            .checked_sub(1)
+            .and_then(|index| BLOCK_SIZES.get(index).copied());
+        Some(Compression {
+            algorithm,
+            level: algorithm.level_range().map(|_| self.level_spin.value() as u32),
+            block_size,
+        })
+    }
+
+    fn set(&self, compression: Option<&Compression>) {
+        let algorithm_index = compression
+            .and_then(|c| CompressionAlgorithm::ALL.iter().position(|a| *a == c.algorithm))
+            .map_or(0, |index| index + 1);
+        self.algorithm_row.set_selected(algorithm_index as u32);
+        if let Some(level) = compression.and_then(|c| c.level) {
+            self.level_spin.set_value(level as f64);
+        }
+        let block_index = compression
+            .and_then(|c| c.block_size)
+            .and_then(|size| BLOCK_SIZES.iter().position(|s| *s == size))
+            .map_or(0, |index| index + 1);
+        self.block_size_row.set_selected(block_index as u32);
+    }
+
+    // Ajusta a faixa de níveis ao algoritmo escolhido, voltando ao nível padrão
+    fn reset_level(&self) {
+        let range = (self.algorithm_row.selected() as usize)
+            .checked_sub(1)
+            .and_then(|index| CompressionAlgorithm::ALL.get(index))
+            .and_then(|algorithm| algorithm.level_range());
+        match range {
+            Some((min, max, default)) => {
+                self.level_spin.set_range(min as f64, max as f64);
+                self.level_spin.set_value(default as f64);
+                self.level_row.set_sensitive(true);
+            }
+            None => self.level_row.set_sensitive(false),
+        }
+        self.block_size_row.set_sensitive(self.algorithm_row.selected() > 0);
+    }
+
+    fn update_subtitle(&self, compression: Option<&Compression>) {
+        match compression {
+            Some(compression) => self.row.set_subtitle(&compression.describe()),
+            None => self.row.set_subtitle(gettext("Backend default")),
+        }
+    }
+}
+
+// Campos do formulário preenchidos a partir de fontes externas (manifestos etc.)
+#[derive(Clone)]
+struct FormWidgets {
+    state: Rc<RefCell<AppState>>,
+    update_ui: Rc<dyn Fn()>,
+    binary_entry: Entry,
+    icon_entry: Entry,
+    name_entry: Entry,
+    exec_entry: Entry,
+    version_entry: Entry,
+    comment_entry: Entry,
+    author_entry: Entry,
+    website_entry: Entry,
+    license_entry: Entry,
+    license_checks: Rc<Vec<(String, CheckButton)>>,
+    category_checks: Rc<Vec<(&'static str, CheckButton)>>,
+    imported_files_row: ActionRow,
+    use_imported_row: ActionRow,
+    use_imported_switch: Switch,
+    output_entry: Entry,
+    file_name_entry: Entry,
+    output_mode_row: ComboRow,
+    backend_row: ComboRow,
+    if_exists_row: ComboRow,
+    reproducible_switch: Switch,
+    work_root_entry: Entry,
+    compression: CompressionWidgets,
+    format_checks: Rc<Vec<(PackageFormat, CheckButton)>>,
+}
+
+impl FormWidgets {
+    // Substitui todo o formulário pelo conteúdo da receita
+    fn apply_recipe(&self, recipe: &Recipe) {
+        let app = &recipe.app;
+        // Binário antes do comando, que é preenchido a partir dele
+        let fields = [
+            (&self.binary_entry, &app.binary_path),
+            (&self.icon_entry, &app.icon_path),
+            (&self.name_entry, &app.name),
+            (&self.exec_entry, &app.exec),
+            (&self.version_entry, &app.version),
+            (&self.comment_entry, &app.comment),
+            (&self.author_entry, &app.author),
+            (&self.website_entry, &app.website),
+        ];
+        for (entry, value) in fields {
+            entry.set_text(value);
+        }
+        if app.license.is_empty() {
+            self.license_entry.set_text("");
+        } else {
+            self.select_license(&app.license);
+        }
+        self.select_categories(&app.categories);
+
+        let build = &recipe.build;
+        let mode_index = OutputMode::ALL.iter().position(|mode| *mode == build.output_mode).unwrap_or(0);
+        self.output_mode_row.set_selected(mode_index as u32);
+        let backend_index = BackendKind::ALL.iter().position(|backend| *backend == build.backend).unwrap_or(0);
+        self.backend_row.set_selected(backend_index as u32);
+        self.file_name_entry.set_text(build.file_name.as_deref().unwrap_or_default());
+        let if_exists_index = ExistingOutput::ALL.iter().position(|policy| *policy == build.if_exists).unwrap_or(0);
+        self.if_exists_row.set_selected(if_exists_index as u32);
+        self.reproducible_switch.set_active(build.reproducible);
+        let work_root = build.work_root.as_ref().map(|p| p.to_string_lossy().to_string());
+        self.work_root_entry.set_text(&work_root.unwrap_or_default());
+        self.compression.set(build.compression.as_ref());
+        for (format, check) in self.format_checks.iter() {
+            check.set_active(build.extra_formats.contains(format));
+        }
+        self.use_imported_switch.set_active(app.use_imported_files);
+
+        let output_folder = recipe.output_folder.as_ref().map(|p| p.to_string_lossy().to_string());
+        self.output_entry.set_text(&output_folder.unwrap_or_default());
+
+        // Campos sem controle próprio, e a compressão exatamente como na receita
+        {
+            let mut state = self.state.borrow_mut();
+            state.output_folder = recipe.output_folder.clone();
+            state.options.compression = build.compression;
+            let metadata = &mut state.metadata;
+            metadata.app_id = app.app_id.clone();
+            metadata.app_id_prefix = app.app_id_prefix.clone();
+            metadata.desktop_file_path = app.desktop_file_path.clone();
+            metadata.metainfo_file_path = app.metainfo_file_path.clone();
+            metadata.desktop_extra = app.desktop_extra.clone();
+            metadata.desktop_extra_groups = app.desktop_extra_groups.clone();
+            metadata.metainfo_extra = app.metainfo_extra.clone();
+        }
+
+        self.show_imported_files();
+        self.update_ui.as_ref()();
+    }
+
+    fn to_recipe(&self) -> Recipe {
+        let state = self.state.borrow();
+        Recipe {
+            output_folder: state.output_folder.clone(),
+            app: state.metadata.clone(),
+            build: state.options.clone(),
+        }
+    }
+
+    // Nomes dos .desktop/metainfo importados na linha correspondente
+    fn show_imported_files(&self) {
+        let state = self.state.borrow();
+        let imported_names: Vec<String> = [
+            &state.metadata.desktop_file_path,
+            &state.metadata.metainfo_file_path,
+        ]
+        .iter()
+        .filter(|p| !p.is_empty())
+        .filter_map(|p| Path::new(p.as_str()).file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .collect();
+
+        if imported_names.is_empty() {
+            self.imported_files_row.set_subtitle(gettext("Project .desktop and metainfo (optional)"));
+            self.use_imported_row.set_sensitive(false);
+        } else {
+            self.imported_files_row.set_subtitle(&imported_names.join(", "));
+            self.use_imported_row.set_sensitive(true);
+        }
+    }
+
+    fn apply_project_manifest(&self, manifest: &ProjectManifest) {
+        self.name_entry.set_text(&manifest.name);
+
+        if let Some(version) = &manifest.version {
+            self.version_entry.set_text(version);
+        }
+        if let Some(description) = &manifest.description {
+            self.comment_entry.set_text(description);
+        }
+        if !manifest.authors.is_empty() {
+            self.author_entry.set_text(&manifest.authors.join(", "));
+        }
+        if let Some(license) = &manifest.license {
+            self.select_license(license);
+        }
+        if let Some(homepage) = &manifest.homepage {
+            self.website_entry.set_text(homepage);
+        }
+    }
+
+    // Campos vazios no metadado importado mantêm o valor atual do formulário
+    fn apply_imported_metadata(&self, imported: &AppImageMetadata) {
+        let fields = [
+            (&self.binary_entry, &imported.binary_path),
+            (&self.name_entry, &imported.name),
+            (&self.exec_entry, &imported.exec),
+            (&self.icon_entry, &imported.icon_path),
+            (&self.comment_entry, &imported.comment),
+            (&self.author_entry, &imported.author),
+            (&self.website_entry, &imported.website),
+        ];
+        for (entry, value) in fields {
+            if !value.is_empty() {
+                entry.set_text(value);
+            }
+        }
+
+        if !imported.license.is_empty() {
+            self.select_license(&imported.license);
+        }
+        if !imported.categories.is_empty() {
+            self.select_categories(&imported.categories);
+        }
+
+        {
+            let mut state = self.state.borrow_mut();
+            let metadata = &mut state.metadata;
+            if !imported.desktop_file_path.is_empty() {
+                metadata.desktop_file_path = imported.desktop_file_path.clone();
+                metadata.desktop_extra = imported.desktop_extra.clone();
+                metadata.desktop_extra_groups = imported.desktop_extra_groups.clone();
+            }
+            if !imported.metainfo_file_path.is_empty() {
+                metadata.metainfo_file_path = imported.metainfo_file_path.clone();
+                metadata.metainfo_extra = imported.metainfo_extra.clone();
+            }
+            if !imported.app_id.is_empty() {
+                metadata.app_id = imported.app_id.clone();
+            }
+        }
+
+        self.update_ui.as_ref()();
+    }
+
+    // Padrões lembrados só ocupam campos ainda vazios
+    fn apply_defaults(&self, defaults: &FormDefaults) {
+        for (entry, value) in [(&self.author_entry, &defaults.author), (&self.website_entry, &defaults.website)] {
+            if entry.text().is_empty() && !value.is_empty() {
+                entry.set_text(value);
+            }
+        }
+        if self.state.borrow().metadata.license.is_empty() && !defaults.license.is_empty() {
+            self.select_license(&defaults.license);
+        }
+    }
+
+    fn select_categories(&self, categories: &str) {
+        // Categorias fora da lista de checkboxes são mantidas na string do estado
+        for (_, check) in self.category_checks.iter() {
+            check.set_active(false);
+        }
+        self.state.borrow_mut().metadata.categories = categories.to_string();
+        for (value, check) in self.category_checks.iter() {
+            if categories.split(';').any(|c| c == *value) {
+                check.set_active(true);
+            }
+        }
+    }
+
+    fn select_license(&self, license: &str) {
+        // Licenças conhecidas marcam o checkbox; as demais vão para o campo livre
+        match self.license_checks.iter().find(|(value, _)| value == license) {
+            Some((_, check)) => check.set_active(true),
+            None => self.license_entry.set_text(license),
+        }
+    }
 }
 
 fn main() {
-    adw::init().expect("Falha ao inicializar libadwaita");
+    // Subcomandos rodam sem interface gráfica
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
+    adw::init().expect("failed to initialize libadwaita");
 
     let app = Application::builder()
         .application_id(APP_ID)
@@ -55,16 +533,32 @@ fn main() {
 
 fn build_ui(app: &Application) {
     let app_state = Rc::new(RefCell::new(AppState::default()));
+    let settings = Rc::new(RefCell::new(Settings::load()));
 
     // Carregar estilos CSS customizados
     load_css();
 
     let window = ApplicationWindow::builder()
         .application(app)
-        .default_width(700)
-        .default_height(1000)
+        .default_width(settings.borrow().window.width.unwrap_or(700))
+        .default_height(settings.borrow().window.height.unwrap_or(1000))
+        .maximized(settings.borrow().window.maximized)
         .build();
 
+    // Tamanho da janela lembrado ao fechar
+    {
+        let settings_clone = settings.clone();
+        window.connect_close_request(move |window| {
+            let (width, height) = window.default_size();
+            let mut settings = settings_clone.borrow_mut();
+            settings.window.width = Some(width).filter(|width| *width > 0);
+            settings.window.height = Some(height).filter(|height| *height > 0);
+            settings.window.maximized = window.is_maximized();
+            settings.save_quietly();
+            glib::Propagation::Proceed
+        });
+    }
+
     let css_provider = CssProvider::new();
     css_provider.load_from_data(
         "
@@ -103,6 +597,12 @@ fn build_ui(app: &Application) {
         progressbar.compact-progress progress {
             border-radius: 12px;
         }
+        .drop-hover {
+            outline: 2px dashed @accent_color;
+            outline-offset: -4px;
+            background-color: alpha(@accent_color, 0.08);
+            border-radius: 6px;
+        }
         "
     );
 
@@ -120,10 +620,93 @@ fn build_ui(app: &Application) {
 
     // Criar HeaderBar
     let header_bar = HeaderBar::new();
-    header_bar.set_title_widget(Some(&Label::new(Some("AppImage Creator"))));
+    let window_title = WindowTitle::new(APP_TITLE, "");
+    header_bar.set_title_widget(Some(&window_title));
     header_bar.set_show_end_title_buttons(true);
     header_bar.set_show_start_title_buttons(true);
 
+    // Receita do projeto: todo o formulário em um arquivo TOML
+    let recipe_path: Rc<RefCell<Option<PathBuf>>> = Rc::default();
+
+    // Ações da janela, usadas pelos botões, pelo menu principal e pelos atalhos
+    let add_action = |name: &str| {
+        let action = gio::SimpleAction::new(name, None);
+        window.add_action(&action);
+        action
+    };
+    let generate_action = add_action("generate");
+    let cancel_action = add_action("cancel");
+    let open_recipe_action = add_action("open-recipe");
+    let save_recipe_action = add_action("save-recipe");
+    let save_recipe_as_action = add_action("save-recipe-as");
+    let browse_binary_action = add_action("browse-binary");
+    let browse_icon_action = add_action("browse-icon");
+    let browse_output_action = add_action("browse-output");
+    let import_metadata_action = add_action("import-metadata");
+    let inspect_appimage_action = add_action("inspect-appimage");
+    let check_environment_action = add_action("check-environment");
+    let undo_action = add_action("undo");
+    let redo_action = add_action("redo");
+    cancel_action.set_enabled(false);
+    undo_action.set_enabled(false);
+    redo_action.set_enabled(false);
+    {
+        let app_clone = app.clone();
+        let quit_action = gio::SimpleAction::new("quit", None);
+        // Fechar as janelas passa pela confirmação de alterações não salvas
+        quit_action.connect_activate(move |_, _| {
+            for window in app_clone.windows() {
+                window.close();
+            }
+        });
+        app.add_action(&quit_action);
+    }
+    for (action, accels) in ACCELERATORS {
+        app.set_accels_for_action(action, accels);
+    }
+    window.set_help_overlay(Some(&build_shortcuts_window()));
+
+    // Desfazer/refazer sobre o estado do formulário, registrado a cada atualização da interface
+    let history = Rc::new(RefCell::new(History::new(Recipe::default())));
+    let open_recipe_button = Button::from_icon_name("document-open-symbolic");
+    open_recipe_button.set_tooltip_text(Some(gettext("Open recipe")));
+    open_recipe_button.set_action_name(Some("win.open-recipe"));
+    header_bar.pack_start(&open_recipe_button);
+    let save_recipe_button = Button::from_icon_name("document-save-symbolic");
+    save_recipe_button.set_tooltip_text(Some(gettext("Save recipe")));
+    save_recipe_button.set_action_name(Some("win.save-recipe"));
+    header_bar.pack_start(&save_recipe_button);
+    let recent_recipes_button = MenuButton::new();
+    recent_recipes_button.set_icon_name("document-open-recent-symbolic");
+    recent_recipes_button.set_tooltip_text(Some(gettext("Recent")));
+    let recent_recipes_list = Box::new(Orientation::Vertical, 0);
+    let recent_recipes_popover = Popover::new();
+    recent_recipes_popover.set_child(Some(&recent_recipes_list));
+    recent_recipes_button.set_popover(Some(&recent_recipes_popover));
+    header_bar.pack_start(&recent_recipes_button);
+
+    // Perfil ativo: valores da organização que preenchem os campos vazios
+    let active_profile: Rc<RefCell<Option<Profile>>> = Rc::default();
+    let profile_button = MenuButton::new();
+    profile_button.set_icon_name("avatar-default-symbolic");
+    profile_button.set_tooltip_text(Some(gettext("Profile: none")));
+    let profile_list = Box::new(Orientation::Vertical, 0);
+    let profile_popover = Popover::new();
+    profile_popover.set_child(Some(&profile_list));
+    profile_button.set_popover(Some(&profile_popover));
+
+    let preflight_button = Button::from_icon_name("system-run-symbolic");
+    preflight_button.set_tooltip_text(Some(gettext("Check environment")));
+    preflight_button.set_action_name(Some("win.check-environment"));
+    let primary_menu_button = MenuButton::new();
+    primary_menu_button.set_icon_name("open-menu-symbolic");
+    primary_menu_button.set_tooltip_text(Some(gettext("Main menu")));
+    primary_menu_button.set_menu_model(Some(&primary_menu()));
+    primary_menu_button.set_primary(true);
+    header_bar.pack_end(&primary_menu_button);
+    header_bar.pack_end(&preflight_button);
+    header_bar.pack_end(&profile_button);
+
     // Container principal com Clamp para largura máxima
     let clamp = Clamp::new();
     clamp.set_maximum_size(700);
@@ -146,17 +729,17 @@ fn build_ui(app: &Application) {
 
     // === GRUPO 1: Arquivos ===
     let files_group = PreferencesGroup::new();
-    files_group.set_title("Arquivos");
-    files_group.set_description(Some("Selecione o binário e ícone da aplicação"));
+    files_group.set_title(gettext("Files"));
+    files_group.set_description(Some(gettext("Select the application binary and icon")));
 
     // Binário
     let binary_row = ActionRow::new();
-    binary_row.set_title("Binário");
-    binary_row.set_subtitle("Executável da aplicação");
-    binary_row.set_tooltip_text(Some("Arquivo binário compilado da sua aplicação"));
+    binary_row.set_title(gettext("Binary"));
+    binary_row.set_subtitle(gettext("Application executable"));
+    binary_row.set_tooltip_text(Some(gettext("Compiled binary of your application")));
     add_prefix_icon_to_action_row(&binary_row, "📦");
     let binary_entry = Entry::new();
-    binary_entry.set_placeholder_text(Some("Ex: /home/usuario/Projetos/meu-app/target/release/meu-app"));
+    binary_entry.set_placeholder_text(Some(gettext("e.g. /home/user/Projects/my-app/target/release/my-app")));
     binary_entry.set_valign(Align::Center);
     binary_entry.set_hexpand(true);
     binary_entry.set_width_chars(30);
@@ -164,7 +747,7 @@ fn build_ui(app: &Application) {
     let binary_button_box = Box::new(Orientation::Horizontal, 6);
     let binary_button_icon = Label::new(Some("🔍"));
     binary_button_icon.add_css_class("dim-label");
-    let binary_button_label = Label::new(Some("Procurar"));
+    let binary_button_label = Label::new(Some(gettext("Browse")));
     binary_button_box.append(&binary_button_icon);
     binary_button_box.append(&binary_button_label);
     binary_button.set_child(Some(&binary_button_box));
@@ -178,12 +761,12 @@ fn build_ui(app: &Application) {
 
     // Ícone
     let icon_row = ActionRow::new();
-    icon_row.set_title("Ícone");
-    icon_row.set_subtitle("Imagem do ícone (PNG, JPG, etc)");
-    icon_row.set_tooltip_text(Some("Imagem exibida no lançador e no AppImage"));
+    icon_row.set_title(gettext("Icon"));
+    icon_row.set_subtitle(gettext("Icon image (PNG, JPG, etc.)"));
+    icon_row.set_tooltip_text(Some(gettext("Image shown in the launcher and in the AppImage")));
     add_prefix_icon_to_action_row(&icon_row, "🖼️");
     let icon_entry = Entry::new();
-    icon_entry.set_placeholder_text(Some("Ex: /home/usuario/Imagens/icon.png"));
+    icon_entry.set_placeholder_text(Some(gettext("e.g. /home/user/Pictures/icon.png")));
     icon_entry.set_valign(Align::Center);
     icon_entry.set_hexpand(true);
     icon_entry.set_width_chars(30);
@@ -191,7 +774,7 @@ fn build_ui(app: &Application) {
     let icon_button_box = Box::new(Orientation::Horizontal, 6);
     let icon_button_icon = Label::new(Some("🖼️"));
     icon_button_icon.add_css_class("dim-label");
-    let icon_button_label = Label::new(Some("Procurar"));
+    let icon_button_label = Label::new(Some(gettext("Browse")));
     icon_button_box.append(&icon_button_icon);
     icon_button_box.append(&icon_button_label);
     icon_button.set_child(Some(&icon_button_box));
@@ -203,20 +786,51 @@ fn build_ui(app: &Application) {
     icon_row.set_activatable_widget(Some(&icon_button));
     files_group.add(&icon_row);
 
+    // .desktop e metainfo existentes
+    let imported_files_row = ActionRow::new();
+    imported_files_row.set_title(gettext("Existing files"));
+    imported_files_row.set_subtitle(gettext("Project .desktop and metainfo (optional)"));
+    imported_files_row.set_tooltip_text(Some(gettext("Imports the metadata of an existing .desktop file or AppStream metainfo")));
+    add_prefix_icon_to_action_row(&imported_files_row, "🗂️");
+    let import_files_button = Button::new();
+    let import_files_button_box = Box::new(Orientation::Horizontal, 6);
+    let import_files_button_icon = Label::new(Some("📥"));
+    import_files_button_icon.add_css_class("dim-label");
+    let import_files_button_label = Label::new(Some(gettext("Import")));
+    import_files_button_box.append(&import_files_button_icon);
+    import_files_button_box.append(&import_files_button_label);
+    import_files_button.set_child(Some(&import_files_button_box));
+    import_files_button.set_valign(Align::Center);
+    imported_files_row.add_suffix(&import_files_button);
+    imported_files_row.set_activatable_widget(Some(&import_files_button));
+    files_group.add(&imported_files_row);
+
+    let use_imported_row = ActionRow::new();
+    use_imported_row.set_title(gettext("Use imported files"));
+    use_imported_row.set_subtitle(gettext("Copy them as they are into the AppImage instead of generating new ones"));
+    use_imported_row.set_tooltip_text(Some(gettext("The files are validated before packaging")));
+    add_prefix_icon_to_action_row(&use_imported_row, "📋");
+    let use_imported_switch = Switch::new();
+    use_imported_switch.set_valign(Align::Center);
+    use_imported_row.add_suffix(&use_imported_switch);
+    use_imported_row.set_activatable_widget(Some(&use_imported_switch));
+    use_imported_row.set_sensitive(false);
+    files_group.add(&use_imported_row);
+
     content_box.append(&files_group);
 
     // === GRUPO 2: Informações Básicas ===
     let basic_group = PreferencesGroup::new();
-    basic_group.set_title("Informações Básicas");
-    basic_group.set_description(Some("Dados essenciais da aplicação"));
+    basic_group.set_title(gettext("Basic Information"));
+    basic_group.set_description(Some(gettext("Essential application data")));
 
     // Nome
     let name_row = ActionRow::new();
-    name_row.set_title("Nome");
-    name_row.set_tooltip_text(Some("Nome amigável exibido ao usuário"));
+    name_row.set_title(gettext("Name"));
+    name_row.set_tooltip_text(Some(gettext("Friendly name shown to the user")));
     add_prefix_icon_to_action_row(&name_row, "📝");
     let name_entry = Entry::new();
-    name_entry.set_placeholder_text(Some("Ex: Meu Aplicativo"));
+    name_entry.set_placeholder_text(Some(gettext("e.g. My Application")));
     name_entry.set_valign(Align::Center);
     name_entry.set_hexpand(true);
     name_entry.set_width_chars(30);
@@ -226,12 +840,12 @@ fn build_ui(app: &Application) {
 
     // Exec
     let exec_row = ActionRow::new();
-    exec_row.set_title("Comando");
-    exec_row.set_subtitle("Nome do executável (ex: myapp)");
-    exec_row.set_tooltip_text(Some("Comando usado no .desktop para iniciar a aplicação"));
+    exec_row.set_title(gettext("Command"));
+    exec_row.set_subtitle(gettext("Executable name (e.g. myapp)"));
+    exec_row.set_tooltip_text(Some(gettext("Command used in the .desktop file to start the application")));
     add_prefix_icon_to_action_row(&exec_row, "▶️");
     let exec_entry = Entry::new();
-    exec_entry.set_placeholder_text(Some("Ex: meu-app"));
+    exec_entry.set_placeholder_text(Some(gettext("e.g. my-app")));
     exec_entry.set_valign(Align::Center);
     exec_entry.set_hexpand(true);
     exec_entry.set_width_chars(30);
@@ -244,29 +858,29 @@ fn build_ui(app: &Application) {
 
     // Categorias - usando ActionRow expandível com CheckButtons
     let categories_row = adw::ExpanderRow::new();
-    categories_row.set_title("Categorias");
-    categories_row.set_subtitle("Selecione as categorias do aplicativo");
-    categories_row.set_tooltip_text(Some("Categorias do menu seguindo o padrão FreeDesktop"));
+    categories_row.set_title(gettext("Categories"));
+    categories_row.set_subtitle(gettext("Select the application categories"));
+    categories_row.set_tooltip_text(Some(gettext("Menu categories following the FreeDesktop standard")));
     add_prefix_icon_to_expander_row(&categories_row, "📂");
 
     // Lista de categorias comuns do FreeDesktop
     let category_options = vec![
-        ("AudioVideo", "Áudio e Vídeo"),
-        ("Audio", "Áudio"),
-        ("Video", "Vídeo"),
-        ("Development", "Desenvolvimento"),
-        ("Education", "Educação"),
-        ("Game", "Jogo"),
-        ("Graphics", "Gráficos"),
-        ("Network", "Rede"),
-        ("Office", "Escritório"),
-        ("Science", "Ciência"),
-        ("Settings", "Configurações"),
-        ("System", "Sistema"),
-        ("Utility", "Utilitário"),
+        ("AudioVideo", gettext("Audio & Video")),
+        ("Audio", gettext("Audio")),
+        ("Video", gettext("Video")),
+        ("Development", gettext("Development")),
+        ("Education", gettext("Education")),
+        ("Game", gettext("Game")),
+        ("Graphics", gettext("Graphics")),
+        ("Network", gettext("Network")),
+        ("Office", gettext("Office")),
+        ("Science", gettext("Science")),
+        ("Settings", gettext("Settings")),
+        ("System", gettext("System")),
+        ("Utility", gettext("Utility")),
     ];
 
-    let mut category_checks = Vec::new();
+    let mut category_checks_vec = Vec::new();
 
     for (cat_value, cat_label) in category_options {
         let check_row = ActionRow::new();
@@ -276,25 +890,27 @@ fn build_ui(app: &Application) {
         check_row.add_prefix(&check);
         check_row.set_activatable_widget(Some(&check));
         categories_row.add_row(&check_row);
-        category_checks.push((cat_value, check));
+        category_checks_vec.push((cat_value, check));
     }
 
+    let category_checks = Rc::new(category_checks_vec);
+
     basic_group.add(&categories_row);
 
     content_box.append(&basic_group);
 
     // === GRUPO 3: Detalhes (Opcional) ===
     let details_group = PreferencesGroup::new();
-    details_group.set_title("Detalhes");
-    details_group.set_description(Some("Informações adicionais (opcional)"));
+    details_group.set_title(gettext("Details"));
+    details_group.set_description(Some(gettext("Additional information (optional)")));
 
     // Versão
     let version_row = ActionRow::new();
-    version_row.set_title("Versão");
-    version_row.set_tooltip_text(Some("Versão exibida no metadado do AppImage"));
+    version_row.set_title(gettext("Version"));
+    version_row.set_tooltip_text(Some(gettext("Version shown in the AppImage metadata")));
     add_prefix_icon_to_action_row(&version_row, "🔖");
     let version_entry = Entry::new();
-    version_entry.set_placeholder_text(Some("Ex: 1.2.3"));
+    version_entry.set_placeholder_text(Some(gettext("e.g. 1.2.3")));
     version_entry.set_valign(Align::Center);
     version_entry.set_hexpand(true);
     version_entry.set_width_chars(30);
@@ -304,11 +920,11 @@ fn build_ui(app: &Application) {
 
     // Descrição
     let comment_row = ActionRow::new();
-    comment_row.set_title("Descrição");
-    comment_row.set_tooltip_text(Some("Breve resumo exibido em lojas e menus"));
+    comment_row.set_title(gettext("Description"));
+    comment_row.set_tooltip_text(Some(gettext("Short summary shown in stores and menus")));
     add_prefix_icon_to_action_row(&comment_row, "💬");
     let comment_entry = Entry::new();
-    comment_entry.set_placeholder_text(Some("Ex: Ferramenta para gerar AppImages"));
+    comment_entry.set_placeholder_text(Some(gettext("e.g. Tool for creating AppImages")));
     comment_entry.set_valign(Align::Center);
     comment_entry.set_hexpand(true);
     comment_entry.set_width_chars(30);
@@ -318,41 +934,43 @@ fn build_ui(app: &Application) {
 
     // Autor
     let author_row = ActionRow::new();
-    author_row.set_title("Autor");
-    author_row.set_tooltip_text(Some("Pessoa ou organização responsável pelo app"));
+    author_row.set_title(gettext("Author"));
+    author_row.set_tooltip_text(Some(gettext("Person or organization responsible for the app")));
     add_prefix_icon_to_action_row(&author_row, "👤");
     let author_entry = Entry::new();
-    author_entry.set_placeholder_text(Some("Ex: Karan Luciano"));
+    author_entry.set_placeholder_text(Some(gettext("e.g. Karan Luciano")));
     author_entry.set_valign(Align::Center);
     author_entry.set_hexpand(true);
     author_entry.set_width_chars(30);
+    let author_indicator = new_profile_indicator();
+    author_row.add_suffix(&author_indicator);
     author_row.add_suffix(&author_entry);
     author_row.set_activatable_widget(Some(&author_entry));
     details_group.add(&author_row);
 
     // Licença
     let license_row = ExpanderRow::new();
-    license_row.set_title("Licença");
-    license_row.set_subtitle("Selecione uma licença comum ou informe outra");
-    license_row.set_tooltip_text(Some("Licença de distribuição do seu aplicativo"));
+    license_row.set_title(gettext("License"));
+    license_row.set_subtitle(gettext("Select a common license or enter another one"));
+    license_row.set_tooltip_text(Some(gettext("Distribution license of your application")));
     add_prefix_icon_to_expander_row(&license_row, "📜");
 
     let license_options = vec![
-        ("GPL-3.0-or-later", "GNU GPL 3.0 ou superior"),
-        ("GPL-2.0-or-later", "GNU GPL 2.0 ou superior"),
-        ("LGPL-3.0-or-later", "GNU LGPL 3.0 ou superior"),
+        ("GPL-3.0-or-later", gettext("GNU GPL 3.0 or later")),
+        ("GPL-2.0-or-later", gettext("GNU GPL 2.0 or later")),
+        ("LGPL-3.0-or-later", gettext("GNU LGPL 3.0 or later")),
         ("MIT", "MIT"),
         ("Apache-2.0", "Apache 2.0"),
         ("BSD-3-Clause", "BSD 3-Clause"),
         ("MPL-2.0", "Mozilla Public License 2.0"),
-        ("Proprietary", "Proprietária"),
+        ("Proprietary", gettext("Proprietary")),
     ];
 
     let mut license_checks_vec: Vec<(String, CheckButton)> = Vec::new();
 
     for (value, label) in &license_options {
         let check_row = ActionRow::new();
-        check_row.set_title(*label);
+        check_row.set_title(label);
         let check = CheckButton::new();
         check.set_valign(Align::Center);
         check_row.add_prefix(&check);
@@ -362,9 +980,9 @@ fn build_ui(app: &Application) {
     }
 
     let custom_license_row = ActionRow::new();
-    custom_license_row.set_title("Outra licença");
+    custom_license_row.set_title(gettext("Other license"));
     let license_entry = Entry::new();
-    license_entry.set_placeholder_text(Some("Ex: GPL-3.0-or-later"));
+    license_entry.set_placeholder_text(Some(gettext("e.g. GPL-3.0-or-later")));
     license_entry.set_valign(Align::Center);
     license_entry.set_hexpand(true);
     license_entry.set_width_chars(30);
@@ -372,18 +990,22 @@ fn build_ui(app: &Application) {
     custom_license_row.set_activatable_widget(Some(&license_entry));
     license_row.add_row(&custom_license_row);
 
+    let license_indicator = new_profile_indicator();
+    license_row.add_action(&license_indicator);
     details_group.add(&license_row);
 
     // Website
     let website_row = ActionRow::new();
     website_row.set_title("Website");
-    website_row.set_tooltip_text(Some("Site oficial, repositório ou página de suporte"));
+    website_row.set_tooltip_text(Some(gettext("Official site, repository or support page")));
     add_prefix_icon_to_action_row(&website_row, "🌐");
     let website_entry = Entry::new();
-    website_entry.set_placeholder_text(Some("Ex: https://meuapp.dev"));
+    website_entry.set_placeholder_text(Some(gettext("e.g. https://myapp.dev")));
     website_entry.set_valign(Align::Center);
     website_entry.set_hexpand(true);
     website_entry.set_width_chars(30);
+    let website_indicator = new_profile_indicator();
+    website_row.add_suffix(&website_indicator);
     website_row.add_suffix(&website_entry);
     website_row.set_activatable_widget(Some(&website_entry));
     details_group.add(&website_row);
@@ -392,16 +1014,16 @@ fn build_ui(app: &Application) {
 
     // === GRUPO 4: Pasta de Saída ===
     let output_group = PreferencesGroup::new();
-    output_group.set_title("Pasta de Saída");
-    output_group.set_description(Some("Onde o AppImage será salvo"));
+    output_group.set_title(gettext("Output Folder"));
+    output_group.set_description(Some(gettext("Where the AppImage will be saved")));
 
     let output_row = ActionRow::new();
-    output_row.set_title("Pasta de Saída");
-    output_row.set_subtitle("Onde o AppImage será salvo");
-    output_row.set_tooltip_text(Some("Diretório onde o arquivo AppImage final será criado"));
+    output_row.set_title(gettext("Output Folder"));
+    output_row.set_subtitle(gettext("Where the AppImage will be saved"));
+    output_row.set_tooltip_text(Some(gettext("Directory where the final AppImage file will be created")));
     add_prefix_icon_to_action_row(&output_row, "📁");
     let output_entry = Entry::new();
-    output_entry.set_placeholder_text(Some("Ex: /home/usuario/Distribuicoes"));
+    output_entry.set_placeholder_text(Some(gettext("e.g. /home/user/Releases")));
     output_entry.set_editable(false);
     output_entry.set_valign(Align::Center);
     output_entry.set_hexpand(true);
@@ -410,7 +1032,7 @@ fn build_ui(app: &Application) {
     let output_button_box = Box::new(Orientation::Horizontal, 6);
     let output_button_icon = Label::new(Some("📁"));
     output_button_icon.add_css_class("dim-label");
-    let output_button_label = Label::new(Some("Escolher Pasta"));
+    let output_button_label = Label::new(Some(gettext("Choose Folder")));
     output_button_box.append(&output_button_icon);
     output_button_box.append(&output_button_label);
     output_button.set_child(Some(&output_button_box));
@@ -418,11 +1040,218 @@ fn build_ui(app: &Application) {
     let output_box = Box::new(Orientation::Horizontal, 6);
     output_box.append(&output_entry);
     output_box.append(&output_button);
+    let output_indicator = new_profile_indicator();
+    output_row.add_suffix(&output_indicator);
     output_row.add_suffix(&output_box);
     output_row.set_activatable_widget(Some(&output_button));
     output_group.add(&output_row);
 
-    let preview_label = Label::new(Some("Preencha os campos para ver o preview."));
+    let file_name_row = ActionRow::new();
+    file_name_row.set_title(gettext("File Name"));
+    file_name_row.set_subtitle(gettext("Name template; the .AppImage extension is appended"));
+    file_name_row.set_tooltip_text(Some(&gettext_f(
+        "Variables: {variables}. e.g. {name}-{version}-{arch}",
+        &[("variables", &FILE_NAME_VARIABLES.map(|v| format!("{{{}}}", v)).join(", "))],
+    )));
+    add_prefix_icon_to_action_row(&file_name_row, "🏷️");
+    let file_name_entry = Entry::new();
+    file_name_entry.set_placeholder_text(Some(DEFAULT_FILE_NAME_TEMPLATE));
+    file_name_entry.set_valign(Align::Center);
+    file_name_entry.set_hexpand(true);
+    file_name_entry.set_width_chars(30);
+    let file_name_indicator = new_profile_indicator();
+    file_name_row.add_suffix(&file_name_indicator);
+    file_name_row.add_suffix(&file_name_entry);
+    output_group.add(&file_name_row);
+
+    let output_mode_model = StringList::new(&[]);
+    for mode in OutputMode::ALL {
+        output_mode_model.append(mode.label());
+    }
+    let output_mode_row = ComboRow::new();
+    output_mode_row.set_title(gettext("Output Format"));
+    output_mode_row.set_subtitle(gettext("The AppDir is the folder assembled before compression"));
+    output_mode_row.set_model(Some(&output_mode_model));
+    add_prefix_icon_to_action_row(output_mode_row.upcast_ref(), "📦");
+    {
+        let state_clone = app_state.clone();
+        output_mode_row.connect_selected_notify(move |row| {
+            if let Some(mode) = OutputMode::ALL.get(row.selected() as usize) {
+                state_clone.borrow_mut().options.output_mode = *mode;
+            }
+        });
+    }
+    output_group.add(&output_mode_row);
+
+    let backend_model = StringList::new(&[]);
+    for backend in BackendKind::ALL {
+        backend_model.append(backend.label());
+    }
+    let backend_row = ComboRow::new();
+    backend_row.set_title("Backend");
+    backend_row.set_subtitle(gettext("Tool that compresses the AppDir into the AppImage"));
+    backend_row.set_tooltip_text(Some(gettext(
+        "Automatic uses cargo appimage, or appimagetool when a compression is set. \
+         Native writes the image without external tools and only needs the runtime",
+    )));
+    backend_row.set_model(Some(&backend_model));
+    add_prefix_icon_to_action_row(backend_row.upcast_ref(), "⚙️");
+    let backend_indicator = new_profile_indicator();
+    backend_row.add_suffix(&backend_indicator);
+    {
+        let state_clone = app_state.clone();
+        backend_row.connect_selected_notify(move |row| {
+            if let Some(backend) = BackendKind::ALL.get(row.selected() as usize) {
+                state_clone.borrow_mut().options.backend = *backend;
+            }
+        });
+    }
+    output_group.add(&backend_row);
+
+    let if_exists_model = StringList::new(&[]);
+    for policy in ExistingOutput::ALL {
+        if_exists_model.append(policy.label());
+    }
+    let if_exists_row = ComboRow::new();
+    if_exists_row.set_title(gettext("If the File Exists"));
+    if_exists_row.set_subtitle(gettext("What to do with a previous build at the same path"));
+    if_exists_row.set_model(Some(&if_exists_model));
+    add_prefix_icon_to_action_row(if_exists_row.upcast_ref(), "♻️");
+    {
+        let state_clone = app_state.clone();
+        if_exists_row.connect_selected_notify(move |row| {
+            if let Some(policy) = ExistingOutput::ALL.get(row.selected() as usize) {
+                state_clone.borrow_mut().options.if_exists = *policy;
+            }
+        });
+    }
+    output_group.add(&if_exists_row);
+
+    let reproducible_row = ActionRow::new();
+    reproducible_row.set_title(gettext("Reproducible Build"));
+    reproducible_row.set_subtitle(gettext("Dates from SOURCE_DATE_EPOCH, fixed permissions and compression (does not use cargo appimage)"));
+    reproducible_row.set_tooltip_text(Some(
+        gettext("Builds with the same inputs produce bit-for-bit identical files"),
+    ));
+    add_prefix_icon_to_action_row(&reproducible_row, "🔁");
+    let reproducible_switch = Switch::new();
+    reproducible_switch.set_valign(Align::Center);
+    reproducible_row.add_suffix(&reproducible_switch);
+    reproducible_row.set_activatable_widget(Some(&reproducible_switch));
+    {
+        let state_clone = app_state.clone();
+        reproducible_switch.connect_active_notify(move |switch| {
+            state_clone.borrow_mut().options.reproducible = switch.is_active();
+        });
+    }
+    output_group.add(&reproducible_row);
+
+    let compression_row = ExpanderRow::new();
+    compression_row.set_title(gettext("Compression"));
+    compression_row.set_subtitle(gettext("Backend default"));
+    compression_row.set_tooltip_text(Some(
+        gettext("Algorithm, level and block size of the SquashFS image (not supported by the cargo appimage backend)"),
+    ));
+    add_prefix_icon_to_expander_row(&compression_row, "🗜️");
+
+    let algorithm_model = StringList::new(&[gettext("Backend default")]);
+    for algorithm in CompressionAlgorithm::ALL {
+        algorithm_model.append(algorithm.id());
+    }
+    let algorithm_row = ComboRow::new();
+    algorithm_row.set_title(gettext("Algorithm"));
+    algorithm_row.set_subtitle(gettext("xz compresses more; lz4 and zstd at low levels decompress faster"));
+    algorithm_row.set_model(Some(&algorithm_model));
+    compression_row.add_row(&algorithm_row);
+
+    let level_row = ActionRow::new();
+    level_row.set_title(gettext("Level"));
+    level_row.set_subtitle(gettext("gzip: 1 to 9; zstd: 1 to 22"));
+    let level_spin = SpinButton::with_range(1.0, 22.0, 1.0);
+    level_spin.set_valign(Align::Center);
+    level_row.add_suffix(&level_spin);
+    level_row.set_activatable_widget(Some(&level_spin));
+    level_row.set_sensitive(false);
+    compression_row.add_row(&level_row);
+
+    let block_size_model = StringList::new(&[&gettext_f(
+        "Default ({size})",
+        &[("size", &format_size(DEFAULT_BLOCK_SIZE as u64))],
+    )]);
+    for block_size in BLOCK_SIZES {
+        block_size_model.append(&format_size(block_size as u64));
+    }
+    let block_size_row = ComboRow::new();
+    block_size_row.set_title(gettext("Block Size"));
+    block_size_row.set_subtitle(gettext("Larger blocks compress more, with slower random access"));
+    block_size_row.set_model(Some(&block_size_model));
+    block_size_row.set_sensitive(false);
+    compression_row.add_row(&block_size_row);
+
+    let compression_indicator = new_profile_indicator();
+    compression_row.add_action(&compression_indicator);
+    output_group.add(&compression_row);
+
+    // Pacotes gerados do mesmo AppDir, na mesma pasta do AppImage
+    let formats_row = ExpanderRow::new();
+    formats_row.set_title(gettext("Additional Formats"));
+    formats_row.set_subtitle(gettext("None"));
+    formats_row.set_tooltip_text(Some(gettext("Packages and manifests generated from the same AppDir and metadata")));
+    add_prefix_icon_to_expander_row(&formats_row, "🧩");
+    let mut format_checks_vec = Vec::new();
+    for format in PackageFormat::ALL {
+        let check_row = ActionRow::new();
+        check_row.set_title(format.label());
+        check_row.set_subtitle(format.description());
+        let check = CheckButton::new();
+        check.set_valign(Align::Center);
+        check_row.add_prefix(&check);
+        check_row.set_activatable_widget(Some(&check));
+        formats_row.add_row(&check_row);
+        format_checks_vec.push((format, check));
+    }
+    let format_checks = Rc::new(format_checks_vec);
+    for (_, check) in format_checks.iter() {
+        let state_clone = app_state.clone();
+        let format_checks = format_checks.clone();
+        let formats_row = formats_row.clone();
+        check.connect_toggled(move |_| {
+            let formats: Vec<PackageFormat> = format_checks
+                .iter()
+                .filter(|(_, check)| check.is_active())
+                .map(|(format, _)| *format)
+                .collect();
+            let ids: Vec<&str> = formats.iter().map(|format| format.id()).collect();
+            formats_row.set_subtitle(if ids.is_empty() { gettext("None").to_string() } else { ids.join(", ") }.as_str());
+            state_clone.borrow_mut().options.extra_formats = formats;
+        });
+    }
+    let formats_indicator = new_profile_indicator();
+    formats_row.add_action(&formats_indicator);
+    output_group.add(&formats_row);
+
+    let compression_widgets = CompressionWidgets {
+        row: compression_row.clone(),
+        algorithm_row: algorithm_row.clone(),
+        level_row: level_row.clone(),
+        level_spin: level_spin.clone(),
+        block_size_row: block_size_row.clone(),
+    };
+
+    let work_root_row = ActionRow::new();
+    work_root_row.set_title(gettext("Work Folder"));
+    work_root_row.set_subtitle(gettext("Where each build assembles its temporary files"));
+    work_root_row.set_tooltip_text(Some(gettext("Each build uses its own subdirectory, removed at the end")));
+    add_prefix_icon_to_action_row(&work_root_row, "🗂️");
+    let work_root_entry = Entry::new();
+    work_root_entry.set_placeholder_text(Some(&std::env::temp_dir().to_string_lossy()));
+    work_root_entry.set_valign(Align::Center);
+    work_root_entry.set_hexpand(true);
+    work_root_entry.set_width_chars(30);
+    work_root_row.add_suffix(&work_root_entry);
+    output_group.add(&work_root_row);
+
+    let preview_label = Label::new(Some(gettext("Fill in the fields to see the preview.")));
     preview_label.set_wrap(true);
     preview_label.set_halign(Align::Center);
     preview_label.set_margin_bottom(4);
@@ -440,8 +1269,29 @@ fn build_ui(app: &Application) {
     let exec_entry_for_ui = exec_entry.clone();
     let categories_row_for_ui = categories_row.clone();
     let output_entry_for_ui = output_entry.clone();
+    let file_name_entry_for_ui = file_name_entry.clone();
     let website_entry_for_ui = website_entry.clone();
     let preview_label_for_ui = preview_label.clone();
+    let active_profile_for_ui = active_profile.clone();
+    let history_for_ui = history.clone();
+    let undo_action_for_ui = undo_action.clone();
+    let redo_action_for_ui = redo_action.clone();
+    let window_title_for_ui = window_title.clone();
+    let recipe_path_for_ui = recipe_path.clone();
+    let profile_indicators_for_ui = [
+        (ProfileField::Author, author_indicator.clone()),
+        (ProfileField::License, license_indicator.clone()),
+        (ProfileField::Website, website_indicator.clone()),
+        (ProfileField::OutputFolder, output_indicator.clone()),
+        (ProfileField::FileName, file_name_indicator.clone()),
+        (ProfileField::Backend, backend_indicator.clone()),
+        (ProfileField::ExtraFormats, formats_indicator.clone()),
+        (ProfileField::Compression, compression_indicator.clone()),
+    ];
+
+    let sample_cache: Rc<RefCell<HashMap<SampleKey, SampleStatus>>> = Rc::default();
+    let (sample_sender, sample_receiver) = unbounded::<(SampleKey, Option<CompressionSample>)>();
+    let sample_cache_for_ui = sample_cache.clone();
 
     let update_ui: Rc<dyn Fn()> = Rc::new(move || {
         let state = app_state_for_ui.borrow();
@@ -461,39 +1311,124 @@ fn build_ui(app: &Application) {
             set_widget_validation(&website_entry_for_ui, false);
         }
 
-        if state.metadata.name.is_empty() || state.metadata.binary_path.is_empty() {
-            preview_label_for_ui.set_text("Preencha o binário e o nome para ver o preview.");
-            return;
+        let recipe = Recipe {
+            output_folder: state.output_folder.clone(),
+            app: state.metadata.clone(),
+            build: state.options.clone(),
+        };
+
+        // Indicação dos campos com o valor do perfil ativo
+        let inherited = match active_profile_for_ui.borrow().as_ref() {
+            Some(profile) => {
+                let tooltip = gettext_f("Inherited from the {profile} profile", &[("profile", &profile.name)]);
+                for (_, indicator) in &profile_indicators_for_ui {
+                    indicator.set_tooltip_text(Some(&tooltip));
+                }
+                profile.inherited(&recipe)
+            }
+            None => Vec::new(),
+        };
+        for (field, indicator) in &profile_indicators_for_ui {
+            indicator.set_visible(inherited.contains(field));
         }
 
-        let file_name = format!("{}.AppImage", state.metadata.name);
-        let mut total_size: u64 = 0;
-        if let Ok(meta) = fs::metadata(&state.metadata.binary_path) {
-            total_size = total_size.saturating_add(meta.len());
+        {
+            let mut history = history_for_ui.borrow_mut();
+            history.record(recipe);
+            undo_action_for_ui.set_enabled(history.can_undo());
+            redo_action_for_ui.set_enabled(history.can_redo());
+            update_window_title(&window_title_for_ui, recipe_path_for_ui.borrow().as_deref(), history.is_dirty());
         }
-        if let Ok(meta) = fs::metadata(&state.metadata.icon_path) {
-            total_size = total_size.saturating_add(meta.len());
+
+        if state.metadata.name.is_empty() || state.metadata.binary_path.is_empty() {
+            preview_label_for_ui.set_text(gettext("Fill in the binary and the name to see the preview."));
+            return;
         }
-        if total_size > 0 {
-            // Estimar overhead adicional de 5 MB para estrutura AppImage
-            total_size = total_size.saturating_add(5 * 1024 * 1024);
-            preview_label_for_ui.set_text(&format!(
-                "Preview: {} (≈ {})",
-                file_name,
-                format_size(total_size)
-            ));
 
-            let estimated_secs = ((total_size as f64) / (1.5 * 1024.0 * 1024.0)).ceil() as u64;
-            time_label_for_ui.set_text(&format!(
-                "Estimativa de tempo: {}",
-                format_duration(estimated_secs)
-            ));
-        } else {
-            preview_label_for_ui.set_text(&format!("Preview: {}", file_name));
+        let file_name = match state.options.output_path(Path::new(""), &state.metadata) {
+            Ok(path) => {
+                file_name_entry_for_ui.remove_css_class("error");
+                path.to_string_lossy().to_string()
+            }
+            Err(err) => {
+                file_name_entry_for_ui.add_css_class("error");
+                preview_label_for_ui.set_text(&gettext_f("Preview: {file}", &[("file", &err)]));
+                time_label_for_ui.set_text("");
+                return;
+            }
+        };
+        let binary_size = fs::metadata(&state.metadata.binary_path).map(|m| m.len()).unwrap_or(0);
+        if binary_size == 0 {
+            preview_label_for_ui.set_text(&gettext_f("Preview: {file}", &[("file", &file_name)]));
             time_label_for_ui.set_text("");
+            return;
+        }
+
+        // Taxa e velocidade medidas comprimindo trechos do binário com a
+        // compressão escolhida (ou a padrão da ferramenta)
+        let compression = state
+            .options
+            .squashfs_compression()
+            .unwrap_or_else(Compression::tool_default);
+        let key: SampleKey = (PathBuf::from(&state.metadata.binary_path), binary_size, compression);
+        let status = sample_cache_for_ui.borrow().get(&key).copied();
+        match status {
+            Some(SampleStatus::Done(sample)) => {
+                let estimate = appimage::estimate(&state.metadata, &sample);
+                preview_label_for_ui.set_text(&gettext_f(
+                    "Preview: {file} (≈ {size}; {appdir_size} AppDir)",
+                    &[
+                        ("file", &file_name),
+                        ("size", &format_size(estimate.output_size)),
+                        ("appdir_size", &format_size(estimate.appdir_size)),
+                    ],
+                ));
+                let estimated_secs = estimate.seconds.ceil() as u64;
+                time_label_for_ui.set_text(&gettext_f(
+                    "Estimated time: {duration} ({compression})",
+                    &[
+                        ("duration", &format_duration(estimated_secs.max(1))),
+                        ("compression", &compression.describe()),
+                    ],
+                ));
+            }
+            Some(SampleStatus::Failed) => {
+                preview_label_for_ui.set_text(&gettext_f("Preview: {file}", &[("file", &file_name)]));
+                time_label_for_ui.set_text("");
+            }
+            Some(SampleStatus::Running) => {
+                preview_label_for_ui.set_text(&gettext_f("Preview: {file} (estimating...)", &[("file", &file_name)]));
+                time_label_for_ui.set_text("");
+            }
+            None => {
+                preview_label_for_ui.set_text(&gettext_f("Preview: {file} (estimating...)", &[("file", &file_name)]));
+                time_label_for_ui.set_text("");
+                sample_cache_for_ui.borrow_mut().insert(key.clone(), SampleStatus::Running);
+                let sender = sample_sender.clone();
+                std::thread::spawn(move || {
+                    let sample = appimage::sample_compression(&key.0, &key.2).ok();
+                    let _ = sender.send_blocking((key, sample));
+                });
+            }
         }
     });
 
+    // Resultado das amostras de compressão: guardar e atualizar o preview
+    {
+        let sample_cache = sample_cache.clone();
+        let update_ui = update_ui.clone();
+        glib::MainContext::default().spawn_local(async move {
+            while let Ok((key, sample)) = sample_receiver.recv().await {
+                let status = match sample {
+                    Some(sample) => SampleStatus::Done(sample),
+                    None => SampleStatus::Failed,
+                };
+                sample_cache.borrow_mut().insert(key, status);
+                update_ui.as_ref()();
+            }
+        });
+    }
+
     content_box.append(&output_group);
 
     scrolled.set_child(Some(&content_box));
@@ -552,10 +1487,10 @@ fn build_ui(app: &Application) {
     header_box.append(&button_icon);
 
     let text_box = Box::new(Orientation::Vertical, 3);
-    let button_label = Label::new(Some("Gerar AppImage"));
+    let button_label = Label::new(Some(gettext("Generate AppImage")));
     button_label.add_css_class("button-main-title");
     button_label.set_halign(Align::Start);
-    let button_subtitle = Label::new(Some("Empacotar aplicação em formato portátil"));
+    let button_subtitle = Label::new(Some(gettext("Package the application in a portable format")));
     button_subtitle.add_css_class("button-main-subtitle");
     button_subtitle.set_halign(Align::Start);
     text_box.append(&button_label);
@@ -564,14 +1499,6 @@ fn build_ui(app: &Application) {
 
     button_content.append(&header_box);
 
-    // Progress bar mais elegante
-    let progress_bar = ProgressBar::new();
-    progress_bar.add_css_class("main-progress-bar");
-    progress_bar.set_visible(false);
-    progress_bar.set_margin_top(6);
-    progress_bar.set_margin_start(8);
-    progress_bar.set_margin_end(8);
-    button_content.append(&progress_bar);
 
     // Botão que contém todo o conteúdo
     let generate_button = Button::new();
@@ -585,10 +1512,24 @@ fn build_ui(app: &Application) {
     generate_button.set_margin_end(16);
     card_box.append(&generate_button);
 
-    button_area.append(&card_box);
+    // Lista das gerações enfileiradas, em andamento e concluídas
+    let builds_list = ListBox::new();
+    builds_list.add_css_class("boxed-list");
+    builds_list.set_selection_mode(gtk4::SelectionMode::None);
+    let builds_scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .vscrollbar_policy(gtk4::PolicyType::Automatic)
+        .propagate_natural_height(true)
+        .max_content_height(260)
+        .child(&builds_list)
+        .build();
+    builds_scrolled.set_margin_start(16);
+    builds_scrolled.set_margin_end(16);
+    builds_scrolled.set_margin_bottom(12);
+    builds_scrolled.set_visible(false);
+    card_box.append(&builds_scrolled);
 
-    let pulse_source = Rc::new(RefCell::new(None::<SourceId>));
-    let (result_sender, result_receiver) = unbounded::<Result<PathBuf, String>>();
+    button_area.append(&card_box);
 
     main_box.append(&button_area);
 
@@ -603,58 +1544,125 @@ fn build_ui(app: &Application) {
 
     window.set_content(Some(&toast_overlay));
 
-    {
-        let toast_clone = toast_overlay.clone();
-        let progress_bar_clone = progress_bar.clone();
-        let button_clone = generate_button.clone();
-        let button_label_clone = button_label.clone();
-        let button_subtitle_clone = button_subtitle.clone();
-        let pulse_source_clone = pulse_source.clone();
-
-        glib::MainContext::default().spawn_local(async move {
-            while let Ok(result) = result_receiver.recv().await {
-                // Parar animação de pulso
-                if let Some(source_id) = pulse_source_clone.borrow_mut().take() {
-                    source_id.remove();
+    // Foco no campo responsável por uma entrada inválida
+    let focus_field: Rc<dyn Fn(InputField)> = {
+        let binary_entry = binary_entry.clone();
+        let icon_entry = icon_entry.clone();
+        let name_entry = name_entry.clone();
+        let exec_entry = exec_entry.clone();
+        let categories_row = categories_row.clone();
+        let output_entry = output_entry.clone();
+        let file_name_entry = file_name_entry.clone();
+        let imported_files_row = imported_files_row.clone();
+        let compression_row = compression_row.clone();
+        let backend_row = backend_row.clone();
+        Rc::new(move |field| match field {
+            InputField::Binary => {
+                binary_entry.grab_focus();
+            }
+            InputField::Icon => {
+                icon_entry.grab_focus();
+            }
+            InputField::Name => {
+                name_entry.grab_focus();
+            }
+            InputField::Exec => {
+                exec_entry.grab_focus();
+            }
+            InputField::Categories => {
+                categories_row.set_expanded(true);
+                categories_row.grab_focus();
+            }
+            InputField::OutputFolder => {
+                output_entry.grab_focus();
+            }
+            InputField::FileName => {
+                file_name_entry.grab_focus();
+            }
+            InputField::DesktopFile | InputField::MetainfoFile => {
+                imported_files_row.grab_focus();
+            }
+            InputField::Compression => {
+                compression_row.set_expanded(true);
+                compression_row.grab_focus();
+            }
+            InputField::Backend => {
+                backend_row.grab_focus();
+            }
+            // Só informado pela linha de comando
+            InputField::AppDir | InputField::AppImage => {}
+        })
+    };
+
+    // Toast específico para cada tipo de erro da geração
+    let show_build_error: ShowBuildError = {
+        let toast_overlay = toast_overlay.clone();
+        let focus_field = focus_field.clone();
+        Rc::new(move |err, log_view| {
+            let toast = match err {
+                BuildError::InvalidInput { message, .. } => {
+                    Toast::new(&gettext_f("Attention: {message}", &[("message", message)]))
                 }
+                _ => Toast::new(&gettext_f("Error: {error}", &[("error", &err.describe())])),
+            };
+            toast.set_timeout(8);
 
-                // Restaurar estado do botão
-                progress_bar_clone.remove_css_class("pulsing");
-                progress_bar_clone.set_visible(false);
-                progress_bar_clone.set_fraction(0.0);
-                button_label_clone.set_text("Gerar AppImage");
-                button_subtitle_clone.set_text("Empacotar aplicação em formato portátil");
-                button_clone.set_sensitive(true);
-
-                match result {
-                    Ok(path) => {
-                        let destination_path = path.clone();
-                        let toast = Toast::new(&format!(
-                            "AppImage gerado com sucesso em:\n{}",
-                            destination_path.display()
-                        ));
-                        if let Some(folder) = destination_path.parent() {
-                            let folder_path = folder.to_path_buf();
-                            toast.set_button_label(Some("Abrir pasta"));
-                            toast.connect_button_clicked(move |_| {
-                                let file = gio::File::for_path(&folder_path);
-                                if let Err(err) = gio::AppInfo::launch_default_for_uri(
-                                    &file.uri(),
-                                    None::<&gio::AppLaunchContext>,
-                                ) {
-                                    eprintln!("Falha ao abrir pasta: {}", err);
-                                }
-                            });
-                        }
-                        toast.set_timeout(5);
-                        toast_clone.add_toast(toast);
-                    }
-                    Err(err) => {
-                        let toast = Toast::new(&format!("Erro: {}", err));
-                        toast.set_timeout(8);
-                        toast_clone.add_toast(toast);
-                    }
-                }
+            if let Some(field) = err.field() {
+                focus_field(field);
+            }
+
+            // Com log, o toast sempre o copia; o comando de instalação e a pasta
+            // de trabalho mantida ficam como botões na linha da geração
+            if let Some(log_view) = log_view {
+                toast.set_button_label(Some(gettext("Copy log")));
+                let log_view_for_toast = log_view.clone();
+                toast.connect_button_clicked(move |_| {
+                    let buffer = log_view_for_toast.buffer();
+                    let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+                    log_view_for_toast.clipboard().set_text(&text);
+                });
+            } else if let Some(hint) = err.install_hint() {
+                toast.set_button_label(Some(gettext("Copy command")));
+                let toast_overlay = toast_overlay.clone();
+                toast.connect_button_clicked(move |_| {
+                    toast_overlay.clipboard().set_text(hint);
+                });
+            } else if let Some(work_dir) = err.kept_work_dir() {
+                let work_dir = work_dir.to_path_buf();
+                toast.set_button_label(Some(gettext("Open folder")));
+                toast.connect_button_clicked(move |_| open_folder(&work_dir));
+            }
+
+            toast_overlay.add_toast(toast);
+        })
+    };
+
+    let build_queue = Rc::new(RefCell::new(BuildQueue {
+        pending: VecDeque::new(),
+        running: Vec::new(),
+        list: builds_list.clone(),
+        list_container: builds_scrolled.clone(),
+        status_label: button_subtitle.clone(),
+        toast_overlay: toast_overlay.clone(),
+        show_build_error: show_build_error.clone(),
+        cancel_buttons: Vec::new(),
+        cancel_action: cancel_action.clone(),
+    }));
+
+    // Cancelar pelo atalho interrompe todas as gerações em execução ou na fila
+    {
+        let build_queue_clone = build_queue.clone();
+        cancel_action.connect_activate(move |_, _| {
+            // Os botões mexem na fila ao serem acionados; não manter o empréstimo
+            let buttons: Vec<Button> = build_queue_clone
+                .borrow()
+                .cancel_buttons
+                .iter()
+                .filter(|button| button.is_visible() && button.is_sensitive())
+                .cloned()
+                .collect();
+            for button in buttons {
+                button.emit_clicked();
             }
         });
     }
@@ -665,17 +1673,21 @@ fn build_ui(app: &Application) {
         let entry_clone = binary_entry.clone();
         let state_clone = app_state.clone();
         let update_validation_clone = update_ui.clone();
-        binary_button.connect_clicked(move |_| {
+        let settings_clone = settings.clone();
+        binary_button.set_action_name(Some("win.browse-binary"));
+        browse_binary_action.connect_activate(move |_, _| {
             let dialog = FileChooserDialog::new(
-                Some("Selecione o Binário"),
+                Some(gettext("Select the Binary")),
                 Some(&window_clone),
                 FileChooserAction::Open,
-                &[("Cancelar", ResponseType::Cancel), ("Selecionar", ResponseType::Accept)],
+                &[(gettext("Cancel"), ResponseType::Cancel), (gettext("Select"), ResponseType::Accept)],
             );
+            start_in_folder(&dialog, settings_clone.borrow().last_binary_dir.as_deref());
 
             let entry_clone2 = entry_clone.clone();
             let state_clone2 = state_clone.clone();
             let update_validation_inner = update_validation_clone.clone();
+            let settings_clone2 = settings_clone.clone();
             dialog.connect_response(move |dialog, response| {
                 if response == ResponseType::Accept {
                     if let Some(file) = dialog.file() {
@@ -683,6 +1695,11 @@ fn build_ui(app: &Application) {
                             let path_str = path.to_string_lossy().to_string();
                             entry_clone2.set_text(&path_str);
                             state_clone2.borrow_mut().metadata.binary_path = path_str;
+                            {
+                                let mut settings = settings_clone2.borrow_mut();
+                                settings.last_binary_dir = settings::parent_dir(&path);
+                                settings.save_quietly();
+                            }
                             update_validation_inner.as_ref()();
                         }
                     }
@@ -700,17 +1717,21 @@ fn build_ui(app: &Application) {
         let entry_clone = icon_entry.clone();
         let state_clone = app_state.clone();
         let update_validation_clone = update_ui.clone();
-        icon_button.connect_clicked(move |_| {
+        let settings_clone = settings.clone();
+        icon_button.set_action_name(Some("win.browse-icon"));
+        browse_icon_action.connect_activate(move |_, _| {
             let dialog = FileChooserDialog::new(
-                Some("Selecione o Ícone"),
+                Some(gettext("Select the Icon")),
                 Some(&window_clone),
                 FileChooserAction::Open,
-                &[("Cancelar", ResponseType::Cancel), ("Selecionar", ResponseType::Accept)],
+                &[(gettext("Cancel"), ResponseType::Cancel), (gettext("Select"), ResponseType::Accept)],
             );
+            start_in_folder(&dialog, settings_clone.borrow().last_icon_dir.as_deref());
 
             let entry_clone2 = entry_clone.clone();
             let state_clone2 = state_clone.clone();
             let update_validation_inner = update_validation_clone.clone();
+            let settings_clone2 = settings_clone.clone();
             dialog.connect_response(move |dialog, response| {
                 if response == ResponseType::Accept {
                     if let Some(file) = dialog.file() {
@@ -718,6 +1739,11 @@ fn build_ui(app: &Application) {
                             let path_str = path.to_string_lossy().to_string();
                             entry_clone2.set_text(&path_str);
                             state_clone2.borrow_mut().metadata.icon_path = path_str;
+                            {
+                                let mut settings = settings_clone2.borrow_mut();
+                                settings.last_icon_dir = settings::parent_dir(&path);
+                                settings.save_quietly();
+                            }
                             update_validation_inner.as_ref()();
                         }
                     }
@@ -735,23 +1761,32 @@ fn build_ui(app: &Application) {
         let entry_clone = output_entry.clone();
         let state_clone = app_state.clone();
         let update_validation_clone = update_ui.clone();
-        output_button.connect_clicked(move |_| {
+        let settings_clone = settings.clone();
+        output_button.set_action_name(Some("win.browse-output"));
+        browse_output_action.connect_activate(move |_, _| {
             let dialog = FileChooserDialog::new(
-                Some("Escolher Pasta de Saída"),
+                Some(gettext("Choose Output Folder")),
                 Some(&window_clone),
                 FileChooserAction::SelectFolder,
-                &[("Cancelar", ResponseType::Cancel), ("Selecionar", ResponseType::Accept)],
+                &[(gettext("Cancel"), ResponseType::Cancel), (gettext("Select"), ResponseType::Accept)],
             );
+            start_in_folder(&dialog, settings_clone.borrow().last_output_dir.as_deref());
 
             let entry_clone2 = entry_clone.clone();
             let state_clone2 = state_clone.clone();
             let update_validation_inner = update_validation_clone.clone();
+            let settings_clone2 = settings_clone.clone();
             dialog.connect_response(move |dialog, response| {
                 if response == ResponseType::Accept {
                     if let Some(file) = dialog.file() {
                         if let Some(path) = file.path() {
                             let path_str = path.to_string_lossy().to_string();
                             entry_clone2.set_text(&path_str);
+                            {
+                                let mut settings = settings_clone2.borrow_mut();
+                                settings.last_output_dir = Some(path.clone());
+                                settings.save_quietly();
+                            }
                             state_clone2.borrow_mut().output_folder = Some(path);
                             update_validation_inner.as_ref()();
                         }
@@ -764,7 +1799,51 @@ fn build_ui(app: &Application) {
         });
     }
 
+    // Compressão: qualquer controle alterado regrava a configuração inteira
+    let sync_compression: Rc<dyn Fn()> = {
+        let widgets = compression_widgets.clone();
+        let state_clone = app_state.clone();
+        let update_ui_clone = update_ui.clone();
+        Rc::new(move || {
+            let compression = widgets.read();
+            widgets.update_subtitle(compression.as_ref());
+            state_clone.borrow_mut().options.compression = compression;
+            update_ui_clone.as_ref()();
+        })
+    };
+    {
+        let widgets = compression_widgets.clone();
+        let sync = sync_compression.clone();
+        algorithm_row.connect_selected_notify(move |_| {
+            widgets.reset_level();
+            sync.as_ref()();
+        });
+    }
+    {
+        let sync = sync_compression.clone();
+        level_spin.connect_value_changed(move |_| sync.as_ref()());
+    }
+    {
+        let sync = sync_compression.clone();
+        block_size_row.connect_selected_notify(move |_| sync.as_ref()());
+    }
+
     // Conectar mudanças nos campos de texto
+    connect_entry_to_state(
+        &file_name_entry,
+        app_state.clone(),
+        |s, v| s.options.file_name = if v.trim().is_empty() { None } else { Some(v) },
+        update_ui.clone(),
+    );
+    connect_entry_to_state(
+        &work_root_entry,
+        app_state.clone(),
+        |s, v| {
+            let v = v.trim();
+            s.options.work_root = if v.is_empty() { None } else { Some(PathBuf::from(v)) };
+        },
+        update_ui.clone(),
+    );
     connect_entry_to_state(
         &binary_entry,
         app_state.clone(),
@@ -865,7 +1944,7 @@ fn build_ui(app: &Application) {
 
     // Conectar mudanças nos checkboxes de categorias
     let update_ui_for_categories = update_ui.clone();
-    for (cat_value, check) in category_checks {
+    for (cat_value, check) in category_checks.iter() {
         let state_clone = app_state.clone();
         let cat_value_owned = cat_value.to_string();
         let update_ui_local = update_ui_for_categories.clone();
@@ -972,100 +2051,939 @@ fn build_ui(app: &Application) {
         });
     }
 
-    // Ação do botão gerar
-    {
-        let toast_clone = toast_overlay.clone();
-        let progress_bar_clone = progress_bar.clone();
-        let button_clone = generate_button.clone();
-        let button_label_clone = button_label.clone();
-        let button_subtitle_clone = button_subtitle.clone();
-        let sender_clone = result_sender.clone();
-        let pulse_source_clone = pulse_source.clone();
-
-        generate_button.connect_clicked(move |_| {
-            let state_data = app_state.borrow().clone();
-            let metadata_data = &state_data.metadata;
-
-            // Validar pasta de saída primeiro
-            if state_data.output_folder.is_none() {
-                let toast = Toast::new("Atenção: Selecione a pasta de saída!");
-                toast_clone.add_toast(toast);
-                return;
-            }
-
-            // Validação dos campos obrigatórios
-            if metadata_data.binary_path.is_empty() {
-                let toast = Toast::new("Atenção: Selecione o binário!");
-                toast_clone.add_toast(toast);
-                return;
-            }
-            if metadata_data.icon_path.is_empty() {
-                let toast = Toast::new("Atenção: Selecione o ícone!");
-                toast_clone.add_toast(toast);
-                return;
-            }
-            if metadata_data.name.is_empty() {
-                let toast = Toast::new("Atenção: Preencha o nome!");
-                toast_clone.add_toast(toast);
-                return;
-            }
-            if metadata_data.exec.is_empty() {
-                let toast = Toast::new("Atenção: Preencha o comando exec!");
-                toast_clone.add_toast(toast);
-                return;
+    let form = FormWidgets {
+        state: app_state.clone(),
+        update_ui: update_ui.clone(),
+        binary_entry: binary_entry.clone(),
+        icon_entry: icon_entry.clone(),
+        name_entry: name_entry.clone(),
+        exec_entry: exec_entry.clone(),
+        version_entry: version_entry.clone(),
+        comment_entry: comment_entry.clone(),
+        author_entry: author_entry.clone(),
+        website_entry: website_entry.clone(),
+        license_entry: license_entry.clone(),
+        license_checks: license_checks.clone(),
+        category_checks: category_checks.clone(),
+        imported_files_row: imported_files_row.clone(),
+        use_imported_row: use_imported_row.clone(),
+        use_imported_switch: use_imported_switch.clone(),
+        output_entry: output_entry.clone(),
+        file_name_entry: file_name_entry.clone(),
+        output_mode_row: output_mode_row.clone(),
+        backend_row: backend_row.clone(),
+        if_exists_row: if_exists_row.clone(),
+        reproducible_switch: reproducible_switch.clone(),
+        work_root_entry: work_root_entry.clone(),
+        compression: compression_widgets.clone(),
+        format_checks: format_checks.clone(),
+    };
+
+    // Troca o perfil ativo: os campos herdados do anterior são esvaziados e os
+    // vazios recebem os valores do novo
+    let select_profile: Rc<dyn Fn(Option<Profile>)> = {
+        let form_clone = form.clone();
+        let active_profile_clone = active_profile.clone();
+        let settings_clone = settings.clone();
+        let profile_button_clone = profile_button.clone();
+        Rc::new(move |profile| {
+            let mut recipe = form_clone.to_recipe();
+            if let Some(previous) = active_profile_clone.borrow().as_ref() {
+                previous.clear(&mut recipe);
             }
-            if metadata_data.categories.is_empty() {
-                let toast = Toast::new("Atenção: Preencha as categorias!");
-                toast_clone.add_toast(toast);
-                return;
+            let name = profile.as_ref().map(|profile| {
+                profile.fill(&mut recipe);
+                profile.name.clone()
+            });
+            let tooltip = match &name {
+                Some(name) => gettext_f("Profile: {profile}", &[("profile", name)]),
+                None => gettext("Profile: none").to_string(),
+            };
+            profile_button_clone.set_tooltip_text(Some(&tooltip));
+            active_profile_clone.replace(profile);
+            form_clone.apply_recipe(&recipe);
+
+            let mut settings = settings_clone.borrow_mut();
+            if settings.profile != name {
+                settings.profile = name;
+                settings.save_quietly();
             }
+        })
+    };
+
+    // Perfil da sessão anterior, depois os padrões de autor, licença e site lembrados
+    let last_profile = settings.borrow().profile.clone();
+    if let Some(name) = last_profile {
+        match Profile::find(&name) {
+            Ok(profile) => select_profile(Some(profile)),
+            Err(err) => eprintln!(
+                "{}",
+                gettext_f("Profile {profile} unavailable: {error}", &[("profile", &name), ("error", &err)])
+            ),
+        }
+    }
+    form.apply_defaults(&settings.borrow().defaults);
+    history.borrow_mut().reset(form.to_recipe());
+    update_ui.as_ref()();
 
-            // Construir caminho de saída
-            let output_folder = state_data.output_folder.unwrap();
-            let output_path = output_folder.join(format!("{}.AppImage", metadata_data.name));
-
-            // Mostrar progress bar no botão com feedback visual melhorado
-            button_clone.set_sensitive(false);
-            button_label_clone.set_text("Gerando AppImage...");
-            button_subtitle_clone.set_text("Isso pode levar alguns instantes");
-            progress_bar_clone.set_visible(true);
-            progress_bar_clone.set_fraction(0.0);
-            progress_bar_clone.add_css_class("pulsing");
+    // Desfazer/refazer: o estado restaurado é reaplicado ao formulário inteiro
+    for (action, undo) in [(&undo_action, true), (&redo_action, false)] {
+        let history_clone = history.clone();
+        let form_clone = form.clone();
+        action.connect_activate(move |_, _| {
+            let state = if undo {
+                history_clone.borrow_mut().undo()
+            } else {
+                history_clone.borrow_mut().redo()
+            };
+            if let Some(recipe) = state {
+                form_clone.apply_recipe(&recipe);
+                history_clone.borrow_mut().finish_restore(form_clone.to_recipe());
+                form_clone.update_ui.as_ref()();
+            }
+        });
+    }
 
-            // Limpar timeout anterior se existir
-            if let Some(source_id) = pulse_source_clone.borrow_mut().take() {
-                source_id.remove();
+    // Menu de perfis: refeito a cada abertura, para mostrar perfis novos ou editados
+    {
+        let list = profile_list.clone();
+        let active_profile_clone = active_profile.clone();
+        let select_profile_clone = select_profile.clone();
+        profile_popover.connect_show(move |popover| {
+            while let Some(child) = list.first_child() {
+                list.remove(&child);
             }
 
-            // Criar animação de pulso suave (100ms para movimento mais fluido)
-            let progress_for_timeout = progress_bar_clone.clone();
-            let pulse_source_for_timeout = pulse_source_clone.clone();
-            let timeout_id =
-                glib::timeout_add_local(Duration::from_millis(100), move || {
-                    progress_for_timeout.pulse();
-                    ControlFlow::Continue
+            let active_name = active_profile_clone.borrow().as_ref().map(|profile| profile.name.clone());
+            let none_check = CheckButton::with_label(gettext("None"));
+            none_check.set_active(active_name.is_none());
+            list.append(&none_check);
+            let mut choices = vec![(none_check.clone(), None)];
+            for profile in Profile::list() {
+                let check = CheckButton::with_label(&profile.name);
+                check.set_group(Some(&none_check));
+                check.set_tooltip_text(Some(&profile.describe()));
+                check.set_active(active_name.as_deref() == Some(profile.name.as_str()));
+                list.append(&check);
+                choices.push((check, Some(profile)));
+            }
+            for (check, profile) in choices {
+                let popover_clone = popover.clone();
+                let select_profile_inner = select_profile_clone.clone();
+                check.connect_toggled(move |check| {
+                    if check.is_active() {
+                        popover_clone.popdown();
+                        select_profile_inner(profile.clone());
+                    }
                 });
-            pulse_source_for_timeout.borrow_mut().replace(timeout_id);
-
-            let metadata_clone = metadata_data.clone();
-            let sender_for_thread = sender_clone.clone();
+            }
 
-            std::thread::spawn(move || {
-                let result = appimage::generate_appimage(
-                    &metadata_clone,
-                    output_path.as_path(),
-                )
-                .map(|_| output_path)
-                .map_err(|err| err.to_string());
-
-                let _ = sender_for_thread.send_blocking(result);
+            list.append(&Separator::new(Orientation::Horizontal));
+            let folder_button = Button::with_label(gettext("Open profiles folder"));
+            folder_button.add_css_class("flat");
+            folder_button.set_tooltip_text(Some(gettext("Each profile is a <name>.toml file in this folder")));
+            let popover_clone = popover.clone();
+            folder_button.connect_clicked(move |_| {
+                popover_clone.popdown();
+                if let Some(dir) = Profile::dir() {
+                    match fs::create_dir_all(&dir) {
+                        Ok(()) => open_folder(&dir),
+                        Err(err) => eprintln!(
+                            "{}",
+                            gettext_f("Failed to create {path}: {error}", &[("path", &dir.display()), ("error", &err)])
+                        ),
+                    }
+                }
+            });
+            list.append(&folder_button);
+        });
+    }
+
+    // Backend e formatos não atualizam a interface ao mudar; a indicação do perfil depende deles
+    {
+        let update_ui_clone = update_ui.clone();
+        backend_row.connect_selected_notify(move |_| update_ui_clone.as_ref()());
+        for (_, check) in format_checks.iter() {
+            let update_ui_clone = update_ui.clone();
+            check.connect_toggled(move |_| update_ui_clone.as_ref()());
+        }
+    }
+
+    // Abrir e salvar receitas (.toml) com todo o formulário
+    let load_recipe: Rc<dyn Fn(&Path)> = {
+        let toast_clone = toast_overlay.clone();
+        let form_clone = form.clone();
+        let recipe_path_clone = recipe_path.clone();
+        let settings_clone = settings.clone();
+        let active_profile_clone = active_profile.clone();
+        let history_clone = history.clone();
+        Rc::new(move |path| {
+            let message = match Recipe::load(path) {
+                Ok(mut recipe) => {
+                    if let Some(profile) = active_profile_clone.borrow().as_ref() {
+                        profile.fill(&mut recipe);
+                    }
+                    form_clone.apply_recipe(&recipe);
+                    recipe_path_clone.replace(Some(path.to_path_buf()));
+                    history_clone.borrow_mut().reset(form_clone.to_recipe());
+                    form_clone.update_ui.as_ref()();
+                    let mut settings = settings_clone.borrow_mut();
+                    settings.add_recent_recipe(path);
+                    settings.save_quietly();
+                    gettext_f("Recipe loaded: {path}", &[("path", &path.display())])
+                }
+                Err(err) => gettext_f("Error opening the recipe: {error}", &[("error", &err)]),
+            };
+            let toast = Toast::new(&message);
+            toast.set_timeout(5);
+            toast_clone.add_toast(toast);
+        })
+    };
+    {
+        let window_clone = window.clone();
+        let load_recipe_clone = load_recipe.clone();
+        let settings_clone = settings.clone();
+        open_recipe_action.connect_activate(move |_, _| {
+            let dialog = FileChooserDialog::new(
+                Some(gettext("Open Recipe")),
+                Some(&window_clone),
+                FileChooserAction::Open,
+                &[(gettext("Cancel"), ResponseType::Cancel), (gettext("Open"), ResponseType::Accept)],
+            );
+            dialog.add_filter(&recipe_file_filter());
+            start_in_folder(&dialog, settings_clone.borrow().last_recipe_dir.as_deref());
+
+            let load_recipe_inner = load_recipe_clone.clone();
+            dialog.connect_response(move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        load_recipe_inner(&path);
+                    }
+                }
+                dialog.close();
+            });
+
+            dialog.show();
+        });
+    }
+
+    // Menu "Recentes": refeito a cada abertura, sem receitas que não existem mais
+    {
+        let list = recent_recipes_list.clone();
+        let load_recipe_clone = load_recipe.clone();
+        let settings_clone = settings.clone();
+        recent_recipes_popover.connect_show(move |popover| {
+            while let Some(child) = list.first_child() {
+                list.remove(&child);
+            }
+
+            let recipes = settings_clone.borrow().existing_recent_recipes();
+            if recipes.is_empty() {
+                let empty_label = Label::new(Some(gettext("No recent recipes")));
+                empty_label.add_css_class("dim-label");
+                empty_label.set_margin_top(6);
+                empty_label.set_margin_bottom(6);
+                empty_label.set_margin_start(6);
+                empty_label.set_margin_end(6);
+                list.append(&empty_label);
+                return;
+            }
+
+            for path in recipes {
+                let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                let button = Button::with_label(&name);
+                button.add_css_class("flat");
+                button.set_tooltip_text(Some(&path.display().to_string()));
+                if let Some(label) = button.child().and_downcast::<Label>() {
+                    label.set_xalign(0.0);
+                }
+                let popover_clone = popover.clone();
+                let load_recipe_inner = load_recipe_clone.clone();
+                button.connect_clicked(move |_| {
+                    popover_clone.popdown();
+                    load_recipe_inner(&path);
+                });
+                list.append(&button);
+            }
+
+            list.append(&Separator::new(Orientation::Horizontal));
+            let clear_button = Button::with_label(gettext("Clear list"));
+            clear_button.add_css_class("flat");
+            let popover_clone = popover.clone();
+            let settings_inner = settings_clone.clone();
+            clear_button.connect_clicked(move |_| {
+                popover_clone.popdown();
+                let mut settings = settings_inner.borrow_mut();
+                settings.recent_recipes.clear();
+                settings.save_quietly();
+            });
+            list.append(&clear_button);
+        });
+    }
+    let save_recipe_to: Rc<dyn Fn(&Path) -> bool> = {
+        let toast_clone = toast_overlay.clone();
+        let form_clone = form.clone();
+        let recipe_path_clone = recipe_path.clone();
+        let settings_clone = settings.clone();
+        let history_clone = history.clone();
+        Rc::new(move |path| {
+            let recipe = form_clone.to_recipe();
+            let (message, saved) = match recipe.save(path) {
+                Ok(()) => {
+                    recipe_path_clone.replace(Some(path.to_path_buf()));
+                    history_clone.borrow_mut().mark_saved();
+                    let mut settings = settings_clone.borrow_mut();
+                    settings.add_recent_recipe(path);
+                    remember_form_defaults(&mut settings, &recipe.app);
+                    settings.save_quietly();
+                    (gettext_f("Recipe saved to: {path}", &[("path", &path.display())]), true)
+                }
+                Err(err) => (gettext_f("Error saving the recipe: {error}", &[("error", &err)]), false),
+            };
+            form_clone.update_ui.as_ref()();
+            let toast = Toast::new(&message);
+            toast.set_timeout(5);
+            toast_clone.add_toast(toast);
+            saved
+        })
+    };
+    let save_recipe_as: SaveRecipeAs = {
+        let window_clone = window.clone();
+        let form_clone = form.clone();
+        let recipe_path_clone = recipe_path.clone();
+        let settings_clone = settings.clone();
+        let save_recipe_to_clone = save_recipe_to.clone();
+        Rc::new(move |on_saved| {
+            let dialog = FileChooserDialog::new(
+                Some(gettext("Save Recipe")),
+                Some(&window_clone),
+                FileChooserAction::Save,
+                &[(gettext("Cancel"), ResponseType::Cancel), (gettext("Save"), ResponseType::Accept)],
+            );
+            dialog.add_filter(&recipe_file_filter());
+            match recipe_path_clone.borrow().as_ref() {
+                Some(path) => {
+                    let _ = dialog.set_file(&gio::File::for_path(path));
+                }
+                None => {
+                    start_in_folder(&dialog, settings_clone.borrow().last_recipe_dir.as_deref());
+                    let name = appimage::sanitize_package_name(&form_clone.state.borrow().metadata.name);
+                    dialog.set_current_name(&format!("{}.toml", name));
+                }
+            }
+
+            let save_recipe_to_inner = save_recipe_to_clone.clone();
+            dialog.connect_response(move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        if save_recipe_to_inner(&path) {
+                            on_saved();
+                        }
+                    }
+                }
+                dialog.close();
+            });
+
+            dialog.show();
+        })
+    };
+    // Salvar grava na receita aberta; sem receita, ou em "Salvar como", pergunta onde
+    {
+        let recipe_path_clone = recipe_path.clone();
+        let save_recipe_to_clone = save_recipe_to.clone();
+        let save_recipe_as_clone = save_recipe_as.clone();
+        save_recipe_action.connect_activate(move |_, _| {
+            let path = recipe_path_clone.borrow().clone();
+            match path {
+                Some(path) => {
+                    save_recipe_to_clone(&path);
+                }
+                None => save_recipe_as_clone(Rc::new(|| {})),
+            }
+        });
+    }
+    {
+        let save_recipe_as_clone = save_recipe_as.clone();
+        save_recipe_as_action.connect_activate(move |_, _| save_recipe_as_clone(Rc::new(|| {})));
+    }
+
+    // Inspecionar um AppImage existente: cabeçalho, MD5 e .desktop embutido
+    {
+        let window_clone = window.clone();
+        let toast_clone = toast_overlay.clone();
+        let settings_clone = settings.clone();
+        inspect_appimage_action.connect_activate(move |_, _| {
+            let dialog = FileChooserDialog::new(
+                Some(gettext("Inspect AppImage")),
+                Some(&window_clone),
+                FileChooserAction::Open,
+                &[(gettext("Cancel"), ResponseType::Cancel), (gettext("Inspect"), ResponseType::Accept)],
+            );
+            let filter = FileFilter::new();
+            filter.set_name(Some("AppImage (*.AppImage)"));
+            filter.add_pattern("*.AppImage");
+            filter.add_pattern("*.appimage");
+            dialog.add_filter(&filter);
+            start_in_folder(&dialog, settings_clone.borrow().last_output_dir.as_deref());
+
+            let toast_clone2 = toast_clone.clone();
+            dialog.connect_response(move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        inspect_appimage(&toast_clone2, path);
+                    }
+                }
+                dialog.close();
+            });
+
+            dialog.show();
+        });
+    }
+
+    // Alterações não salvas: perguntar antes de fechar
+    {
+        let history_clone = history.clone();
+        let recipe_path_clone = recipe_path.clone();
+        let save_recipe_to_clone = save_recipe_to.clone();
+        let save_recipe_as_clone = save_recipe_as.clone();
+        window.connect_close_request(move |window| {
+            if !history_clone.borrow().is_dirty() {
+                return glib::Propagation::Proceed;
+            }
+
+            let dialog = adw::MessageDialog::new(
+                Some(window),
+                Some(gettext("Save changes?")),
+                Some(gettext("Changes to the form will be lost if you don't save them to a recipe.")),
+            );
+            dialog.add_response("cancel", gettext("Cancel"));
+            dialog.add_response("discard", gettext("Discard"));
+            dialog.add_response("save", gettext("Save"));
+            dialog.set_response_appearance("discard", adw::ResponseAppearance::Destructive);
+            dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+            dialog.set_default_response(Some("save"));
+            dialog.set_close_response("cancel");
+
+            let window_clone = window.clone();
+            let history_inner = history_clone.clone();
+            let recipe_path_inner = recipe_path_clone.clone();
+            let save_recipe_to_inner = save_recipe_to_clone.clone();
+            let save_recipe_as_inner = save_recipe_as_clone.clone();
+            dialog.connect_response(None, move |_, response| match response {
+                "discard" => {
+                    history_inner.borrow_mut().mark_saved();
+                    window_clone.close();
+                }
+                "save" => {
+                    let close: Rc<dyn Fn()> = {
+                        let window_clone = window_clone.clone();
+                        Rc::new(move || window_clone.close())
+                    };
+                    let path = recipe_path_inner.borrow().clone();
+                    match path {
+                        Some(path) => {
+                            if save_recipe_to_inner(&path) {
+                                close();
+                            }
+                        }
+                        None => save_recipe_as_inner(close),
+                    }
+                }
+                _ => {}
+            });
+            dialog.present();
+            glib::Propagation::Stop
+        });
+    }
+
+    // Verificação das ferramentas em segundo plano: algumas demoram a informar a versão
+    {
+        let window_clone = window.clone();
+        let state_clone = app_state.clone();
+        check_environment_action.connect_activate(move |action, _| {
+            action.set_enabled(false);
+            let options = state_clone.borrow().options.clone();
+            let (sender, receiver) = unbounded::<PreflightReport>();
+            std::thread::spawn(move || {
+                let _ = sender.send_blocking(appimage::check_environment(&options));
+            });
+
+            let window_clone2 = window_clone.clone();
+            let action_clone = action.clone();
+            glib::MainContext::default().spawn_local(async move {
+                if let Ok(report) = receiver.recv().await {
+                    show_preflight_report(&window_clone2, &report);
+                }
+                action_clone.set_enabled(true);
+            });
+        });
+    }
+
+    // Oferecer importação do manifesto do projeto que contém o binário
+    {
+        let toast_clone = toast_overlay.clone();
+        let form_clone = form.clone();
+        let last_offered = Rc::new(RefCell::new(None::<PathBuf>));
+        binary_entry.connect_changed(move |entry| {
+            let path_text = entry.text().to_string();
+            let binary_path = Path::new(path_text.trim());
+            if !binary_path.is_file() {
+                return;
+            }
+
+            let Some(manifest) = project::detect_project(binary_path) else {
+                return;
+            };
+
+            // Não repetir a oferta para o mesmo manifesto
+            if last_offered.borrow().as_ref() == Some(&manifest.path) {
+                return;
+            }
+            last_offered.replace(Some(manifest.path.clone()));
+
+            let toast = Toast::new(&gettext_f(
+                "{kind} project detected: {name}",
+                &[("kind", &manifest.kind.label()), ("name", &manifest.name)],
+            ));
+            toast.set_button_label(Some(gettext("Import")));
+            toast.set_timeout(10);
+            let form_for_toast = form_clone.clone();
+            toast.connect_button_clicked(move |_| {
+                form_for_toast.apply_project_manifest(&manifest);
+            });
+            toast_clone.add_toast(toast);
+        });
+    }
+
+    // Importar .desktop/metainfo existentes
+    {
+        let window_clone = window.clone();
+        let toast_clone = toast_overlay.clone();
+        let form_clone = form.clone();
+        import_files_button.set_action_name(Some("win.import-metadata"));
+        import_metadata_action.connect_activate(move |_, _| {
+            let dialog = FileChooserDialog::new(
+                Some(gettext("Select the .desktop and/or metainfo")),
+                Some(&window_clone),
+                FileChooserAction::Open,
+                &[(gettext("Cancel"), ResponseType::Cancel), (gettext("Import"), ResponseType::Accept)],
+            );
+            dialog.set_select_multiple(true);
+
+            let filter = FileFilter::new();
+            filter.set_name(Some(gettext("Desktop entry and AppStream (*.desktop, *.xml)")));
+            filter.add_pattern("*.desktop");
+            filter.add_pattern("*.metainfo.xml");
+            filter.add_pattern("*.appdata.xml");
+            filter.add_pattern("*.xml");
+            dialog.add_filter(&filter);
+
+            let toast_clone2 = toast_clone.clone();
+            let form_clone2 = form_clone.clone();
+            dialog.connect_response(move |dialog, response| {
+                if response == ResponseType::Accept {
+                    let files = dialog.files();
+                    for index in 0..files.n_items() {
+                        let Some(path) = files
+                            .item(index)
+                            .and_downcast::<gio::File>()
+                            .and_then(|file| file.path())
+                        else {
+                            continue;
+                        };
+
+                        let is_desktop = path.extension().and_then(|e| e.to_str()) == Some("desktop");
+                        let imported = if is_desktop {
+                            freedesktop::import_desktop_file(&path)
+                        } else {
+                            freedesktop::import_metainfo(&path)
+                        };
+
+                        match imported {
+                            Ok(imported) => form_clone2.apply_imported_metadata(&imported),
+                            Err(err) => {
+                                let toast = Toast::new(&gettext_f("Error importing: {error}", &[("error", &err)]));
+                                toast.set_timeout(8);
+                                toast_clone2.add_toast(toast);
+                            }
+                        }
+                    }
+
+                    form_clone2.show_imported_files();
+                }
+                dialog.close();
             });
+
+            dialog.show();
+        });
+    }
+
+    {
+        let state_clone = app_state.clone();
+        use_imported_switch.connect_active_notify(move |switch| {
+            state_clone.borrow_mut().metadata.use_imported_files = switch.is_active();
+        });
+    }
+
+    // Arrastar e soltar: a janela aceita qualquer arquivo reconhecido e cada
+    // linha apenas o que ela preenche
+    let apply_dropped: ApplyDropped = {
+        let form_clone = form.clone();
+        let toast_clone = toast_overlay.clone();
+        let settings_clone = settings.clone();
+        let load_recipe_clone = load_recipe.clone();
+        Rc::new(move |path, kind| {
+            let path_text = path.to_string_lossy().to_string();
+            let message = match kind {
+                DroppedKind::Binary => {
+                    form_clone.binary_entry.set_text(&path_text);
+                    let mut settings = settings_clone.borrow_mut();
+                    settings.last_binary_dir = settings::parent_dir(path);
+                    settings.save_quietly();
+                    gettext_f("Binary: {path}", &[("path", &path.display())])
+                }
+                DroppedKind::Icon => {
+                    form_clone.icon_entry.set_text(&path_text);
+                    let mut settings = settings_clone.borrow_mut();
+                    settings.last_icon_dir = settings::parent_dir(path);
+                    settings.save_quietly();
+                    gettext_f("Icon: {path}", &[("path", &path.display())])
+                }
+                DroppedKind::OutputFolder => {
+                    form_clone.output_entry.set_text(&path_text);
+                    form_clone.state.borrow_mut().output_folder = Some(path.to_path_buf());
+                    form_clone.update_ui.as_ref()();
+                    let mut settings = settings_clone.borrow_mut();
+                    settings.last_output_dir = Some(path.to_path_buf());
+                    settings.save_quietly();
+                    gettext_f("Output folder: {path}", &[("path", &path.display())])
+                }
+                DroppedKind::AppDir | DroppedKind::MetadataFile => {
+                    let imported = match kind {
+                        DroppedKind::AppDir => freedesktop::import_appdir(path),
+                        _ if path.extension().is_some_and(|ext| ext == "desktop") => {
+                            freedesktop::import_desktop_file(path)
+                        }
+                        _ => freedesktop::import_metainfo(path),
+                    };
+                    match imported {
+                        Ok(imported) => {
+                            form_clone.apply_imported_metadata(&imported);
+                            form_clone.show_imported_files();
+                            gettext_f("Imported: {path}", &[("path", &path.display())])
+                        }
+                        Err(err) => gettext_f("Error importing: {error}", &[("error", &err)]),
+                    }
+                }
+                DroppedKind::Recipe => {
+                    load_recipe_clone(path);
+                    return;
+                }
+            };
+            let toast = Toast::new(&message);
+            toast.set_timeout(4);
+            toast_clone.add_toast(toast);
+        })
+    };
+    connect_drop_target(&window_box, &DroppedKind::ALL, apply_dropped.clone());
+    connect_drop_target(&binary_row, &[DroppedKind::Binary], apply_dropped.clone());
+    connect_drop_target(&icon_row, &[DroppedKind::Icon], apply_dropped.clone());
+    connect_drop_target(&output_row, &[DroppedKind::OutputFolder], apply_dropped.clone());
+    connect_drop_target(
+        &imported_files_row,
+        &[DroppedKind::MetadataFile, DroppedKind::AppDir],
+        apply_dropped.clone(),
+    );
+
+    // Ação do botão gerar: cada clique enfileira uma geração independente
+    {
+        let state_clone = app_state.clone();
+        let build_queue_clone = build_queue.clone();
+        let window_clone = window.clone();
+        let settings_clone = settings.clone();
+
+        generate_button.set_action_name(Some("win.generate"));
+        generate_action.connect_activate(move |_, _| {
+            let state_data = state_clone.borrow().clone();
+            let metadata_data = &state_data.metadata;
+
+            // Validar pasta de saída primeiro
+            if state_data.output_folder.is_none() {
+                show_build_error(
+                    &BuildError::invalid_input(InputField::OutputFolder, gettext("Select the output folder!")),
+                    None,
+                );
+                return;
+            }
+
+            // Validação dos campos obrigatórios e das opções
+            if let Err(err) = appimage::validate_metadata(metadata_data).and_then(|()| state_data.options.validate()) {
+                show_build_error(&err, None);
+                return;
+            }
+            {
+                let mut settings = settings_clone.borrow_mut();
+                if remember_form_defaults(&mut settings, metadata_data) {
+                    settings.save_quietly();
+                }
+            }
+
+            // Construir caminho de saída a partir do modelo do nome
+            let output_folder = state_data.output_folder.clone().unwrap();
+            let output_path = match state_data.options.output_path(&output_folder, metadata_data) {
+                Ok(path) => path,
+                Err(err) => {
+                    show_build_error(&err, None);
+                    return;
+                }
+            };
+
+            // Duas gerações gravando o mesmo arquivo se sobrescreveriam
+            if build_queue_clone.borrow().is_building(&output_path) {
+                let toast = Toast::new(&gettext_f(
+                    "A build is already running for {path}",
+                    &[("path", &output_path.display())],
+                ));
+                toast.set_timeout(5);
+                build_queue_clone.borrow().toast_overlay.add_toast(toast);
+                return;
+            }
+
+            // Sem política definida, perguntar antes de substituir uma geração anterior
+            let existing = appimage::existing_outputs(&output_path, state_data.options.output_mode);
+            if state_data.options.if_exists != ExistingOutput::Fail || existing.is_empty() {
+                enqueue_build(&build_queue_clone, metadata_data.clone(), output_path, state_data.options.clone());
+                return;
+            }
+
+            let body = existing
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            let dialog = adw::MessageDialog::new(
+                Some(&window_clone),
+                Some(gettext("Replace the previous build?")),
+                Some(&gettext_f("Already exists:\n{paths}", &[("paths", &body)])),
+            );
+            dialog.add_response("cancel", gettext("Cancel"));
+            dialog.add_response("increment", gettext("Number it"));
+            dialog.add_response("backup", gettext("Keep backup"));
+            dialog.add_response("overwrite", gettext("Replace"));
+            dialog.set_response_appearance("overwrite", adw::ResponseAppearance::Destructive);
+            dialog.set_default_response(Some("increment"));
+            dialog.set_close_response("cancel");
+
+            let build_queue = build_queue_clone.clone();
+            dialog.connect_response(None, move |_, response| {
+                let Some(policy) = ExistingOutput::from_id(response) else {
+                    return;
+                };
+                let options = BuildOptions {
+                    if_exists: policy,
+                    ..state_data.options.clone()
+                };
+                enqueue_build(&build_queue, state_data.metadata.clone(), output_path.clone(), options);
+            });
+            dialog.present();
         });
     }
 
     window.present();
 }
 
+// O que um arquivo solto sobre a janela preenche
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DroppedKind {
+    Binary,
+    Icon,
+    OutputFolder,
+    AppDir,
+    MetadataFile,
+    Recipe,
+}
+
+// Formatos de ícone convertidos para PNG na geração
+const ICON_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "ico", "bmp"];
+
+impl DroppedKind {
+    const ALL: [DroppedKind; 6] = [
+        DroppedKind::Binary,
+        DroppedKind::Icon,
+        DroppedKind::OutputFolder,
+        DroppedKind::AppDir,
+        DroppedKind::MetadataFile,
+        DroppedKind::Recipe,
+    ];
+
+    // Pastas com AppRun são AppDirs; arquivos sem extensão conhecida, binários
+    fn of(path: &Path) -> Option<Self> {
+        if path.is_dir() {
+            return Some(if path.join("AppRun").is_file() {
+                DroppedKind::AppDir
+            } else {
+                DroppedKind::OutputFolder
+            });
+        }
+        if !path.is_file() {
+            return None;
+        }
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        let extension = path.extension().map(|ext| ext.to_string_lossy().to_lowercase());
+        Some(match extension.as_deref() {
+            Some("toml") => DroppedKind::Recipe,
+            Some("desktop") => DroppedKind::MetadataFile,
+            Some("xml") if name.ends_with(".metainfo.xml") || name.ends_with(".appdata.xml") => {
+                DroppedKind::MetadataFile
+            }
+            Some(ext) if ICON_EXTENSIONS.contains(&ext) => DroppedKind::Icon,
+            _ => DroppedKind::Binary,
+        })
+    }
+}
+
+// Alvo de arrastar e soltar que realça o widget enquanto arquivos passam sobre ele
+fn connect_drop_target(
+    widget: &impl IsA<Widget>,
+    accepts: &'static [DroppedKind],
+    on_drop: ApplyDropped,
+) {
+    let target = DropTarget::new(FileList::static_type(), DragAction::COPY);
+    {
+        let widget = widget.clone().upcast::<Widget>();
+        target.connect_enter(move |_, _, _| {
+            widget.add_css_class("drop-hover");
+            DragAction::COPY
+        });
+    }
+    {
+        let widget = widget.clone().upcast::<Widget>();
+        target.connect_leave(move |_| widget.remove_css_class("drop-hover"));
+    }
+    {
+        let widget = widget.clone().upcast::<Widget>();
+        target.connect_drop(move |_, value, _, _| {
+            widget.remove_css_class("drop-hover");
+            let Ok(files) = value.get::<FileList>() else {
+                return false;
+            };
+            let dropped: Vec<(PathBuf, DroppedKind)> = files
+                .files()
+                .iter()
+                .filter_map(|file| file.path())
+                .filter_map(|path| DroppedKind::of(&path).map(|kind| (path, kind)))
+                .filter(|(_, kind)| accepts.contains(kind))
+                .collect();
+            for (path, kind) in &dropped {
+                on_drop(path, *kind);
+            }
+            !dropped.is_empty()
+        });
+    }
+    widget.add_controller(target);
+}
+
+fn primary_menu() -> gio::Menu {
+    let menu = gio::Menu::new();
+    let sections: [&[(&str, &str)]; 4] = [
+        &[
+            (gettext("Open recipe..."), "win.open-recipe"),
+            (gettext("Save recipe"), "win.save-recipe"),
+            (gettext("Save recipe as..."), "win.save-recipe-as"),
+        ],
+        &[
+            (gettext("Undo"), "win.undo"),
+            (gettext("Redo"), "win.redo"),
+        ],
+        &[
+            (gettext("Generate AppImage"), "win.generate"),
+            (gettext("Cancel builds"), "win.cancel"),
+            (gettext("Inspect AppImage..."), "win.inspect-appimage"),
+            (gettext("Check environment"), "win.check-environment"),
+        ],
+        &[
+            (gettext("Keyboard shortcuts"), "win.show-help-overlay"),
+            (gettext("Quit"), "app.quit"),
+        ],
+    ];
+    for items in sections {
+        let section = gio::Menu::new();
+        for (label, action) in items {
+            section.append(Some(label), Some(action));
+        }
+        menu.append_section(None, &section);
+    }
+    menu
+}
+
+// Janela de atalhos (Ctrl+?) montada a partir de SHORTCUT_GROUPS
+fn build_shortcuts_window() -> gtk4::ShortcutsWindow {
+    let mut groups = String::new();
+    for (title, shortcuts) in SHORTCUT_GROUPS {
+        groups.push_str(&format!(
+            "<child><object class=\"GtkShortcutsGroup\"><property name=\"title\">{}</property>",
+            glib::markup_escape_text(gettext(title))
+        ));
+        for (action, title) in shortcuts {
+            groups.push_str(&format!(
+                "<child><object class=\"GtkShortcutsShortcut\">\
+                 <property name=\"action-name\">{}</property>\
+                 <property name=\"title\">{}</property></object></child>",
+                action,
+                glib::markup_escape_text(gettext(title))
+            ));
+        }
+        groups.push_str("</object></child>");
+    }
+    let ui = format!(
+        "<interface><object class=\"GtkShortcutsWindow\" id=\"shortcuts\">\
+         <property name=\"modal\">true</property>\
+         <child><object class=\"GtkShortcutsSection\">\
+         <property name=\"section-name\">main</property>{}</object></child>\
+         </object></interface>",
+        groups
+    );
+    gtk4::Builder::from_string(&ui)
+        .object("shortcuts")
+        .expect("failed to build the shortcuts window")
+}
+
+// Título com o nome da receita e "•" quando há alterações não salvas
+fn update_window_title(title: &WindowTitle, recipe_path: Option<&Path>, dirty: bool) {
+    title.set_title(&if dirty { format!("• {}", APP_TITLE) } else { APP_TITLE.to_string() });
+    let recipe_name = recipe_path
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().to_string());
+    title.set_subtitle(&recipe_name.unwrap_or_default());
+}
+
+// Marca de campo herdado do perfil, visível só enquanto o valor for o do perfil
+fn new_profile_indicator() -> Image {
+    let indicator = Image::from_icon_name("avatar-default-symbolic");
+    indicator.add_css_class("dim-label");
+    indicator.set_valign(Align::Center);
+    indicator.set_visible(false);
+    indicator
+}
+
+// Seletores abrem na última pasta usada para o mesmo tipo de arquivo
+fn start_in_folder(dialog: &FileChooserDialog, folder: Option<&Path>) {
+    if let Some(folder) = folder.filter(|folder| folder.is_dir()) {
+        let _ = dialog.set_current_folder(Some(&gio::File::for_path(folder)));
+    }
+}
+
+fn remember_form_defaults(settings: &mut Settings, metadata: &AppImageMetadata) -> bool {
+    settings.remember_defaults(&metadata.author, &metadata.license, &metadata.website)
+}
+
+fn recipe_file_filter() -> FileFilter {
+    let filter = FileFilter::new();
+    filter.set_name(Some(gettext("Recipes (*.toml)")));
+    filter.add_pattern("*.toml");
+    filter
+}
+
 fn connect_entry_to_state<F>(
     entry: &Entry,
     state: Rc<RefCell<AppState>>,
@@ -1083,6 +3001,593 @@ where
     });
 }
 
+fn new_build_job_row(name: &str, output_path: &Path) -> BuildJobRow {
+    let row = ExpanderRow::new();
+    row.set_title(&glib::markup_escape_text(name));
+    row.set_subtitle(gettext("Queued"));
+    row.set_tooltip_text(Some(&output_path.to_string_lossy()));
+
+    let progress_bar = ProgressBar::new();
+    progress_bar.add_css_class("compact-progress");
+    progress_bar.set_valign(Align::Center);
+    progress_bar.set_width_request(120);
+    row.add_action(&progress_bar);
+
+    let cancel_button = Button::from_icon_name("process-stop-symbolic");
+    cancel_button.set_tooltip_text(Some(gettext("Cancel")));
+    cancel_button.add_css_class("flat");
+    cancel_button.set_valign(Align::Center);
+    row.add_action(&cancel_button);
+
+    let test_button = Button::from_icon_name("media-playback-start-symbolic");
+    test_button.set_tooltip_text(Some(gettext("Test: runs the generated AppImage and shows its output")));
+    test_button.add_css_class("flat");
+    test_button.set_valign(Align::Center);
+    test_button.set_visible(false);
+    row.add_action(&test_button);
+
+    let remove_button = Button::from_icon_name("window-close-symbolic");
+    remove_button.set_tooltip_text(Some(gettext("Remove from list")));
+    remove_button.add_css_class("flat");
+    remove_button.set_valign(Align::Center);
+    remove_button.set_visible(false);
+    row.add_action(&remove_button);
+
+    // Log expansível com as etapas da geração
+    let log_view = TextView::new();
+    log_view.set_editable(false);
+    log_view.set_cursor_visible(false);
+    log_view.set_monospace(true);
+    log_view.set_wrap_mode(WrapMode::WordChar);
+    log_view.set_margin_top(6);
+    log_view.set_margin_bottom(6);
+    log_view.set_margin_start(6);
+    log_view.set_margin_end(6);
+    let log_scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .vscrollbar_policy(gtk4::PolicyType::Automatic)
+        .min_content_height(160)
+        .child(&log_view)
+        .build();
+    row.add_row(&log_scrolled);
+
+    BuildJobRow {
+        row,
+        progress_bar,
+        cancel_button,
+        test_button,
+        remove_button,
+        log_view,
+    }
+}
+
+fn enqueue_build(
+    queue: &Rc<RefCell<BuildQueue>>,
+    metadata: AppImageMetadata,
+    output_path: PathBuf,
+    options: BuildOptions,
+) {
+    let job = new_build_job_row(&metadata.name, &output_path);
+    let cancel = CancelFlag::default();
+
+    // Cancelar uma geração ainda na fila apenas a retira; em execução, sinaliza a thread,
+    // que encerra o processo externo e remove o diretório de trabalho
+    {
+        let queue_weak = Rc::downgrade(queue);
+        let cancel_clone = cancel.clone();
+        let row_clone = job.row.clone();
+        job.cancel_button.connect_clicked(move |button| {
+            cancel_clone.cancel();
+            button.set_sensitive(false);
+            row_clone.set_subtitle(gettext("Cancelling..."));
+
+            let Some(queue) = queue_weak.upgrade() else {
+                return;
+            };
+            let removed = {
+                let mut queue = queue.borrow_mut();
+                let position = queue.pending.iter().position(|build| build.job.row == row_clone);
+                position.and_then(|index| queue.pending.remove(index))
+            };
+            if let Some(build) = removed {
+                finish_build_row(&build.job, &Err(BuildError::Cancelled));
+                queue.borrow().update_status();
+            }
+        });
+    }
+
+    {
+        let queue_weak = Rc::downgrade(queue);
+        let row_clone = job.row.clone();
+        job.remove_button.connect_clicked(move |_| {
+            if let Some(queue) = queue_weak.upgrade() {
+                let queue = queue.borrow();
+                queue.list.remove(&row_clone);
+                queue.update_status();
+            }
+        });
+    }
+
+    {
+        let mut queue_mut = queue.borrow_mut();
+        queue_mut.list.append(&job.row);
+        queue_mut.cancel_buttons.retain(|button| button.is_visible());
+        queue_mut.cancel_buttons.push(job.cancel_button.clone());
+        queue_mut.pending.push_back(QueuedBuild {
+            metadata,
+            output_path,
+            options,
+            cancel,
+            job,
+        });
+    }
+
+    start_queued_builds(queue);
+}
+
+fn start_queued_builds(queue: &Rc<RefCell<BuildQueue>>) {
+    loop {
+        let build = {
+            let mut queue_mut = queue.borrow_mut();
+            if queue_mut.running.len() >= MAX_CONCURRENT_BUILDS {
+                break;
+            }
+            let Some(build) = queue_mut.pending.pop_front() else {
+                break;
+            };
+            queue_mut.running.push(build.output_path.clone());
+            build
+        };
+        run_build(queue, build);
+    }
+
+    queue.borrow().update_status();
+}
+
+fn run_build(queue: &Rc<RefCell<BuildQueue>>, build: QueuedBuild) {
+    let QueuedBuild {
+        metadata,
+        output_path,
+        options,
+        cancel,
+        job,
+    } = build;
+
+    job.row.set_subtitle(BuildStage::PreparingAppDir.label());
+    job.progress_bar.set_fraction(0.0);
+
+    let (sender, receiver) = unbounded::<BuildMessage>();
+    let thread_output_path = output_path.clone();
+    std::thread::spawn(move || {
+        let sender_for_events = sender.clone();
+        let result = appimage::generate_appimage(
+            &metadata,
+            &thread_output_path,
+            &options,
+            &move |event| {
+                let _ = sender_for_events.send_blocking(BuildMessage::Event(event));
+            },
+            &cancel,
+        );
+
+        let _ = sender.send_blocking(BuildMessage::Finished(result));
+    });
+
+    let queue = queue.clone();
+    glib::MainContext::default().spawn_local(async move {
+        let mut size_report = None;
+        while let Ok(message) = receiver.recv().await {
+            let result = match message {
+                BuildMessage::Event(BuildEvent::Log(record)) => {
+                    append_log_record(&job.log_view, &record);
+                    continue;
+                }
+                BuildMessage::Event(BuildEvent::SizeReport(report)) => {
+                    size_report = Some(report);
+                    continue;
+                }
+                BuildMessage::Event(event) => {
+                    if let Some(fraction) = event.overall_fraction() {
+                        job.progress_bar.set_fraction(fraction);
+                    }
+                    job.row.set_subtitle(&glib::markup_escape_text(&describe_build_event(&event)));
+                    continue;
+                }
+                BuildMessage::Finished(result) => result,
+            };
+
+            finish_build_row(&job, &result);
+            queue.borrow_mut().running.retain(|path| path != &output_path);
+
+            let (toast_overlay, show_build_error) = {
+                let queue = queue.borrow();
+                (queue.toast_overlay.clone(), queue.show_build_error.clone())
+            };
+            match result {
+                Ok(path) => {
+                    {
+                        let toast_overlay = toast_overlay.clone();
+                        let path = path.clone();
+                        let row = job.row.clone();
+                        job.test_button.connect_clicked(move |button| {
+                            test_appimage(&toast_overlay, button, &row, path.clone());
+                        });
+                    }
+                    let toast = match &size_report {
+                        Some(report) => {
+                            Toast::new(&gettext_f("Built successfully: {summary}", &[("summary", &report.summary())]))
+                        }
+                        None => Toast::new(&gettext_f(
+                            "AppImage built successfully at:\n{path}",
+                            &[("path", &path.display())],
+                        )),
+                    };
+                    if let Some(report) = size_report.take() {
+                        // Os detalhes trazem o relatório de tamanho e a opção de abrir a pasta
+                        toast.set_button_label(Some(gettext("Details")));
+                        let toast_overlay_for_details = toast_overlay.clone();
+                        toast.connect_button_clicked(move |_| {
+                            show_size_report(&toast_overlay_for_details, &path, &report);
+                        });
+                    } else if let Some(folder) = path.parent() {
+                        let folder_path = folder.to_path_buf();
+                        toast.set_button_label(Some(gettext("Open folder")));
+                        toast.connect_button_clicked(move |_| open_folder(&folder_path));
+                    }
+                    toast.set_timeout(8);
+                    toast_overlay.add_toast(toast);
+                }
+                Err(BuildError::Cancelled) => {
+                    let toast = Toast::new(gettext("Cancelled"));
+                    toast.set_timeout(3);
+                    toast_overlay.add_toast(toast);
+                }
+                Err(err) => show_build_error(&err, Some(&job.log_view)),
+            }
+
+            start_queued_builds(&queue);
+            break;
+        }
+    });
+}
+
+// Estado final da linha de uma geração
+fn finish_build_row(job: &BuildJobRow, result: &Result<PathBuf, BuildError>) {
+    let subtitle = match result {
+        Ok(path) => gettext_f("Done: {path}", &[("path", &path.display())]),
+        Err(BuildError::Cancelled) => gettext("Cancelled").to_string(),
+        Err(err) => gettext_f("Error: {error}", &[("error", err)]),
+    };
+    job.row.set_subtitle(&glib::markup_escape_text(&subtitle));
+    job.progress_bar.set_visible(false);
+    job.cancel_button.set_visible(false);
+    job.test_button.set_visible(result.as_ref().is_ok_and(|path| path.is_file()));
+    job.remove_button.set_visible(true);
+    if result.as_ref().is_err_and(|err| !matches!(err, BuildError::Cancelled)) {
+        job.row.add_css_class("error");
+        job.row.set_expanded(true);
+    }
+
+    let Err(err) = result else {
+        return;
+    };
+    if let Some(hint) = err.install_hint() {
+        let copy_button = Button::from_icon_name("edit-copy-symbolic");
+        copy_button.set_tooltip_text(Some(gettext("Copy command")));
+        copy_button.add_css_class("flat");
+        copy_button.set_valign(Align::Center);
+        copy_button.connect_clicked(move |button| button.clipboard().set_text(hint));
+        job.row.add_action(&copy_button);
+    }
+    if let Some(work_dir) = err.kept_work_dir() {
+        let work_dir = work_dir.to_path_buf();
+        let folder_button = Button::from_icon_name("folder-open-symbolic");
+        folder_button.set_tooltip_text(Some(gettext("Open folder")));
+        folder_button.add_css_class("flat");
+        folder_button.set_valign(Align::Center);
+        folder_button.connect_clicked(move |_| open_folder(&work_dir));
+        job.row.add_action(&folder_button);
+    }
+}
+
+// Relatório de tamanho de uma geração concluída, com os maiores arquivos do AppDir
+fn show_size_report(parent: &ToastOverlay, path: &Path, report: &SizeReport) {
+    let mut body = format!("{}\n\n{}", path.display(), report.summary());
+    if !report.largest.is_empty() {
+        body.push_str(&format!("\n\n{}", gettext("Largest files in the AppDir:")));
+        for line in report.largest_lines() {
+            body.push_str(&format!("\n{}", line.trim_start()));
+        }
+    }
+
+    let window = parent.root().and_downcast::<gtk4::Window>();
+    let dialog = adw::MessageDialog::new(window.as_ref(), Some(gettext("Size report")), Some(&body));
+    dialog.add_response("close", gettext("Close"));
+    dialog.add_response("open", gettext("Open folder"));
+    // Somente AppImages (não o AppDir do modo somente AppDir) podem ser integrados
+    if path.is_file() {
+        dialog.add_response("integrate", gettext("Integrate into the system"));
+    }
+    dialog.set_default_response(Some("close"));
+    let folder = path.parent().map(Path::to_path_buf);
+    let appimage = path.to_path_buf();
+    let toast_overlay = parent.clone();
+    dialog.connect_response(None, move |_, response| match response {
+        "open" => {
+            if let Some(folder) = &folder {
+                open_folder(folder);
+            }
+        }
+        "integrate" => integrate_appimage(&toast_overlay, appimage.clone()),
+        _ => {}
+    });
+    dialog.present();
+}
+
+// Execução de teste em segundo plano; o resultado abre em um diálogo
+fn test_appimage(toast_overlay: &ToastOverlay, button: &Button, row: &ExpanderRow, appimage: PathBuf) {
+    button.set_sensitive(false);
+    let previous_subtitle = row.subtitle();
+    row.set_subtitle(&glib::markup_escape_text(&gettext_f(
+        "Testing for up to {seconds} s...",
+        &[("seconds", &DEFAULT_TEST_TIMEOUT.as_secs())],
+    )));
+
+    let (sender, receiver) = unbounded::<Result<TestRun, BuildError>>();
+    std::thread::spawn(move || {
+        let result = appimage::test_appimage(&appimage, DEFAULT_TEST_TIMEOUT, false, &CancelFlag::default());
+        let _ = sender.send_blocking(result);
+    });
+
+    let toast_overlay = toast_overlay.clone();
+    let button = button.clone();
+    let row = row.clone();
+    glib::MainContext::default().spawn_local(async move {
+        if let Ok(result) = receiver.recv().await {
+            match result {
+                Ok(run) => show_test_report(&toast_overlay, &run),
+                Err(err) => toast_overlay.add_toast(Toast::new(&gettext_f("Test failed to run: {error}", &[("error", &err.describe())]))),
+            }
+        }
+        row.set_subtitle(&previous_subtitle);
+        button.set_sensitive(true);
+    });
+}
+
+fn show_test_report(parent: &ToastOverlay, run: &TestRun) {
+    let mut body = format!("{}\n\n{}", run.command, run.summary());
+    if !run.missing_libraries.is_empty() {
+        body.push_str(&format!(
+            "\n\n{}",
+            gettext("Missing libraries (include them in the AppDir, under usr/lib):")
+        ));
+        for library in &run.missing_libraries {
+            body.push_str(&format!("\n{}", library));
+        }
+    }
+
+    let window = parent.root().and_downcast::<gtk4::Window>();
+    let heading = if run.passed() { gettext("Test passed") } else { gettext("Test failed") };
+    let dialog = adw::MessageDialog::new(window.as_ref(), Some(heading), Some(&body));
+
+    let output = run.output();
+    if !output.is_empty() {
+        let output_view = TextView::new();
+        output_view.set_editable(false);
+        output_view.set_cursor_visible(false);
+        output_view.set_monospace(true);
+        output_view.set_wrap_mode(WrapMode::WordChar);
+        output_view.buffer().set_text(&output);
+        let output_scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk4::PolicyType::Never)
+            .vscrollbar_policy(gtk4::PolicyType::Automatic)
+            .min_content_height(200)
+            .child(&output_view)
+            .build();
+        dialog.set_extra_child(Some(&output_scrolled));
+        dialog.add_response("copy", gettext("Copy output"));
+    }
+    if !run.missing_libraries.is_empty() {
+        dialog.add_response("copy-libraries", gettext("Copy libraries"));
+    }
+    dialog.add_response("close", gettext("Close"));
+    dialog.set_default_response(Some("close"));
+
+    let libraries = run.missing_libraries.join("\n");
+    dialog.connect_response(None, move |dialog, response| match response {
+        "copy" => dialog.clipboard().set_text(&output),
+        "copy-libraries" => dialog.clipboard().set_text(&libraries),
+        _ => {}
+    });
+    dialog.present();
+}
+
+// Leitura do AppImage em segundo plano (o MD5 e a extração do .desktop podem demorar)
+fn inspect_appimage(toast_overlay: &ToastOverlay, appimage: PathBuf) {
+    let (sender, receiver) = unbounded::<Result<AppImageInfo, BuildError>>();
+    std::thread::spawn(move || {
+        let _ = sender.send_blocking(appimage::inspect_appimage(&appimage));
+    });
+
+    let toast_overlay = toast_overlay.clone();
+    glib::MainContext::default().spawn_local(async move {
+        match receiver.recv().await {
+            Ok(Ok(info)) => show_appimage_info(&toast_overlay, &info),
+            Ok(Err(err)) => toast_overlay.add_toast(Toast::new(&gettext_f("Failed to inspect: {error}", &[("error", &err.describe())]))),
+            Err(_) => {}
+        }
+    });
+}
+
+fn show_appimage_info(toast_overlay: &ToastOverlay, info: &AppImageInfo) {
+    let window = toast_overlay.root().and_downcast::<gtk4::Window>();
+    let heading = info
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "AppImage".to_string());
+    let dialog = adw::MessageDialog::new(window.as_ref(), Some(&heading), Some(&info.summary()));
+
+    if let Ok(desktop_entry) = &info.desktop_entry {
+        let desktop_view = TextView::new();
+        desktop_view.set_editable(false);
+        desktop_view.set_cursor_visible(false);
+        desktop_view.set_monospace(true);
+        desktop_view.set_wrap_mode(WrapMode::WordChar);
+        desktop_view.buffer().set_text(desktop_entry);
+        let desktop_scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk4::PolicyType::Never)
+            .vscrollbar_policy(gtk4::PolicyType::Automatic)
+            .min_content_height(200)
+            .child(&desktop_view)
+            .build();
+        dialog.set_extra_child(Some(&desktop_scrolled));
+    }
+    if info.appimage_type.is_some() {
+        dialog.add_response("integrate", gettext("Integrate into the system"));
+    }
+    dialog.add_response("copy", gettext("Copy MD5"));
+    dialog.add_response("close", gettext("Close"));
+    dialog.set_default_response(Some("close"));
+    dialog.set_close_response("close");
+
+    let toast_overlay = toast_overlay.clone();
+    let path = info.path.clone();
+    let md5 = info.md5.clone();
+    dialog.connect_response(None, move |dialog, response| match response {
+        "integrate" => integrate_appimage(&toast_overlay, path.clone()),
+        "copy" => dialog.clipboard().set_text(&md5),
+        _ => {}
+    });
+    dialog.present();
+}
+
+// Integração ao menu em segundo plano (a cópia para ~/Applications pode demorar),
+// com "Desfazer" no toast de sucesso
+fn integrate_appimage(toast_overlay: &ToastOverlay, appimage: PathBuf) {
+    let (sender, receiver) = unbounded::<Result<IntegrationDirs, BuildError>>();
+    let source = appimage.clone();
+    std::thread::spawn(move || {
+        let result = IntegrationDirs::for_user().and_then(|dirs| appimage::integrate(&source, &dirs).map(|_| dirs));
+        let _ = sender.send_blocking(result);
+    });
+
+    let toast_overlay = toast_overlay.clone();
+    glib::MainContext::default().spawn_local(async move {
+        let Ok(result) = receiver.recv().await else {
+            return;
+        };
+        let toast = match result {
+            Ok(dirs) => {
+                let toast = Toast::new(gettext("Integrated into the applications menu"));
+                toast.set_button_label(Some(gettext("Undo")));
+                let toast_overlay_for_undo = toast_overlay.clone();
+                toast.connect_button_clicked(move |_| {
+                    // Pelo caminho original, para remover também a cópia em ~/Applications
+                    let message = match appimage::remove_integration(&appimage, &dirs) {
+                        Ok(_) => gettext("Integration removed").to_string(),
+                        Err(err) => gettext_f("Failed to remove the integration: {error}", &[("error", &err.describe())]),
+                    };
+                    toast_overlay_for_undo.add_toast(Toast::new(&message));
+                });
+                toast
+            }
+            Err(err) => Toast::new(&gettext_f("Failed to integrate: {error}", &[("error", &err.describe())])),
+        };
+        toast.set_timeout(8);
+        toast_overlay.add_toast(toast);
+    });
+}
+
+fn show_preflight_report(window: &ApplicationWindow, report: &PreflightReport) {
+    let mut body = format!("{}\n", gettext_f("Build: {pipeline}", &[("pipeline", &report.pipeline)]));
+    for check in &report.checks {
+        let requirement = if check.required { "" } else { gettext(" (optional)") };
+        body.push_str(&format!("\n{} {}{}: {}", check.symbol(), check.name, requirement, check.status()));
+        if !check.is_ok() {
+            body.push_str(&format!(
+                "\n    {}\n    {}",
+                check.purpose,
+                gettext_f("To install: {hint}", &[("hint", &check.install_hint)])
+            ));
+        }
+    }
+
+    let heading = if report.is_ready() {
+        gettext("Environment ready to build")
+    } else {
+        gettext("Tools are missing to build")
+    };
+    let dialog = adw::MessageDialog::new(Some(window), Some(heading), Some(&body));
+    dialog.add_response("copy", gettext("Copy report"));
+    dialog.add_response("close", gettext("Close"));
+    dialog.set_default_response(Some("close"));
+    dialog.set_close_response("close");
+    let window_clone = window.clone();
+    dialog.connect_response(None, move |_, response| {
+        if response == "copy" {
+            window_clone.clipboard().set_text(&body);
+        }
+    });
+    dialog.present();
+}
+
+fn open_folder(folder: &Path) {
+    let file = gio::File::for_path(folder);
+    if let Err(err) = gio::AppInfo::launch_default_for_uri(&file.uri(), None::<&gio::AppLaunchContext>) {
+        eprintln!("{}", gettext_f("Failed to open folder: {error}", &[("error", &err)]));
+    }
+}
+
+fn describe_build_event(event: &BuildEvent) -> String {
+    match event {
+        BuildEvent::StageStarted(stage) => format!("{}...", stage.label()),
+        BuildEvent::StageFinished(stage) => format!("{} ✓", stage.label()),
+        BuildEvent::Progress {
+            stage,
+            processed,
+            total,
+        } => gettext_f(
+            "{stage}: {processed} of ~{total}",
+            &[
+                ("stage", &stage.label()),
+                ("processed", &format_size(*processed)),
+                ("total", &format_size(*total)),
+            ],
+        ),
+        BuildEvent::Log(record) => record.message.clone(),
+        BuildEvent::SizeReport(report) => report.summary(),
+    }
+}
+
+fn append_log_record(view: &TextView, record: &LogRecord) {
+    let buffer = view.buffer();
+    let tag_name = match record.level {
+        LogLevel::Info => None,
+        LogLevel::Warning => Some(("log-warning", "#e5a50a")),
+        LogLevel::Error => Some(("log-error", "#e01b24")),
+        LogLevel::Output => Some(("log-output", "#77767b")),
+    };
+
+    let mut end = buffer.end_iter();
+    let line = format!("{}\n", record.format_line());
+    match tag_name {
+        Some((name, color)) => {
+            if buffer.tag_table().lookup(name).is_none() {
+                let tag = TextTag::builder().name(name).foreground(color).build();
+                buffer.tag_table().add(&tag);
+            }
+            buffer.insert_with_tags_by_name(&mut end, &line, &[name]);
+        }
+        None => buffer.insert(&mut end, &line),
+    }
+
+    // Manter a última linha visível
+    let mark = buffer.create_mark(None, &buffer.end_iter(), false);
+    view.scroll_mark_onscreen(&mark);
+    buffer.delete_mark(&mark);
+}
+
 fn add_prefix_icon_to_action_row(row: &ActionRow, emoji: &str) {
     let icon_label = Label::new(Some(emoji));
     icon_label.add_css_class("dim-label");
@@ -1110,23 +3615,6 @@ fn set_widget_validation<W: gtk4::prelude::WidgetExt>(widget: &W, is_valid: bool
     }
 }
 
-fn format_size(bytes: u64) -> String {
-    const KB: f64 = 1024.0;
-    const MB: f64 = KB * 1024.0;
-    const GB: f64 = MB * 1024.0;
-
-    let size = bytes as f64;
-    if size >= GB {
-        format!("{:.2} GB", size / GB)
-    } else if size >= MB {
-        format!("{:.1} MB", size / MB)
-    } else if size >= KB {
-        format!("{:.0} KB", size / KB)
-    } else {
-        format!("{} B", bytes)
-    }
-}
-
 fn format_duration(seconds: u64) -> String {
     if seconds >= 60 {
         let minutes = seconds / 60;
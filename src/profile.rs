@@ -0,0 +1,239 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::appimage::{BackendKind, Compression, PackageFormat};
+use crate::i18n::{gettext, gettext_f};
+use crate::recipe::Recipe;
+
+// Perfil: valores comuns a vários aplicativos (autor, licença, site, prefixo do
+// App ID, pasta de saída e opções da geração), gravados em TOML na pasta de
+// perfis. Preenche apenas o que a receita ou o formulário deixaram sem valor
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Profile {
+    // Nome do arquivo, sem a extensão
+    #[serde(skip)]
+    pub(crate) name: String,
+    pub(crate) author: String,
+    pub(crate) license: String,
+    pub(crate) website: String,
+    // Ex: "org.exemplo" gera org.exemplo.<pacote> quando o App ID não é informado
+    pub(crate) app_id_prefix: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) output_folder: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) backend: Option<BackendKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) file_name: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) extra_formats: Vec<PackageFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) compression: Option<Compression>,
+}
+
+// Campos que um perfil pode preencher, para indicar na interface o que foi herdado
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProfileField {
+    Author,
+    License,
+    Website,
+    AppIdPrefix,
+    OutputFolder,
+    Backend,
+    FileName,
+    ExtraFormats,
+    Compression,
+}
+
+impl ProfileField {
+    pub(crate) const ALL: [ProfileField; 9] = [
+        ProfileField::Author,
+        ProfileField::License,
+        ProfileField::Website,
+        ProfileField::AppIdPrefix,
+        ProfileField::OutputFolder,
+        ProfileField::Backend,
+        ProfileField::FileName,
+        ProfileField::ExtraFormats,
+        ProfileField::Compression,
+    ];
+}
+
+impl Profile {
+    // $XDG_CONFIG_HOME/appimage-creator/profiles
+    pub(crate) fn dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("appimage-creator").join("profiles"))
+    }
+
+    pub(crate) fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let mut profile: Profile = toml::from_str(&content)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        profile.name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        // "~/" parte da pasta pessoal; caminhos relativos, da pasta do perfil
+        if let Some(folder) = profile.output_folder.take() {
+            let base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            profile.output_folder = Some(match (folder.strip_prefix("~"), dirs::home_dir()) {
+                (Ok(rest), Some(home)) => home.join(rest),
+                _ if folder.is_relative() => base.join(folder),
+                _ => folder,
+            });
+        }
+        Ok(profile)
+    }
+
+    // Um nome procura <pasta de perfis>/<nome>.toml; um caminho é lido diretamente
+    pub(crate) fn find(name: &str) -> io::Result<Self> {
+        if name.contains('/') || name.ends_with(".toml") {
+            return Self::load(Path::new(name));
+        }
+        let dir = Self::dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, gettext("configuration folder not found")))?;
+        let path = dir.join(format!("{}.toml", name));
+        if !path.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                gettext_f("profile {name} not found in {path}", &[("name", &name), ("path", &dir.display())]),
+            ));
+        }
+        Self::load(&path)
+    }
+
+    // Perfis da pasta de perfis, em ordem de nome; os inválidos são ignorados
+    pub(crate) fn list() -> Vec<Self> {
+        let Some(entries) = Self::dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+            return Vec::new();
+        };
+        let mut profiles: Vec<Profile> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .filter_map(|path| match Self::load(&path) {
+                Ok(profile) => Some(profile),
+                Err(err) => {
+                    eprintln!(
+                        "{}",
+                        gettext_f("Invalid profile in {path}: {error}", &[("path", &path.display()), ("error", &err)])
+                    );
+                    None
+                }
+            })
+            .collect();
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        profiles
+    }
+
+    // Preenche os campos que a receita deixou sem valor
+    pub(crate) fn fill(&self, recipe: &mut Recipe) {
+        let app = &mut recipe.app;
+        let text_fields = [
+            (&mut app.author, &self.author),
+            (&mut app.license, &self.license),
+            (&mut app.website, &self.website),
+            (&mut app.app_id_prefix, &self.app_id_prefix),
+        ];
+        for (field, value) in text_fields {
+            if field.trim().is_empty() && !value.trim().is_empty() {
+                *field = value.trim().to_string();
+            }
+        }
+
+        if recipe.output_folder.is_none() {
+            recipe.output_folder = self.output_folder.clone();
+        }
+        let build = &mut recipe.build;
+        if build.backend == BackendKind::Auto {
+            build.backend = self.backend.unwrap_or_default();
+        }
+        if build.file_name.is_none() {
+            build.file_name = self.file_name.clone();
+        }
+        if build.extra_formats.is_empty() {
+            build.extra_formats = self.extra_formats.clone();
+        }
+        if build.compression.is_none() {
+            build.compression = self.compression;
+        }
+    }
+
+    // Desfaz `fill`: campos com o valor deste perfil voltam a ficar sem valor
+    pub(crate) fn clear(&self, recipe: &mut Recipe) {
+        for field in self.inherited(recipe) {
+            let (app, build) = (&mut recipe.app, &mut recipe.build);
+            match field {
+                ProfileField::Author => app.author.clear(),
+                ProfileField::License => app.license.clear(),
+                ProfileField::Website => app.website.clear(),
+                ProfileField::AppIdPrefix => app.app_id_prefix.clear(),
+                ProfileField::OutputFolder => recipe.output_folder = None,
+                ProfileField::Backend => build.backend = BackendKind::Auto,
+                ProfileField::FileName => build.file_name = None,
+                ProfileField::ExtraFormats => build.extra_formats.clear(),
+                ProfileField::Compression => build.compression = None,
+            }
+        }
+    }
+
+    // Campos da receita iguais ao valor definido neste perfil
+    pub(crate) fn inherited(&self, recipe: &Recipe) -> Vec<ProfileField> {
+        let (app, build) = (&recipe.app, &recipe.build);
+        let same_text = |value: &str, profile_value: &str| {
+            !profile_value.trim().is_empty() && value.trim() == profile_value.trim()
+        };
+        ProfileField::ALL
+            .into_iter()
+            .filter(|field| match field {
+                ProfileField::Author => same_text(&app.author, &self.author),
+                ProfileField::License => same_text(&app.license, &self.license),
+                ProfileField::Website => same_text(&app.website, &self.website),
+                ProfileField::AppIdPrefix => same_text(&app.app_id_prefix, &self.app_id_prefix),
+                ProfileField::OutputFolder => {
+                    self.output_folder.is_some() && recipe.output_folder == self.output_folder
+                }
+                ProfileField::Backend => self
+                    .backend
+                    .is_some_and(|backend| backend != BackendKind::Auto && build.backend == backend),
+                ProfileField::FileName => self.file_name.is_some() && build.file_name == self.file_name,
+                ProfileField::ExtraFormats => {
+                    !self.extra_formats.is_empty() && build.extra_formats == self.extra_formats
+                }
+                ProfileField::Compression => self.compression.is_some() && build.compression == self.compression,
+            })
+            .collect()
+    }
+
+    // Resumo para a interface: os valores que o perfil define
+    pub(crate) fn describe(&self) -> String {
+        let mut parts: Vec<String> = [
+            (gettext("Author"), &self.author),
+            (gettext("License"), &self.license),
+            (gettext("Website"), &self.website),
+            (gettext("App ID prefix"), &self.app_id_prefix),
+        ]
+        .into_iter()
+        .filter(|(_, value)| !value.trim().is_empty())
+        .map(|(label, value)| format!("{}: {}", label, value.trim()))
+        .collect();
+        if let Some(folder) = &self.output_folder {
+            parts.push(gettext_f("Output: {path}", &[("path", &folder.display())]));
+        }
+        if let Some(backend) = self.backend {
+            parts.push(gettext_f("Backend: {backend}", &[("backend", &backend.label())]));
+        }
+        if let Some(compression) = &self.compression {
+            parts.push(gettext_f("Compression: {compression}", &[("compression", &compression.describe())]));
+        }
+        if parts.is_empty() {
+            gettext("No values set").to_string()
+        } else {
+            parts.join("\n")
+        }
+    }
+}
@@ -0,0 +1,514 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+use toml::Value as TomlValue;
+
+// Quantos diretórios subir a partir do binário procurando um manifesto
+const MAX_SEARCH_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ManifestKind {
+    Cargo,
+    PackageJson,
+    PyProject,
+    Meson,
+}
+
+impl ManifestKind {
+    const ALL: [ManifestKind; 4] = [
+        ManifestKind::Cargo,
+        ManifestKind::PackageJson,
+        ManifestKind::PyProject,
+        ManifestKind::Meson,
+    ];
+
+    fn file_name(self) -> &'static str {
+        match self {
+            ManifestKind::Cargo => "Cargo.toml",
+            ManifestKind::PackageJson => "package.json",
+            ManifestKind::PyProject => "pyproject.toml",
+            ManifestKind::Meson => "meson.build",
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            ManifestKind::Cargo => "Cargo",
+            ManifestKind::PackageJson => "npm",
+            ManifestKind::PyProject => "Python",
+            ManifestKind::Meson => "Meson",
+        }
+    }
+}
+
+// Metadados lidos do manifesto do projeto que contém o binário
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ProjectManifest {
+    pub(crate) kind: ManifestKind,
+    pub(crate) path: PathBuf,
+    pub(crate) name: String,
+    pub(crate) version: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) authors: Vec<String>,
+    pub(crate) license: Option<String>,
+    pub(crate) homepage: Option<String>,
+}
+
+// Sobe a partir do diretório do binário procurando Cargo.toml, package.json,
+// pyproject.toml ou meson.build e retorna o primeiro manifesto reconhecido
+pub(crate) fn detect_project(binary_path: &Path) -> Option<ProjectManifest> {
+    let binary_stem = binary_path.file_stem()?.to_str()?.to_string();
+    let start = binary_path.parent()?;
+
+    for dir in start.ancestors().take(MAX_SEARCH_DEPTH) {
+        for kind in ManifestKind::ALL {
+            let candidate = dir.join(kind.file_name());
+            if !candidate.is_file() {
+                continue;
+            }
+
+            if let Some(manifest) = read_manifest(kind, &candidate, &binary_stem) {
+                return Some(manifest);
+            }
+        }
+    }
+
+    None
+}
+
+fn read_manifest(kind: ManifestKind, path: &Path, binary_stem: &str) -> Option<ProjectManifest> {
+    let content = fs::read_to_string(path).ok()?;
+
+    match kind {
+        ManifestKind::Cargo => parse_cargo(path, &content, binary_stem),
+        ManifestKind::PackageJson => parse_package_json(path, &content),
+        ManifestKind::PyProject => parse_pyproject(path, &content),
+        ManifestKind::Meson => parse_meson(path, &content),
+    }
+}
+
+fn parse_cargo(path: &Path, content: &str, binary_stem: &str) -> Option<ProjectManifest> {
+    let root: TomlValue = toml::from_str(content).ok()?;
+    let workspace_package = root.get("workspace").and_then(|w| w.get("package"));
+    let root_dir = path.parent()?;
+
+    // O pacote da raiz só vale se gerar o binário; senão, procurar nos membros
+    if let Some(package) = root.get("package") {
+        if cargo_produces_binary(root_dir, &root, package, binary_stem) {
+            return cargo_manifest_from_package(path, package, workspace_package);
+        }
+    }
+
+    let members = root
+        .get("workspace")
+        .and_then(|w| w.get("members"))
+        .and_then(|m| m.as_array())?;
+
+    for member in members.iter().filter_map(|m| m.as_str()) {
+        for member_dir in expand_member(root_dir, member) {
+            let member_path = member_dir.join("Cargo.toml");
+            let Ok(member_content) = fs::read_to_string(&member_path) else {
+                continue;
+            };
+            let Ok(member_root) = toml::from_str::<TomlValue>(&member_content) else {
+                continue;
+            };
+            let Some(package) = member_root.get("package") else {
+                continue;
+            };
+
+            if cargo_produces_binary(&member_dir, &member_root, package, binary_stem) {
+                return cargo_manifest_from_package(&member_path, package, workspace_package);
+            }
+        }
+    }
+
+    None
+}
+
+fn expand_member(root_dir: &Path, member: &str) -> Vec<PathBuf> {
+    match member.strip_suffix("/*") {
+        Some(parent) => fs::read_dir(root_dir.join(parent))
+            .map(|entries| {
+                let mut dirs: Vec<PathBuf> = entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.is_dir())
+                    .collect();
+                dirs.sort();
+                dirs
+            })
+            .unwrap_or_default(),
+        None => vec![root_dir.join(member)],
+    }
+}
+
+// Binário com o nome do pacote, declarado em [[bin]] ou descoberto em src/bin/
+fn cargo_produces_binary(package_dir: &Path, root: &TomlValue, package: &TomlValue, binary_stem: &str) -> bool {
+    let normalize = |s: &str| s.replace('_', "-");
+    let wanted = normalize(binary_stem);
+
+    if package
+        .get("name")
+        .and_then(|n| n.as_str())
+        .is_some_and(|name| normalize(name) == wanted)
+    {
+        return true;
+    }
+
+    let declared = root
+        .get("bin")
+        .and_then(|b| b.as_array())
+        .map(|bins| {
+            bins.iter()
+                .filter_map(|bin| bin.get("name").and_then(|n| n.as_str()))
+                .any(|name| normalize(name) == wanted)
+        })
+        .unwrap_or(false);
+    if declared {
+        return true;
+    }
+
+    let autobins = package.get("autobins").and_then(|a| a.as_bool()).unwrap_or(true);
+    autobins
+        && fs::read_dir(package_dir.join("src/bin"))
+            .map(|entries| {
+                entries.filter_map(|e| e.ok()).any(|entry| {
+                    let path = entry.path();
+                    let name = if path.is_dir() && path.join("main.rs").is_file() {
+                        path.file_name()
+                    } else if path.extension().is_some_and(|ext| ext == "rs") {
+                        path.file_stem()
+                    } else {
+                        None
+                    };
+                    name.and_then(|n| n.to_str()).is_some_and(|name| normalize(name) == wanted)
+                })
+            })
+            .unwrap_or(false)
+}
+
+fn cargo_manifest_from_package(
+    path: &Path,
+    package: &TomlValue,
+    workspace_package: Option<&TomlValue>,
+) -> Option<ProjectManifest> {
+    // Campos podem ser herdados com `campo.workspace = true`
+    let field = |key: &str| -> Option<TomlValue> {
+        let value = package.get(key)?;
+        let inherited = value
+            .get("workspace")
+            .and_then(|w| w.as_bool())
+            .unwrap_or(false);
+        if inherited {
+            workspace_package?.get(key).cloned()
+        } else {
+            Some(value.clone())
+        }
+    };
+    let string_field = |key: &str| field(key).and_then(|v| v.as_str().map(str::to_string));
+
+    let name = package.get("name")?.as_str()?.to_string();
+    let authors = field("authors")
+        .and_then(|v| v.as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|a| a.as_str())
+        .map(strip_email)
+        .collect();
+
+    Some(ProjectManifest {
+        kind: ManifestKind::Cargo,
+        path: path.to_path_buf(),
+        name,
+        version: string_field("version"),
+        description: string_field("description"),
+        authors,
+        license: string_field("license"),
+        homepage: string_field("homepage").or_else(|| string_field("repository")),
+    })
+}
+
+fn parse_package_json(path: &Path, content: &str) -> Option<ProjectManifest> {
+    let root: JsonValue = serde_json::from_str(content).ok()?;
+    let string_at = |key: &str| root.get(key).and_then(|v| v.as_str()).map(str::to_string);
+
+    // Nomes com escopo (@org/app) usam apenas a parte final
+    let name = string_at("name")?;
+    let name = name.rsplit('/').next().unwrap_or(&name).to_string();
+
+    let mut authors = Vec::new();
+    if let Some(author) = root.get("author").and_then(json_person) {
+        authors.push(author);
+    }
+    if let Some(contributors) = root.get("contributors").and_then(|c| c.as_array()) {
+        authors.extend(contributors.iter().filter_map(json_person));
+    }
+
+    let repository = root.get("repository").and_then(|r| match r {
+        JsonValue::String(url) => Some(url.clone()),
+        other => other.get("url").and_then(|u| u.as_str()).map(str::to_string),
+    });
+
+    Some(ProjectManifest {
+        kind: ManifestKind::PackageJson,
+        path: path.to_path_buf(),
+        name,
+        version: string_at("version"),
+        description: string_at("description"),
+        authors,
+        license: string_at("license"),
+        homepage: string_at("homepage").or(repository),
+    })
+}
+
+fn json_person(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::String(person) => Some(strip_email(person)),
+        other => other.get("name").and_then(|n| n.as_str()).map(str::to_string),
+    }
+}
+
+fn parse_pyproject(path: &Path, content: &str) -> Option<ProjectManifest> {
+    let root: TomlValue = toml::from_str(content).ok()?;
+
+    // PEP 621 ([project]) tem precedência sobre [tool.poetry]
+    if let Some(project) = root.get("project") {
+        let string_at = |key: &str| project.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        let name = string_at("name")?;
+
+        let authors = project
+            .get("authors")
+            .and_then(|a| a.as_array())
+            .map(|list| {
+                list.iter()
+                    .filter_map(|a| a.get("name").and_then(|n| n.as_str()))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let license = project.get("license").and_then(|l| match l {
+            TomlValue::String(spdx) => Some(spdx.clone()),
+            other => other.get("text").and_then(|t| t.as_str()).map(str::to_string),
+        });
+
+        let homepage = project.get("urls").and_then(|urls| {
+            ["Homepage", "homepage", "Repository", "repository", "Source"]
+                .iter()
+                .find_map(|key| urls.get(*key).and_then(|u| u.as_str()))
+                .map(str::to_string)
+        });
+
+        return Some(ProjectManifest {
+            kind: ManifestKind::PyProject,
+            path: path.to_path_buf(),
+            name,
+            version: string_at("version"),
+            description: string_at("description"),
+            authors,
+            license,
+            homepage,
+        });
+    }
+
+    let poetry = root.get("tool").and_then(|t| t.get("poetry"))?;
+    let string_at = |key: &str| poetry.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    let authors = poetry
+        .get("authors")
+        .and_then(|a| a.as_array())
+        .map(|list| list.iter().filter_map(|a| a.as_str()).map(strip_email).collect())
+        .unwrap_or_default();
+
+    Some(ProjectManifest {
+        kind: ManifestKind::PyProject,
+        path: path.to_path_buf(),
+        name: string_at("name")?,
+        version: string_at("version"),
+        description: string_at("description"),
+        authors,
+        license: string_at("license"),
+        homepage: string_at("homepage").or_else(|| string_at("repository")),
+    })
+}
+
+fn parse_meson(path: &Path, content: &str) -> Option<ProjectManifest> {
+    // Apenas a chamada project(...) interessa; comentários são descartados
+    let source: String = content
+        .lines()
+        .map(strip_meson_comment)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let start = source.find("project(")? + "project(".len();
+    let call = meson_call_body(&source[start..])?;
+
+    let name = meson_strings(call).into_iter().next()?;
+    let version = meson_keyword(call, "version")
+        .and_then(|value| meson_strings(value).into_iter().next());
+    let license = meson_keyword(call, "license").and_then(|value| {
+        let licenses = meson_strings(value);
+        (!licenses.is_empty()).then(|| licenses.join(" AND "))
+    });
+
+    Some(ProjectManifest {
+        kind: ManifestKind::Meson,
+        path: path.to_path_buf(),
+        name,
+        version,
+        description: None,
+        authors: Vec::new(),
+        license,
+        homepage: None,
+    })
+}
+
+// "#" fora de strings inicia um comentário; dentro de 'texto' é literal
+fn strip_meson_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, ch) in line.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '\'' => in_string = !in_string,
+            '#' if !in_string => return &line[..index],
+            _ => {}
+        }
+    }
+
+    line
+}
+
+fn meson_call_body(source: &str) -> Option<&str> {
+    let mut depth = 1usize;
+    let mut in_string = false;
+
+    for (index, ch) in source.char_indices() {
+        match ch {
+            '\'' => in_string = !in_string,
+            '(' | '[' if !in_string => depth += 1,
+            ')' | ']' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&source[..index]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn meson_keyword<'a>(call: &'a str, keyword: &str) -> Option<&'a str> {
+    let mut search_from = 0;
+
+    while let Some(offset) = call[search_from..].find(keyword) {
+        let position = search_from + offset;
+        let rest = call[position + keyword.len()..].trim_start();
+        let preceded_by_word = call[..position]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_');
+
+        if !preceded_by_word {
+            if let Some(value) = rest.strip_prefix(':') {
+                let value = value.trim_start();
+                let end = if value.starts_with('[') {
+                    value.find(']').map(|i| i + 1).unwrap_or(value.len())
+                } else {
+                    value.find(',').unwrap_or(value.len())
+                };
+                return Some(&value[..end]);
+            }
+        }
+
+        search_from = position + keyword.len();
+    }
+
+    None
+}
+
+fn meson_strings(value: &str) -> Vec<String> {
+    value
+        .split('\'')
+        .skip(1)
+        .step_by(2)
+        .map(str::to_string)
+        .collect()
+}
+
+fn strip_email(person: &str) -> String {
+    person
+        .split('<')
+        .next()
+        .unwrap_or(person)
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TestDir;
+
+    #[test]
+    fn meson_keeps_hash_inside_strings() {
+        let content = "# projeto\nproject('my#app', 'c', # linguagem\n  version: '1.0#beta', license: ['MIT', 'GPL-3.0'])\n";
+        let manifest = parse_meson(Path::new("meson.build"), content).unwrap();
+        assert_eq!(manifest.name, "my#app");
+        assert_eq!(manifest.version.as_deref(), Some("1.0#beta"));
+        assert_eq!(manifest.license.as_deref(), Some("MIT AND GPL-3.0"));
+    }
+
+    #[test]
+    fn meson_comment_strip_handles_escaped_quotes() {
+        assert_eq!(strip_meson_comment("x = 'it\\'s # not' # comment"), "x = 'it\\'s # not' ");
+        assert_eq!(strip_meson_comment("# only comment"), "");
+        assert_eq!(strip_meson_comment("no comment"), "no comment");
+    }
+
+    #[test]
+    fn cargo_root_package_must_produce_binary() {
+        let content = "[package]\nname = \"other\"\nversion = \"0.1.0\"\n";
+        let path = Path::new("/nonexistent/Cargo.toml");
+        assert!(parse_cargo(path, content, "other").is_some());
+        assert!(parse_cargo(path, content, "my-app").is_none());
+
+        let with_bin = "[package]\nname = \"other\"\n\n[[bin]]\nname = \"my_app\"\n";
+        assert_eq!(parse_cargo(path, with_bin, "my-app").unwrap().name, "other");
+    }
+
+    #[test]
+    fn cargo_root_package_with_src_bin() {
+        let dir = TestDir::new("project-src-bin");
+        let path = dir.write("Cargo.toml", "[package]\nname = \"tools\"\n");
+        dir.write("src/bin/helper.rs", "fn main() {}");
+        dir.write("src/bin/server/main.rs", "fn main() {}");
+        let content = fs::read_to_string(&path).unwrap();
+
+        assert!(parse_cargo(&path, &content, "helper").is_some());
+        assert!(parse_cargo(&path, &content, "server").is_some());
+        assert!(parse_cargo(&path, &content, "client").is_none());
+    }
+
+    #[test]
+    fn cargo_workspace_with_root_package_uses_member() {
+        let dir = TestDir::new("project-workspace");
+        let path = dir.write(
+            "Cargo.toml",
+            "[package]\nname = \"root\"\n\n[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.package]\nversion = \"2.0.0\"\n",
+        );
+        dir.write("crates/app/Cargo.toml", "[package]\nname = \"app\"\nversion.workspace = true\n");
+        let content = fs::read_to_string(&path).unwrap();
+
+        let manifest = parse_cargo(&path, &content, "app").unwrap();
+        assert_eq!(manifest.name, "app");
+        assert_eq!(manifest.version.as_deref(), Some("2.0.0"));
+        assert_eq!(manifest.path, dir.join("crates/app/Cargo.toml"));
+        assert_eq!(parse_cargo(&path, &content, "root").unwrap().name, "root");
+    }
+}
@@ -0,0 +1,106 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::appimage::BuildOptions;
+use crate::AppImageMetadata;
+
+// Receita de um projeto: metadados do aplicativo, pasta de saída e opções da
+// geração, gravados em TOML. Caminhos relativos partem da pasta da receita
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Recipe {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) output_folder: Option<PathBuf>,
+    pub(crate) app: AppImageMetadata,
+    pub(crate) build: BuildOptions,
+}
+
+impl Recipe {
+    pub(crate) fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let mut recipe: Recipe = toml::from_str(&content)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+
+        let base = recipe_dir(path);
+        recipe.map_paths(|p| if p.is_relative() { base.join(p) } else { p.to_path_buf() });
+        Ok(recipe)
+    }
+
+    // Caminhos dentro da pasta da receita são gravados relativos a ela, para que
+    // o projeto possa ser movido ou versionado junto com a receita
+    pub(crate) fn save(&self, path: &Path) -> io::Result<()> {
+        let base = recipe_dir(path);
+        let mut recipe = self.clone();
+        recipe.map_paths(|p| match p.strip_prefix(&base) {
+            Ok(relative) if relative.as_os_str().is_empty() => PathBuf::from("."),
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => p.to_path_buf(),
+        });
+
+        let content = toml::to_string_pretty(&recipe)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+
+        // Arquivo temporário e rename, como nas configurações: uma falha durante a
+        // gravação não deixa a receita truncada
+        let mut temp = path.as_os_str().to_os_string();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        if let Err(err) = fs::write(&temp, content).and_then(|()| fs::rename(&temp, path)) {
+            let _ = fs::remove_file(&temp);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    fn map_paths(&mut self, map: impl Fn(&Path) -> PathBuf) {
+        let app = &mut self.app;
+        let text_paths = [
+            &mut app.binary_path,
+            &mut app.icon_path,
+            &mut app.desktop_file_path,
+            &mut app.metainfo_file_path,
+        ];
+        for path in text_paths {
+            if !path.is_empty() {
+                *path = map(Path::new(path.as_str())).to_string_lossy().to_string();
+            }
+        }
+
+        let paths = [&mut self.output_folder, &mut self.build.work_root, &mut self.build.runtime_file];
+        for path in paths.into_iter().flatten() {
+            *path = map(path);
+        }
+    }
+}
+
+fn recipe_dir(path: &Path) -> PathBuf {
+    path.parent().map(Path::to_path_buf).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TestDir;
+
+    #[test]
+    fn save_replaces_recipe_and_keeps_paths_relative() {
+        let dir = TestDir::new("recipe-save");
+        let path = dir.write("app.toml", "antigo");
+        let mut recipe = Recipe::default();
+        recipe.app.name = "Demo".to_string();
+        recipe.app.binary_path = dir.join("target/release/demo").to_string_lossy().to_string();
+        recipe.output_folder = Some(dir.path().to_path_buf());
+
+        recipe.save(&path).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("binary_path = \"target/release/demo\""), "{}", content);
+        assert!(content.contains("output_folder = \".\""), "{}", content);
+        assert_eq!(Recipe::load(&path).unwrap(), recipe);
+        // Só a receita fica na pasta, sem o temporário
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
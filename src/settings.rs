@@ -0,0 +1,129 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{gettext, gettext_f};
+
+// Quantas receitas manter em "Recentes"
+const MAX_RECENT_RECIPES: usize = 10;
+
+const SETTINGS_FILE_NAME: &str = "settings.toml";
+
+// Preferências da interface mantidas entre sessões, em
+// $XDG_CONFIG_HOME/appimage-creator/settings.toml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Settings {
+    // Mais recente primeiro
+    pub(crate) recent_recipes: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) last_binary_dir: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) last_icon_dir: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) last_output_dir: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) last_recipe_dir: Option<PathBuf>,
+    // Perfil ativo na última sessão
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) profile: Option<String>,
+    pub(crate) window: WindowSettings,
+    // Valores iniciais do formulário, lembrados da última geração ou receita salva
+    pub(crate) defaults: FormDefaults,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct WindowSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) width: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) height: Option<i32>,
+    pub(crate) maximized: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct FormDefaults {
+    pub(crate) author: String,
+    pub(crate) license: String,
+    pub(crate) website: String,
+}
+
+impl Settings {
+    pub(crate) fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("appimage-creator").join(SETTINGS_FILE_NAME))
+    }
+
+    // Sem arquivo (primeira execução) ou com arquivo inválido, usa os padrões
+    pub(crate) fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Settings::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content).unwrap_or_else(|err| {
+                eprintln!(
+                    "{}",
+                    gettext_f("Invalid settings in {path}: {error}", &[("path", &path.display()), ("error", &err)])
+                );
+                Settings::default()
+            }),
+            Err(_) => Settings::default(),
+        }
+    }
+
+    pub(crate) fn save(&self) -> io::Result<()> {
+        let path = Self::path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, gettext("configuration folder not found")))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = toml::to_string_pretty(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        // Arquivo temporário e rename, para uma falha não apagar as configurações
+        let temp = path.with_extension("toml.tmp");
+        fs::write(&temp, content)?;
+        fs::rename(&temp, &path)
+    }
+
+    // Grava ignorando falhas: perder uma preferência não deve interromper o uso
+    pub(crate) fn save_quietly(&self) {
+        if let Err(err) = self.save() {
+            eprintln!("{}", gettext_f("Failed to save the settings: {error}", &[("error", &err)]));
+        }
+    }
+
+    pub(crate) fn add_recent_recipe(&mut self, path: &Path) {
+        self.recent_recipes.retain(|recent| recent != path);
+        self.recent_recipes.insert(0, path.to_path_buf());
+        self.recent_recipes.truncate(MAX_RECENT_RECIPES);
+        self.last_recipe_dir = path.parent().map(Path::to_path_buf);
+    }
+
+    // Autor, licença e site preenchidos passam a ser os padrões; retorna se algo mudou
+    pub(crate) fn remember_defaults(&mut self, author: &str, license: &str, website: &str) -> bool {
+        let previous = self.defaults.clone();
+        for (field, value) in [
+            (&mut self.defaults.author, author),
+            (&mut self.defaults.license, license),
+            (&mut self.defaults.website, website),
+        ] {
+            if !value.trim().is_empty() {
+                *field = value.trim().to_string();
+            }
+        }
+        self.defaults != previous
+    }
+
+    // Receitas recentes que ainda existem
+    pub(crate) fn existing_recent_recipes(&self) -> Vec<PathBuf> {
+        self.recent_recipes.iter().filter(|path| path.is_file()).cloned().collect()
+    }
+}
+
+// Pasta de um arquivo escolhido, para abrir o próximo seletor no mesmo lugar
+pub(crate) fn parent_dir(path: &Path) -> Option<PathBuf> {
+    path.parent().filter(|dir| dir.is_dir()).map(Path::to_path_buf)
+}